@@ -1,4 +1,5 @@
-use reqwest::header::{HeaderMap, AUTHORIZATION};
+use log::{error, info};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use reqwest::Client;
 use serde_json::Value;
 
@@ -26,7 +27,7 @@ use serde_json::Value;
 ///
 /// # Example
 ///
-/// ```rust
+/// ```rust,ignore
 /// let access_token = "your_access_token";
 /// let profile_id = get_profile_id(&access_token).await?;
 /// println!("Profile ID: {}", profile_id);
@@ -41,33 +42,34 @@ pub async fn get_profile_id(
         base_url.unwrap_or_else(|| "https://api.linkedin.com".to_string())
     );
 
+    // The token is marked sensitive so that it never shows up in `Debug` output; neither
+    // headers nor the user info (which holds personal data) are logged.
+    let mut authorization: HeaderValue = format!("Bearer {}", access_token).parse()?;
+    authorization.set_sensitive(true);
     let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", access_token).parse()?);
+    headers.insert(AUTHORIZATION, authorization);
 
-    println!("Sending request to {}", url);
+    info!("Sending request to {}", url);
     let response = client.get(url).headers(headers).send().await?;
 
-    println!("Response status: {}", response.status());
+    info!("Response status: {}", response.status());
 
     if response.status().is_success() {
         let profile_info: Value = response.json().await?;
-        println!("User info: {:?}", profile_info);
 
         let profile_id = profile_info["sub"].as_str().unwrap_or("").to_string();
         if profile_id.is_empty() {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
+            Err(Box::new(std::io::Error::other(
                 "Profile ID not found",
             )))
         } else {
-            println!("Profile ID: {}", profile_id);
+            info!("Profile ID: {}", profile_id);
             Ok(profile_id)
         }
     } else {
         let error_text = response.text().await?;
-        println!("Failed to retrieve user info: {:?}", error_text);
-        Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
+        error!("Failed to retrieve user info: {:?}", error_text);
+        Err(Box::new(std::io::Error::other(
             format!("Failed to retrieve user info: {}", error_text),
         )))
     }
@@ -1,2 +1,2 @@
-pub(crate) mod connections;
-pub(crate) mod post;
+pub mod connections;
+pub mod post;
@@ -1,20 +1,23 @@
-use log::info;
-use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
-use reqwest::Client;
+use crate::models::account::account_urn;
+use log::{debug, error, info};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, Url};
+use serde::Serialize;
 
-/// Publishes an article on LinkedIn using the provided access token and profile ID.
+/// Publishes an article on LinkedIn using the provided access token on behalf of an account.
 ///
 /// # Arguments
 ///
-/// * `access_token` - A string slice that holds the LinkedIn API access token. This token must have the necessary permissions to publish content on behalf of the user.
-/// * `profile_id` - A string slice that holds the LinkedIn profile ID of the author of the article.
+/// * `access_token` - A string slice that holds the LinkedIn API access token. This token must have the necessary permissions to publish content on behalf of the account: `w_member_social` for a member, `w_organization_social` of an administrator of the page for an organization.
+/// * `author` - The account the article is published on behalf of: a member's profile ID, or an organization URN (see `account_urn`).
 /// * `title` - A string slice that contains the title of the article to be published.
 /// * `_content` - A string slice that contains the body of the article. Although currently not used, this parameter is meant for future expansion where article content can be directly embedded.
 ///
 /// # Returns
 ///
-/// This function returns a `Result<(), Box<dyn std::error::Error>>`.
-/// * If the article is published successfully, it returns `Ok(())`.
+/// This function returns a `Result<Option<String>, Box<dyn std::error::Error>>`.
+/// * If the article is published successfully, it returns the URN of the new share (from the
+///   `X-RestLi-Id` header, or the `id` of the response body), if LinkedIn sent one.
 /// * If the publication fails, it returns an `Err` with the associated error message.
 ///
 /// # Errors
@@ -25,38 +28,89 @@ use reqwest::Client;
 ///
 /// # Example
 ///
-/// ```rust
+/// ```rust,ignore
 /// let access_token = "your_access_token";
-/// let profile_id = "your_profile_id";
+/// let author = "urn:li:organization:2414183";
 /// let title = "My First Article with Rust and LinkedIn API";
 /// let content = "<h1>This is a Heading</h1><p>This is a paragraph of the article.</p>";
 ///
-/// publish_article(access_token, profile_id, title, content).await?;
+/// let urn = publish_article(access_token, author, title, content, None).await?;
 /// ```
 pub async fn publish_article(
     access_token: &str,
-    profile_id: &str,
+    author: &str,
     _title: &str,
     _content: &str,
     base_url: Option<&str>,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
     let client = Client::new();
-    let url = format!(
-        "{}/v2/ugcPosts",
-        base_url.unwrap_or("https://api.linkedin.com")
-    );
+    let url = ugc_posts_url(base_url);
 
-    let mut headers = HeaderMap::new();
-    headers.insert(AUTHORIZATION, format!("Bearer {}", access_token).parse()?);
+    let mut headers = authorization(access_token)?;
     headers.insert(CONTENT_TYPE, "application/json".parse()?);
 
-    let body = serde_json::json!({
-        "author": format!("urn:li:person:{}", profile_id),
+    let body = article_request_body(author, _content);
+
+    // Only the body is logged, never the headers: they carry the access token.
+    info!("Sending POST request to {} on behalf of {}", url, body["author"]);
+    debug!("Request body: {}", body);
+
+    let response = client.post(url).headers(headers).json(&body).send().await?;
+
+    if response.status().is_success() {
+        let header_urn = response
+            .headers()
+            .get("x-restli-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let urn = match header_urn {
+            Some(urn) => Some(urn),
+            None => response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|body| body["id"].as_str().map(str::to_string)),
+        };
+        info!("Article published successfully! {}", urn.as_deref().unwrap_or(""));
+        Ok(urn)
+    } else {
+        error!("Failed to publish article: {:?}", response.text().await?);
+        Err(Box::new(std::io::Error::other(
+            "Failed to publish article",
+        )))
+    }
+}
+
+/// Returns the URL of the LinkedIn endpoint `publish_article` posts to.
+///
+/// `base_url` is the base URL of the LinkedIn API, `None` for `https://api.linkedin.com`.
+pub fn ugc_posts_url(base_url: Option<&str>) -> String {
+    format!("{}/v2/ugcPosts", base_url.unwrap_or("https://api.linkedin.com"))
+}
+
+/// Builds the JSON body `publish_article` sends to LinkedIn to publish `content` on behalf
+/// of `author`, whose URN (a member's or an organization's, see `account_urn`) is the body's
+/// `author`.
+///
+/// This is also what the scheduler logs in dry-run mode, so the body logged there is exactly
+/// the one that would have been sent.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let body = article_request_body("your_profile_id", "Hello LinkedIn!");
+/// assert_eq!(body["author"], "urn:li:person:your_profile_id");
+/// let body = article_request_body("urn:li:organization:2414183", "We are hiring!");
+/// assert_eq!(body["author"], "urn:li:organization:2414183");
+/// ```
+pub fn article_request_body(author: &str, content: &str) -> serde_json::Value {
+    serde_json::json!({
+        "author": account_urn(author),
         "lifecycleState": "PUBLISHED",
         "specificContent": {
             "com.linkedin.ugc.ShareContent": {
                 "shareCommentary": {
-                    "text": _content,
+                    "text": content,
                 },
                 "shareMediaCategory": "NONE",
                 "media": []
@@ -65,20 +119,116 @@ pub async fn publish_article(
         "visibility": {
             "com.linkedin.ugc.MemberNetworkVisibility": "PUBLIC"
         }
-    });
-
-    info!("Sending POST request to LinkedIn with body: {:?}", body);
+    })
+}
 
-    let response = client.post(url).headers(headers).json(&body).send().await?;
+/// Deletes a share published by `publish_article`.
+///
+/// # Arguments
+///
+/// * `access_token` - The LinkedIn API access token, of the profile owning the share.
+/// * `urn` - The URN of the share, as returned by `publish_article` (e.g. `urn:li:share:123`).
+/// * `base_url` - Base URL of the LinkedIn API, `None` for `https://api.linkedin.com`.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or LinkedIn responds with a failure status code.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// delete_article(access_token, "urn:li:share:123", None).await?;
+/// ```
+pub async fn delete_article(
+    access_token: &str,
+    urn: &str,
+    base_url: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = resource_url(&ugc_posts_url(base_url), urn)?;
+    info!("Sending DELETE request to {}", url);
+    let headers = authorization(access_token)?;
+    let response = Client::new().delete(url).headers(headers).send().await?;
 
     if response.status().is_success() {
-        info!("Article published successfully!");
+        info!("Article {} deleted", urn);
         Ok(())
     } else {
-        println!("Failed to publish article: {:?}", response.text().await?);
-        Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Failed to publish article",
-        )))
+        let error_text = response.text().await?;
+        error!("Failed to delete article {}: {:?}", urn, error_text);
+        Err(Box::new(std::io::Error::other(format!("Failed to delete article: {}", error_text))))
+    }
+}
+
+/// Engagement counters of a LinkedIn share.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ArticleMetrics {
+    pub likes: u64,
+    pub comments: u64,
+}
+
+/// Fetches the engagement counters of a share published by `publish_article`, from the
+/// `socialActions` endpoint of the LinkedIn API.
+///
+/// # Arguments
+///
+/// * `access_token` - The LinkedIn API access token.
+/// * `urn` - The URN of the share, as returned by `publish_article`.
+/// * `base_url` - Base URL of the LinkedIn API, `None` for `https://api.linkedin.com`.
+///
+/// # Errors
+///
+/// Returns an error if the request fails, LinkedIn responds with a failure status code or the
+/// response is not JSON. Missing counters are read as 0.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let metrics = fetch_article_metrics(access_token, "urn:li:share:123", None).await?;
+/// println!("{} likes", metrics.likes);
+/// ```
+pub async fn fetch_article_metrics(
+    access_token: &str,
+    urn: &str,
+    base_url: Option<&str>,
+) -> Result<ArticleMetrics, Box<dyn std::error::Error>> {
+    let base_url = format!("{}/v2/socialActions", base_url.unwrap_or("https://api.linkedin.com"));
+    let url = resource_url(&base_url, urn)?;
+    info!("Sending GET request to {}", url);
+    let headers = authorization(access_token)?;
+    let response = Client::new().get(url).headers(headers).send().await?;
+
+    if response.status().is_success() {
+        let body: serde_json::Value = response.json().await?;
+        Ok(ArticleMetrics {
+            likes: body["likesSummary"]["totalLikes"].as_u64().unwrap_or(0),
+            comments: body["commentsSummary"]["aggregatedTotalComments"].as_u64().unwrap_or(0),
+        })
+    } else {
+        let error_text = response.text().await?;
+        error!("Failed to fetch the metrics of {}: {:?}", urn, error_text);
+        Err(Box::new(std::io::Error::other(format!("Failed to fetch metrics: {}", error_text))))
     }
 }
+
+/// Returns the URL of the resource `id` under `collection_url`, with `id` percent-encoded as
+/// LinkedIn expects for URNs (`urn:li:share:1` becomes `urn%3Ali%3Ashare%3A1`).
+fn resource_url(collection_url: &str, id: &str) -> Result<Url, Box<dyn std::error::Error>> {
+    let encoded: String = id
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+    Ok(Url::parse(&format!("{}/{}", collection_url, encoded))?)
+}
+
+/// Returns the headers authenticating a request with `access_token`, marked sensitive so that
+/// the token never shows up in `Debug` output.
+fn authorization(access_token: &str) -> Result<HeaderMap, Box<dyn std::error::Error>> {
+    let mut authorization: HeaderValue = format!("Bearer {}", access_token).parse()?;
+    authorization.set_sensitive(true);
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, authorization);
+    Ok(headers)
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Number of times `TestClock::advance` yields, so that the tasks it wakes up get to run.
+const SETTLE_YIELDS: usize = 64;
+
+/// Source of the current time for scheduling decisions.
+///
+/// The scheduler asks its clock whether posts are due, late, paced or in a blackout, and the
+/// web server whether a post is scheduled in the past. Production code uses `SystemClock`;
+/// tests use `TestClock` to publish "at 09:00" without waiting for it.
+///
+/// The scheduler still waits with `tokio::time::sleep`, so a clock must agree with tokio's
+/// timer: `TestClock` follows it, and pausing tokio's time pauses both.
+pub trait Clock: Send + Sync {
+    /// Returns the current UTC instant.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock starting at a given instant and moving with tokio's timer, for tests.
+///
+/// # Functionality
+///
+/// `now` is the start instant plus the time elapsed on tokio's clock since the `TestClock`
+/// was created. In a test running with tokio's time paused (`#[tokio::test(start_paused = true)]`),
+/// it only moves when the test calls `advance`, or when every task is waiting on a timer and
+/// tokio skips to the next one; the sleeps of the scheduler then end exactly when the clock
+/// reaches their deadline. Without pausing, it moves in real time from the start instant.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// #[tokio::test(start_paused = true)]
+/// async fn test_publishes_at_nine() {
+///     let clock = Arc::new(TestClock::new(Utc.with_ymd_and_hms(2024, 5, 10, 8, 59, 0).unwrap()));
+///     // ... start the scheduler with `clock` and a post scheduled at 09:00 ...
+///     clock.advance(Duration::from_secs(60)).await;
+///     // ... the post is published ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TestClock {
+    start: DateTime<Utc>,
+    started: Instant,
+}
+
+impl TestClock {
+    /// Returns a clock whose current time is `start`.
+    pub fn new(start: DateTime<Utc>) -> Self {
+        TestClock {
+            start,
+            started: Instant::now(),
+        }
+    }
+
+    /// Moves the clock, and tokio's timer, forward by `duration`, waking up the tasks whose
+    /// sleeps end in the meantime.
+    ///
+    /// It then yields to the runtime a number of times, so that the woken tasks can run until
+    /// they wait again before the caller checks what they did. That is enough for work done in
+    /// memory, such as the scheduler with the in-memory repositories; tasks waiting on real I/O
+    /// may need more time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if tokio's time is not paused.
+    pub async fn advance(&self, duration: Duration) {
+        tokio::time::advance(duration).await;
+        for _ in 0..SETTLE_YIELDS {
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> DateTime<Utc> {
+        let elapsed = chrono::Duration::from_std(self.started.elapsed()).unwrap_or(chrono::Duration::MAX);
+        self.start + elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Tests that a test clock starts at the given instant and moves with tokio's paused timer.
+    #[tokio::test(start_paused = true)]
+    async fn test_test_clock_follows_tokio_timer() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 10, 8, 59, 0).unwrap();
+        let clock = TestClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(60)).await;
+        assert_eq!(clock.now(), Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap());
+
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+        assert_eq!(clock.now(), Utc.with_ymd_and_hms(2024, 5, 10, 10, 0, 0).unwrap());
+    }
+}
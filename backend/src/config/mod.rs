@@ -1 +1,4 @@
-pub(crate) mod settings;
+pub mod database;
+pub mod reload;
+pub mod secret;
+pub mod settings;
@@ -0,0 +1,238 @@
+use crate::config::settings::{ConfigError, Settings};
+use log::{error, info, warn};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use toml::Value;
+
+/// How often the configuration file's modification time is checked.
+pub const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Settings shared by the tasks of a binary.
+///
+/// Readers call `settings.borrow().clone()` to get the current `Arc<Settings>`, and can
+/// await `settings.changed()` to be woken up by a reload. A reload replaces the whole
+/// `Arc`, so a reader always sees a complete, validated set of settings.
+pub type SharedSettings = watch::Receiver<Arc<Settings>>;
+
+/// Sections that are only read at startup: a change is logged but needs a restart.
+const RESTART_REQUIRED: &[&str] = &["http", "cors", "database"];
+
+/// Returns `SharedSettings` that never change, e.g. for tests.
+pub fn fixed(settings: Settings) -> SharedSettings {
+    watch::channel(Arc::new(settings)).1
+}
+
+/// Starts watching the configuration and returns the shared settings.
+///
+/// # Functionality
+///
+/// Spawns a task that reloads the settings with `Settings::load` when the configuration
+/// file's modification time changes (checked every `CONFIG_WATCH_INTERVAL`) or when the
+/// process receives `SIGHUP`. Each reload goes through `apply_reload`: valid settings are
+/// swapped in atomically and the differences are logged, while invalid settings are
+/// reported and the current ones are kept.
+///
+/// # Parameters
+///
+/// - `initial`: The settings loaded at startup.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// let settings = watch_settings(Settings::load()?);
+/// let poll_interval = settings.borrow().scheduler.poll_interval_secs;
+/// ```
+pub fn watch_settings(initial: Settings) -> SharedSettings {
+    let (sender, receiver) = watch::channel(Arc::new(initial));
+    let path = Settings::config_path();
+
+    tokio::spawn(async move {
+        let mut hangup = hangup_signal();
+        let mut modified = modified_time(&path);
+        let mut interval = tokio::time::interval(CONFIG_WATCH_INTERVAL);
+
+        loop {
+            let trigger = tokio::select! {
+                _ = interval.tick() => {
+                    let current = modified_time(&path);
+                    if current == modified {
+                        continue;
+                    }
+                    modified = current;
+                    "configuration file change"
+                }
+                _ = next_hangup(&mut hangup) => "SIGHUP",
+            };
+
+            info!("Reloading settings after {}", trigger);
+            apply_reload(&sender, Settings::load());
+            if sender.is_closed() {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Swaps in reloaded settings if they are valid.
+///
+/// # Functionality
+///
+/// - Invalid settings are logged with every invalid key and the current settings are kept.
+/// - Settings identical to the current ones are ignored.
+/// - Otherwise the new settings replace the current ones in a single step, and each changed
+///   key is logged by `diff` (secrets are not printed). Changes to sections that are only
+///   read at startup are logged as needing a restart.
+///
+/// # Returns
+///
+/// Returns `true` if the settings were replaced.
+pub fn apply_reload(sender: &watch::Sender<Arc<Settings>>, reloaded: Result<Settings, ConfigError>) -> bool {
+    let new = match reloaded {
+        Ok(new) => new,
+        Err(e) => {
+            error!("Keeping the current settings: {}", e);
+            return false;
+        }
+    };
+
+    let changes = diff(&sender.borrow(), &new);
+    if changes.is_empty() {
+        info!("Settings unchanged");
+        return false;
+    }
+
+    for change in &changes {
+        info!("Setting changed: {}", change);
+        if RESTART_REQUIRED.iter().any(|section| change.starts_with(&format!("{}.", section))) {
+            warn!("{} takes effect after a restart", change.split(':').next().unwrap_or_default());
+        }
+    }
+    sender.send_replace(Arc::new(new));
+    true
+}
+
+/// Describes the differences between two settings, one line per changed key.
+///
+/// Lines have the form `key: old -> new`. Secrets are reported as changed without their
+/// values, and the password of the database URI is redacted.
+pub fn diff(old: &Settings, new: &Settings) -> Vec<String> {
+    let old_values = flatten(&old.redacted());
+    let new_values = flatten(&new.redacted());
+
+    let mut changes = Vec::new();
+    if old.access_token != new.access_token {
+        changes.push("access_token: changed".to_string());
+    }
+    if old.database.url != new.database.url && old_values.get("database.url") == new_values.get("database.url") {
+        changes.push("database.url: changed".to_string());
+    }
+
+    let keys: std::collections::BTreeSet<&String> = old_values.keys().chain(new_values.keys()).collect();
+    for key in keys.into_iter().filter(|key| *key != "access_token") {
+        let (old_value, new_value) = (old_values.get(key), new_values.get(key));
+        if old_value != new_value {
+            let show = |value: Option<&String>| value.cloned().unwrap_or_else(|| "(unset)".to_string());
+            changes.push(format!("{}: {} -> {}", key, show(old_value), show(new_value)));
+        }
+    }
+    changes
+}
+
+/// Flattens settings into `section.key` -> value, with values rendered as TOML.
+fn flatten(settings: &Settings) -> BTreeMap<String, String> {
+    fn walk(prefix: &str, value: &Value, values: &mut BTreeMap<String, String>) {
+        match value {
+            Value::Table(table) => {
+                for (key, value) in table {
+                    let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                    walk(&path, value, values);
+                }
+            }
+            value => {
+                values.insert(prefix.to_string(), value.to_string());
+            }
+        }
+    }
+
+    let mut values = BTreeMap::new();
+    if let Ok(value) = Value::try_from(settings) {
+        walk("", &value, &mut values);
+    }
+    values
+}
+
+fn modified_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(unix)]
+type HangupSignal = Option<tokio::signal::unix::Signal>;
+
+#[cfg(not(unix))]
+type HangupSignal = ();
+
+#[cfg(unix)]
+fn hangup_signal() -> HangupSignal {
+    use tokio::signal::unix::{signal, SignalKind};
+    signal(SignalKind::hangup())
+        .inspect_err(|e| error!("Could not listen for SIGHUP: {}", e))
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> HangupSignal {}
+
+/// Waits for the next `SIGHUP`; never completes where the signal is not available.
+#[cfg(unix)]
+async fn next_hangup(hangup: &mut HangupSignal) {
+    match hangup {
+        Some(signal) => {
+            signal.recv().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(not(unix))]
+async fn next_hangup(_hangup: &mut HangupSignal) {
+    std::future::pending().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::secret::Secret;
+
+    /// Tests that valid settings are swapped in and reported key by key, without secrets.
+    #[test]
+    fn test_valid_reload_is_applied() {
+        let (sender, receiver) = watch::channel(Arc::new(Settings::default()));
+        let mut new = Settings::default();
+        new.scheduler.poll_interval_secs = 5;
+        new.access_token = Secret::new("new-token");
+
+        assert_eq!(
+            diff(&Settings::default(), &new),
+            vec!["access_token: changed", "scheduler.poll_interval_secs: 20 -> 5"]
+        );
+        assert!(apply_reload(&sender, Ok(new.clone())));
+        assert_eq!(*receiver.borrow().clone(), new);
+    }
+
+    /// Tests that invalid or identical settings leave the current settings in place.
+    #[test]
+    fn test_invalid_reload_keeps_current_settings() {
+        let (sender, receiver) = watch::channel(Arc::new(Settings::default()));
+        let current = receiver.borrow().clone();
+
+        let invalid = Settings::from_sources(Some("[scheduler]\npoll_interval_secs = 0"), Vec::new());
+        assert!(!apply_reload(&sender, invalid));
+        assert!(!apply_reload(&sender, Ok(Settings::default())));
+
+        assert!(Arc::ptr_eq(&current, &receiver.borrow()));
+    }
+}
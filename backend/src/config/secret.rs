@@ -0,0 +1,304 @@
+use crate::config::settings::Settings;
+use crate::models::secret::EncryptedSecret;
+use crate::repository::secrets::SecretRepository;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::{info, warn};
+use mongodb::bson::DateTime as BsonDateTime;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::error::Error;
+use std::fmt;
+
+/// Environment variable holding the base64-encoded 256-bit key of the secret store.
+///
+/// The key is deliberately not a setting: it must never be written to `config.toml`
+/// or printed with `--print-config`.
+pub const SECRET_KEY_ENV: &str = "LINKEDIN_AUTOMATION_SECRET_KEY";
+
+/// Name of the LinkedIn access token in the secret store.
+pub const ACCESS_TOKEN_SECRET: &str = "linkedin_access_token";
+
+/// Text shown instead of a secret value.
+pub const REDACTED: &str = "<redacted>";
+
+/// A secret value, such as an access token.
+///
+/// `Debug`, `Display` and `Serialize` print `<redacted>` instead of the value (or an
+/// empty string for an empty secret), so a secret cannot leak through logs, `{:?}` of
+/// a struct holding it, or `--print-config`. The value is only available through
+/// `Secret::expose`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Secret(value.into())
+    }
+
+    /// Returns the secret value. Callers must not log it.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns whether `candidate` is this secret, in a time that does not depend on where
+    /// they differ, so that a token cannot be guessed one character at a time. An empty
+    /// secret matches nothing.
+    pub fn matches(&self, candidate: &str) -> bool {
+        let expected = digest(&SHA256, self.0.as_bytes());
+        let actual = digest(&SHA256, candidate.as_bytes());
+        let difference = expected.as_ref().iter().zip(actual.as_ref()).fold(0, |acc, (a, b)| acc | (a ^ b));
+        !self.is_empty() && difference == 0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", if self.is_empty() { "" } else { REDACTED })
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.is_empty() { "" } else { REDACTED })
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(if self.is_empty() { "" } else { REDACTED })
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Secret)
+    }
+}
+
+/// Encrypts and decrypts the secrets kept in the database with AES-256-GCM.
+pub struct SecretCipher {
+    key: LessSafeKey,
+    random: SystemRandom,
+}
+
+impl SecretCipher {
+    /// Creates a cipher from a base64-encoded 256-bit key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not valid base64 or is not 32 bytes long.
+    pub fn from_base64(key: &str) -> Result<Self, Box<dyn Error>> {
+        let bytes = STANDARD
+            .decode(key.trim())
+            .map_err(|_| format!("{} must be base64 encoded", SECRET_KEY_ENV))?;
+        let key = UnboundKey::new(&AES_256_GCM, &bytes)
+            .map_err(|_| format!("{} must be a 256-bit (32 bytes) key", SECRET_KEY_ENV))?;
+        Ok(SecretCipher {
+            key: LessSafeKey::new(key),
+            random: SystemRandom::new(),
+        })
+    }
+
+    /// Creates a cipher from `LINKEDIN_AUTOMATION_SECRET_KEY`, or returns `None` if it is not set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable is set to an invalid key.
+    pub fn from_env() -> Result<Option<Self>, Box<dyn Error>> {
+        match std::env::var(SECRET_KEY_ENV) {
+            Ok(key) => SecretCipher::from_base64(&key).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Generates a new random key, base64 encoded, suitable for `LINKEDIN_AUTOMATION_SECRET_KEY`.
+    pub fn generate_key() -> Result<String, Box<dyn Error>> {
+        let mut key = [0u8; 32];
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| "Could not generate a random key")?;
+        Ok(STANDARD.encode(key))
+    }
+
+    /// Encrypts `value` as the secret called `name`, with a fresh random nonce.
+    pub fn encrypt(&self, name: &str, value: &Secret) -> Result<EncryptedSecret, Box<dyn Error>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.random.fill(&mut nonce).map_err(|_| "Could not generate a nonce")?;
+
+        let mut ciphertext = value.expose().as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(name.as_bytes()), &mut ciphertext)
+            .map_err(|_| "Could not encrypt the secret")?;
+
+        Ok(EncryptedSecret {
+            name: name.to_string(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+            updated_at: BsonDateTime::now(),
+        })
+    }
+
+    /// Decrypts a stored secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the secret was encrypted with another key, was tampered with,
+    /// or was stored under another name.
+    pub fn decrypt(&self, secret: &EncryptedSecret) -> Result<Secret, Box<dyn Error>> {
+        let invalid = || format!("Secret {} cannot be decrypted with the configured key", secret.name);
+        let nonce: [u8; NONCE_LEN] = STANDARD
+            .decode(&secret.nonce)
+            .ok()
+            .and_then(|nonce| nonce.try_into().ok())
+            .ok_or_else(invalid)?;
+        let mut ciphertext = STANDARD.decode(&secret.ciphertext).map_err(|_| invalid())?;
+
+        let plaintext = self
+            .key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(secret.name.as_bytes()),
+                &mut ciphertext,
+            )
+            .map_err(|_| invalid())?;
+        let value = String::from_utf8(plaintext.to_vec()).map_err(|_| invalid())?;
+        Ok(Secret(value))
+    }
+}
+
+/// Returns the LinkedIn access token from the first configured source.
+///
+/// # Functionality
+///
+/// The sources are tried in this order:
+///
+/// 1. `access_token_file`: the file's contents, with surrounding whitespace trimmed
+///    (e.g. a Docker or Kubernetes secret mounted as a file).
+/// 2. `access_token`: set in `config.toml` or, preferably, with the
+///    `LINKEDIN_AUTOMATION__ACCESS_TOKEN` environment variable.
+/// 3. The encrypted secret store in the database, when `LINKEDIN_AUTOMATION_SECRET_KEY`
+///    is set (see the `secrets` binary).
+///
+/// # Parameters
+///
+/// - `settings`: The loaded settings.
+/// - `store`: The secret store of the configured database.
+/// - `cipher`: The cipher built from `LINKEDIN_AUTOMATION_SECRET_KEY`, if set.
+///
+/// # Errors
+///
+/// Returns an error if the token file cannot be read, the stored secret cannot be
+/// decrypted, or no source provides a token.
+pub async fn resolve_access_token(
+    settings: &Settings,
+    store: &dyn SecretRepository,
+    cipher: Option<&SecretCipher>,
+) -> Result<Secret, Box<dyn Error>> {
+    if let Some(path) = &settings.access_token_file {
+        let token = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read access_token_file {}: {}", path, e))?;
+        info!("Using the access token from {}", path);
+        return Ok(Secret::new(token.trim()));
+    }
+
+    if !settings.access_token.is_empty() {
+        warn!("Using the access token from the settings; prefer access_token_file or the secret store");
+        return Ok(settings.access_token.clone());
+    }
+
+    if let Some(cipher) = cipher {
+        if let Some(secret) = store.get(ACCESS_TOKEN_SECRET).await? {
+            info!("Using the access token from the encrypted secret store");
+            return cipher.decrypt(&secret);
+        }
+    }
+
+    Err(format!(
+        "No access token: set access_token_file, LINKEDIN_AUTOMATION__ACCESS_TOKEN, or store it with the `secrets` binary and set {}",
+        SECRET_KEY_ENV
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::memory::InMemorySecretRepository;
+
+    fn cipher() -> SecretCipher {
+        SecretCipher::from_base64(&SecretCipher::generate_key().unwrap()).unwrap()
+    }
+
+    /// Tests that secrets never appear in debug or serialized output.
+    #[test]
+    fn test_secret_is_redacted() {
+        let secret = Secret::new("AQX-secret-token");
+
+        assert_eq!(format!("{:?}", secret), "\"<redacted>\"");
+        assert_eq!(secret.to_string(), REDACTED);
+        assert_eq!(serde_json::to_string(&secret).unwrap(), "\"<redacted>\"");
+        assert_eq!(secret.expose(), "AQX-secret-token");
+    }
+
+    /// Tests that a secret only matches its own value, and that an empty secret matches nothing.
+    #[test]
+    fn test_secret_matches() {
+        let secret = Secret::new("AQX-secret-token");
+
+        assert!(secret.matches("AQX-secret-token"));
+        assert!(!secret.matches("AQX-secret-tokem"));
+        assert!(!secret.matches("AQX"));
+        assert!(!Secret::default().matches(""));
+    }
+
+    /// Tests that a stored secret only decrypts with the same key and under the same name.
+    #[test]
+    fn test_encryption_round_trip() {
+        let cipher = cipher();
+        let encrypted = cipher.encrypt(ACCESS_TOKEN_SECRET, &Secret::new("token")).unwrap();
+
+        assert!(!encrypted.ciphertext.contains("token"));
+        assert_eq!(cipher.decrypt(&encrypted).unwrap().expose(), "token");
+        assert!(self::cipher().decrypt(&encrypted).is_err());
+
+        let renamed = EncryptedSecret {
+            name: "other".to_string(),
+            ..encrypted
+        };
+        assert!(cipher.decrypt(&renamed).is_err());
+    }
+
+    /// Tests the order of the access token sources.
+    #[tokio::test]
+    async fn test_resolve_access_token() {
+        let store = InMemorySecretRepository::new();
+        let cipher = cipher();
+        let mut settings = Settings::default();
+
+        assert!(resolve_access_token(&settings, &store, Some(&cipher)).await.is_err());
+
+        store
+            .put(cipher.encrypt(ACCESS_TOKEN_SECRET, &Secret::new("from-store")).unwrap())
+            .await
+            .unwrap();
+        let token = resolve_access_token(&settings, &store, Some(&cipher)).await.unwrap();
+        assert_eq!(token.expose(), "from-store");
+
+        settings.access_token = Secret::new("from-env");
+        let token = resolve_access_token(&settings, &store, Some(&cipher)).await.unwrap();
+        assert_eq!(token.expose(), "from-env");
+
+        let path = std::env::temp_dir().join(format!("token-{}", mongodb::bson::oid::ObjectId::new()));
+        std::fs::write(&path, "from-file\n").unwrap();
+        settings.access_token_file = Some(path.to_string_lossy().to_string());
+        let token = resolve_access_token(&settings, &store, Some(&cipher)).await.unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(token.expose(), "from-file");
+    }
+}
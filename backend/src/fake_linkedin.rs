@@ -0,0 +1,506 @@
+use chrono::{DateTime, Utc};
+use linkedin_automation::shutdown::Shutdown;
+use log::{info, warn};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use warp::filters::BoxedFilter;
+use warp::http::StatusCode;
+use warp::reply::Response;
+use warp::{Filter, Rejection, Reply};
+
+/// Command-line usage of the fake LinkedIn API server.
+const USAGE: &str = "Usage: fake_linkedin [--port 8090] [--profile-id fake-profile] \
+[--error-rate 0.1] [--rate-limit-rate 0.05] [--latency-ms 500]";
+
+/// Seconds a client is told to wait in the `Retry-After` header of an injected 429.
+const RETRY_AFTER_SECS: u64 = 1;
+
+/// Options of the fake LinkedIn API server, read from the command line.
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    /// Port listened on, on every interface.
+    port: u16,
+    /// The `sub` returned by `/v2/userinfo`, i.e. the profile owning any access token.
+    profile_id: String,
+    /// Fraction of the API requests answered with `500 Internal Server Error`.
+    error_rate: f64,
+    /// Fraction of the API requests answered with `429 Too Many Requests`.
+    rate_limit_rate: f64,
+    /// Delay added before answering each API request.
+    latency: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            port: 8090,
+            profile_id: "fake-profile".to_string(),
+            error_rate: 0.0,
+            rate_limit_rate: 0.0,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+impl Options {
+    /// Parses the command-line arguments, without the program name.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message to print if an option is unknown, has no value or an invalid one,
+    /// or if the failure rates add up to more than 1.
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(option) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{} needs a value\n{}", option, USAGE))?;
+            let invalid = || format!("Invalid value {:?} for {}\n{}", value, option, USAGE);
+            match option.as_str() {
+                "--port" => options.port = value.parse().map_err(|_| invalid())?,
+                "--profile-id" => options.profile_id = value.clone(),
+                "--error-rate" => options.error_rate = parse_rate(&value).ok_or_else(invalid)?,
+                "--rate-limit-rate" => options.rate_limit_rate = parse_rate(&value).ok_or_else(invalid)?,
+                "--latency-ms" => {
+                    let millis: u64 = value.parse().map_err(|_| invalid())?;
+                    options.latency = Duration::from_millis(millis);
+                }
+                _ => return Err(format!("Unknown option {}\n{}", option, USAGE)),
+            }
+        }
+        if options.error_rate + options.rate_limit_rate > 1.0 {
+            return Err(format!("--error-rate and --rate-limit-rate add up to more than 1\n{}", USAGE));
+        }
+        Ok(options)
+    }
+}
+
+/// Parses a fraction between 0 and 1.
+fn parse_rate(value: &str) -> Option<f64> {
+    value.parse().ok().filter(|rate| (0.0..=1.0).contains(rate))
+}
+
+/// A post stored by the fake API, as listed by `GET /_fake/shares`.
+#[derive(Debug, Clone, Serialize)]
+struct FakeShare {
+    urn: String,
+    author: String,
+    /// The request body the post was created with.
+    body: serde_json::Value,
+    created_at: DateTime<Utc>,
+}
+
+/// In-memory state of the fake LinkedIn API.
+struct FakeLinkedIn {
+    options: Options,
+    shares: Mutex<BTreeMap<String, FakeShare>>,
+    /// Size of the uploaded assets, keyed by asset ID; `None` until the upload is done.
+    assets: Mutex<BTreeMap<String, Option<usize>>>,
+    next_id: AtomicU64,
+    random: SystemRandom,
+}
+
+impl FakeLinkedIn {
+    fn new(options: Options) -> Self {
+        FakeLinkedIn {
+            options,
+            shares: Mutex::new(BTreeMap::new()),
+            assets: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(1),
+            random: SystemRandom::new(),
+        }
+    }
+
+    /// Returns a new ID, unique within this server.
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Returns a random number in `0.0..1.0`.
+    fn roll(&self) -> f64 {
+        let mut bytes = [0u8; 4];
+        if self.random.fill(&mut bytes).is_err() {
+            return 1.0;
+        }
+        u32::from_be_bytes(bytes) as f64 / (u32::MAX as f64 + 1.0)
+    }
+
+    /// Waits for the configured latency, then returns the injected failure answering the
+    /// request, if any: a 500 with probability `error_rate`, a 429 with probability
+    /// `rate_limit_rate`.
+    async fn injected_failure(&self) -> Option<Response> {
+        if !self.options.latency.is_zero() {
+            tokio::time::sleep(self.options.latency).await;
+        }
+        let roll = self.roll();
+        if roll < self.options.error_rate {
+            warn!("Injecting a 500 error");
+            return Some(error(StatusCode::INTERNAL_SERVER_ERROR, "Injected server error"));
+        }
+        if roll < self.options.error_rate + self.options.rate_limit_rate {
+            warn!("Injecting a 429 error");
+            let reply = error(StatusCode::TOO_MANY_REQUESTS, "Injected throttling");
+            return Some(warp::reply::with_header(reply, "retry-after", RETRY_AFTER_SECS.to_string()).into_response());
+        }
+        None
+    }
+
+    /// Stores a post created with `body` and returns the 201 response carrying its URN.
+    fn create_share(&self, body: serde_json::Value) -> Response {
+        let Some(author) = body["author"].as_str().filter(|author| !author.is_empty()) else {
+            return error(StatusCode::UNPROCESSABLE_ENTITY, "/author is required");
+        };
+        let urn = format!("urn:li:share:{}", self.next_id());
+        info!("Created {} on behalf of {}", urn, author);
+        let share = FakeShare {
+            urn: urn.clone(),
+            author: author.to_string(),
+            body,
+            created_at: Utc::now(),
+        };
+        self.shares.lock().unwrap().insert(urn.clone(), share);
+
+        let reply = warp::reply::with_status(warp::reply::json(&serde_json::json!({ "id": urn })), StatusCode::CREATED);
+        warp::reply::with_header(reply, "x-restli-id", urn).into_response()
+    }
+
+    /// Deletes the post with the given URN.
+    fn delete_share(&self, urn: &str) -> Response {
+        match self.shares.lock().unwrap().remove(urn) {
+            Some(_) => {
+                info!("Deleted {}", urn);
+                StatusCode::NO_CONTENT.into_response()
+            }
+            None => error(StatusCode::NOT_FOUND, &format!("{} does not exist", urn)),
+        }
+    }
+}
+
+/// Returns an error response in the format of the LinkedIn API.
+fn error(status: StatusCode, message: &str) -> Response {
+    let body = serde_json::json!({ "status": status.as_u16(), "message": message });
+    warp::reply::with_status(warp::reply::json(&body), status).into_response()
+}
+
+/// Returns whether the `Authorization` header holds a bearer token. Any token is accepted.
+fn is_authorized(authorization: &Option<String>) -> bool {
+    authorization
+        .as_deref()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| !token.trim().is_empty())
+}
+
+/// Decodes a percent-encoded path segment, such as an encoded URN (`urn%3Ali%3Ashare%3A1`).
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes.get(index + 1..index + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match (bytes[index], hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Builds a route of the emulated API: the request must carry a bearer token, then failures
+/// are injected, then `handle` answers it.
+fn api<F, T>(filter: F, state: &Arc<FakeLinkedIn>, handle: fn(&FakeLinkedIn, T) -> Response) -> BoxedFilter<(Response,)>
+where
+    F: Filter<Extract = (T,), Error = Rejection> + Clone + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    let state = Arc::clone(state);
+    filter
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(move |request: T, authorization: Option<String>| {
+            let state = Arc::clone(&state);
+            async move {
+                if !is_authorized(&authorization) {
+                    return Ok::<_, Rejection>(error(StatusCode::UNAUTHORIZED, "Invalid access token"));
+                }
+                if let Some(failure) = state.injected_failure().await {
+                    return Ok(failure);
+                }
+                Ok(handle(&state, request))
+            }
+        })
+        .boxed()
+}
+
+/// Returns every route of the fake LinkedIn API.
+///
+/// # Functionality
+///
+/// Emulates the endpoints used by the scheduler, and a few more for the frontend:
+///
+/// - `GET /v2/userinfo`: the profile owning the access token, `--profile-id`.
+/// - `POST /v2/ugcPosts` and `POST /rest/posts`: stores a post and returns its URN in the
+///   `X-RestLi-Id` header and the `id` of the body.
+/// - `DELETE /v2/ugcPosts/{urn}` and `DELETE /rest/posts/{urn}`: deletes a post.
+/// - `GET /v2/socialActions/{urn}`: made-up likes and comments of a post.
+/// - `POST /v2/assets?action=registerUpload`: registers an asset and returns the URL to `PUT`
+///   its content to (`/_fake/upload/{id}`, on this server).
+///
+/// These endpoints accept any bearer token and are subject to failure injection. Two more
+/// endpoints help inspecting the server: `GET /_fake/shares` lists the stored posts and
+/// `PUT /_fake/upload/{id}` receives asset uploads.
+fn routes(state: Arc<FakeLinkedIn>) -> BoxedFilter<(Response,)> {
+    let userinfo = api(warp::get().and(warp::path!("v2" / "userinfo")).map(|| ()), &state, |state, ()| {
+        let profile = serde_json::json!({
+            "sub": state.options.profile_id,
+            "name": "Fake User",
+            "given_name": "Fake",
+            "family_name": "User",
+            "email": "fake.user@example.com",
+        });
+        warp::reply::json(&profile).into_response()
+    });
+
+    let ugc_posts = warp::post().and(warp::path!("v2" / "ugcPosts")).and(warp::body::json());
+    let posts = warp::post().and(warp::path!("rest" / "posts")).and(warp::body::json());
+    let create = api(ugc_posts.or(posts).unify(), &state, |state, body| state.create_share(body));
+
+    let delete_ugc_post = warp::delete().and(warp::path!("v2" / "ugcPosts" / String));
+    let delete_post = warp::delete().and(warp::path!("rest" / "posts" / String));
+    let delete = api(delete_ugc_post.or(delete_post).unify(), &state, |state, urn: String| {
+        state.delete_share(&percent_decode(&urn))
+    });
+
+    let social_actions = warp::get().and(warp::path!("v2" / "socialActions" / String));
+    let metrics = api(social_actions, &state, |state, urn: String| {
+        let urn = percent_decode(&urn);
+        if !state.shares.lock().unwrap().contains_key(&urn) {
+            return error(StatusCode::NOT_FOUND, &format!("{} does not exist", urn));
+        }
+        let id: u64 = urn.rsplit(':').next().and_then(|id| id.parse().ok()).unwrap_or(0);
+        let actions = serde_json::json!({
+            "target": urn,
+            "likesSummary": { "totalLikes": id * 7 % 100 },
+            "commentsSummary": { "aggregatedTotalComments": id * 3 % 20 },
+        });
+        warp::reply::json(&actions).into_response()
+    });
+
+    let register_upload = warp::post()
+        .and(warp::path!("v2" / "assets"))
+        .and(warp::query::<BTreeMap<String, String>>())
+        .and(warp::header::<String>("host"));
+    let assets = api(register_upload.map(|query, host| (query, host)), &state, |state, request| {
+        let (query, host): (BTreeMap<String, String>, String) = request;
+        if query.get("action").map(String::as_str) != Some("registerUpload") {
+            return error(StatusCode::BAD_REQUEST, "Only action=registerUpload is supported");
+        }
+        let id = state.next_id().to_string();
+        state.assets.lock().unwrap().insert(id.clone(), None);
+        let upload = serde_json::json!({
+            "value": {
+                "asset": format!("urn:li:digitalmediaAsset:{}", id),
+                "uploadMechanism": {
+                    "com.linkedin.digitalmedia.uploading.MediaUploadHttpRequest": {
+                        "uploadUrl": format!("http://{}/_fake/upload/{}", host, id),
+                    }
+                }
+            }
+        });
+        warp::reply::json(&upload).into_response()
+    });
+
+    let upload_state = Arc::clone(&state);
+    let upload = warp::put()
+        .and(warp::path!("_fake" / "upload" / String))
+        .and(warp::body::bytes())
+        .map(move |id: String, content: bytes::Bytes| {
+            let mut assets = upload_state.assets.lock().unwrap();
+            match assets.get_mut(&id) {
+                Some(size) => {
+                    info!("Received {} bytes for asset {}", content.len(), id);
+                    *size = Some(content.len());
+                    StatusCode::CREATED.into_response()
+                }
+                None => error(StatusCode::NOT_FOUND, &format!("Asset {} was not registered", id)),
+            }
+        });
+
+    let list_state = Arc::clone(&state);
+    let list = warp::get().and(warp::path!("_fake" / "shares")).map(move || {
+        let shares: Vec<FakeShare> = list_state.shares.lock().unwrap().values().cloned().collect();
+        warp::reply::json(&shares).into_response()
+    });
+
+    userinfo
+        .or(create)
+        .unify()
+        .or(delete)
+        .unify()
+        .or(metrics)
+        .unify()
+        .or(assets)
+        .unify()
+        .or(upload)
+        .unify()
+        .or(list)
+        .unify()
+        .boxed()
+}
+
+/// Entry point of the fake LinkedIn API server, for local development without a real token.
+///
+/// # Functionality
+///
+/// Serves the routes of `routes` on `--port` (8090 by default), keeping everything in memory.
+/// Point the scheduler at it with `linkedin.api_base_url` and any access token. To see how the
+/// scheduler copes with an unreliable API, requests can be slowed down with `--latency-ms` and
+/// answered with 500s or 429s (with `Retry-After`) at random, with `--error-rate` and
+/// `--rate-limit-rate`, fractions between 0 and 1.
+///
+/// # Example Usage
+///
+/// ```bash
+/// cargo run --bin fake_linkedin -- --port 8090 --error-rate 0.1 --latency-ms 300
+///
+/// LINKEDIN_AUTOMATION__LINKEDIN__API_BASE_URL=http://localhost:8090 \
+/// LINKEDIN_AUTOMATION__ACCESS_TOKEN=fake-token cargo run --bin scheduler
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the options are invalid or the port cannot be bound.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let options = Options::parse(std::env::args().skip(1))?;
+    let address = SocketAddr::from(([0, 0, 0, 0], options.port));
+    info!("Fake LinkedIn API running on http://{}/ with {:?}", address, options);
+
+    let shutdown = Shutdown::listen();
+    let mut stop_accepting = shutdown.clone();
+    let (_, server) = warp::serve(routes(Arc::new(FakeLinkedIn::new(options))))
+        .try_bind_with_graceful_shutdown(address, async move { stop_accepting.requested().await })?;
+    server.await;
+
+    info!("Fake LinkedIn API stopped");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linkedin_automation::config::secret::Secret;
+    use linkedin_automation::publishing::linkedin::LinkedInPublisher;
+    use linkedin_automation::publishing::publisher::{Publication, Publisher};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    /// Tests the parsing of the command-line options.
+    #[test]
+    fn test_options() {
+        assert_eq!(Options::parse(args(&[])).unwrap(), Options::default());
+
+        let options = Options::parse(args(&["--port", "9000", "--error-rate", "0.25", "--latency-ms", "50"])).unwrap();
+        assert_eq!(options.port, 9000);
+        assert_eq!(options.error_rate, 0.25);
+        assert_eq!(options.latency, Duration::from_millis(50));
+
+        assert!(Options::parse(args(&["--error-rate", "1.5"])).is_err());
+        assert!(Options::parse(args(&["--error-rate", "0.6", "--rate-limit-rate", "0.6"])).is_err());
+        assert!(Options::parse(args(&["--port"])).is_err());
+        assert!(Options::parse(args(&["--verbose", "yes"])).is_err());
+    }
+
+    /// Tests that encoded URNs are decoded and invalid escapes kept as they are.
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("urn%3Ali%3Ashare%3A1"), "urn:li:share:1");
+        assert_eq!(percent_decode("urn:li:share:1"), "urn:li:share:1");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    /// Tests that requests need a bearer token and that failures are injected.
+    #[tokio::test]
+    async fn test_authorization_and_failure_injection() {
+        let route = routes(Arc::new(FakeLinkedIn::new(Options::default())));
+        let response = warp::test::request().path("/v2/userinfo").reply(&route).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        for (options, status) in [
+            (Options { error_rate: 1.0, ..Options::default() }, StatusCode::INTERNAL_SERVER_ERROR),
+            (Options { rate_limit_rate: 1.0, ..Options::default() }, StatusCode::TOO_MANY_REQUESTS),
+        ] {
+            let route = routes(Arc::new(FakeLinkedIn::new(options)));
+            let response = warp::test::request()
+                .path("/v2/userinfo")
+                .header("authorization", "Bearer fake-token")
+                .reply(&route)
+                .await;
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    /// Tests the asset upload flow: registering an upload, then uploading to the given URL.
+    #[tokio::test]
+    async fn test_asset_upload() {
+        let route = routes(Arc::new(FakeLinkedIn::new(Options::default())));
+        let response = warp::test::request()
+            .method("POST")
+            .path("/v2/assets?action=registerUpload")
+            .header("authorization", "Bearer fake-token")
+            .header("host", "localhost:8090")
+            .json(&serde_json::json!({ "registerUploadRequest": {} }))
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        let mechanism = &body["value"]["uploadMechanism"]["com.linkedin.digitalmedia.uploading.MediaUploadHttpRequest"];
+        let upload_url = mechanism["uploadUrl"].as_str().unwrap();
+        let path = upload_url.strip_prefix("http://localhost:8090").unwrap();
+
+        let response = warp::test::request().method("PUT").path(path).body("image").reply(&route).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response = warp::test::request().method("PUT").path("/_fake/upload/999").body("image").reply(&route).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    /// Tests the whole lifecycle of a post through the LinkedIn publisher used by the scheduler:
+    /// publishing on behalf of the token's owner, fetching metrics and deleting it.
+    #[tokio::test]
+    async fn test_linkedin_publisher_against_fake_api() {
+        let state = Arc::new(FakeLinkedIn::new(Options::default()));
+        let (address, server) = warp::serve(routes(Arc::clone(&state))).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let publisher = LinkedInPublisher::new(Secret::new("fake-token"), &format!("http://{}", address));
+        let publication = Publication {
+            title: "Title",
+            content: "Hello from the fake API".into(),
+            author: None,
+            media: &[],
+        };
+        let urn = publisher.publish(&publication).await.unwrap().unwrap();
+
+        let share = state.shares.lock().unwrap()[&urn].clone();
+        assert_eq!(share.author, "urn:li:person:fake-profile");
+        let metrics = publisher.fetch_metrics(&urn).await.unwrap();
+        assert!(metrics.likes.is_some());
+
+        publisher.delete(&urn).await.unwrap();
+        assert!(state.shares.lock().unwrap().is_empty());
+        assert!(publisher.delete(&urn).await.is_err());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod api;
+pub mod clock;
+pub mod config;
+pub mod models;
+pub mod problem;
+pub mod publishing;
+pub mod repository;
+pub mod scheduling;
+pub mod shutdown;
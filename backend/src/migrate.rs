@@ -0,0 +1,185 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use futures_util::TryStreamExt;
+use linkedin_automation::config::settings::{local_to_utc, Settings};
+use linkedin_automation::models::post::PostStatus;
+use linkedin_automation::repository::mongo::connect_database;
+use log::{error, info, warn};
+use mongodb::bson::{doc, Bson, DateTime as BsonDateTime, Document};
+
+/// Converts a legacy `scheduled_time` value into a native BSON `DateTime`.
+///
+/// # Functionality
+///
+/// Older versions of the web server stored `scheduled_time` as `Int64` milliseconds
+/// since the Unix epoch, while some tools inserted RFC 3339 strings or the raw
+/// `YYYY-MM-DD HH:MM` value received from the frontend. This function normalizes
+/// all of these representations:
+///
+/// - `DateTime`: returned unchanged.
+/// - `Int64` / `Int32` / `Double`: interpreted as milliseconds since the Unix epoch.
+/// - `String`: parsed as RFC 3339 first, then as `YYYY-MM-DD HH:MM` in the given `offset`.
+///
+/// # Parameters
+///
+/// - `value`: The stored `scheduled_time` value.
+/// - `offset`: The timezone offset used to interpret strings without offset information.
+///
+/// # Returns
+///
+/// Returns `Some(BsonDateTime)` if the value could be converted, or `None` if the
+/// value has an unsupported type or cannot be parsed.
+fn to_bson_datetime(value: &Bson, offset: FixedOffset) -> Option<BsonDateTime> {
+    match value {
+        Bson::DateTime(datetime) => Some(*datetime),
+        Bson::Int64(millis) => Some(BsonDateTime::from_millis(*millis)),
+        Bson::Int32(millis) => Some(BsonDateTime::from_millis(i64::from(*millis))),
+        Bson::Double(millis) if millis.is_finite() => Some(BsonDateTime::from_millis(*millis as i64)),
+        Bson::String(text) => DateTime::parse_from_rfc3339(text)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .ok()
+            .or_else(|| local_to_utc(text, "%Y-%m-%d %H:%M", offset).ok())
+            .map(BsonDateTime::from_chrono),
+        _ => None,
+    }
+}
+
+/// Entry point for the `posts` data migration.
+///
+/// # Functionality
+///
+/// Scans the posts collection of the configured MongoDB database for documents whose
+/// `scheduled_time` is not stored as a native BSON `DateTime` (int64/int32/double
+/// milliseconds or strings) and rewrites each of them with the converted value
+/// returned by `to_bson_datetime`. Documents that cannot be converted are left
+/// untouched and reported in the logs.
+///
+/// It then renames the legacy `"pending"` status to `"scheduled"`, the equivalent status
+/// of the `PostStatus` state machine.
+///
+/// The migration is idempotent: documents already holding a BSON `DateTime` are
+/// not matched by the filter, so running it several times is safe.
+///
+/// # Example Usage
+///
+/// The database and the timezone offset used for legacy strings come from the layered
+/// `Settings`. The MongoDB URI can also be passed as the first argument:
+///
+/// ```bash
+/// cargo run --bin migrate -- mongodb://localhost:27017
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid, if the
+/// connection to MongoDB fails, or if a query or update fails.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let settings = Settings::load().inspect_err(|e| error!("{}", e))?;
+    let mut database = settings.database.clone();
+    if let Some(uri) = std::env::args().nth(1) {
+        database.url = uri;
+    }
+    let offset = settings.timezone();
+
+    let db = connect_database(&database).await?;
+    let posts: mongodb::Collection<Document> = db.collection(&database.posts_collection);
+
+    let filter = doc! {
+        "scheduled_time": { "$type": ["long", "int", "double", "string"] }
+    };
+    let mut cursor = posts.find(filter).await?;
+
+    let mut converted = 0;
+    let mut skipped = 0;
+    while let Some(post) = cursor.try_next().await? {
+        let id = match post.get("_id") {
+            Some(id) => id.clone(),
+            None => continue,
+        };
+        let Some(value) = post.get("scheduled_time") else {
+            continue;
+        };
+
+        match to_bson_datetime(value, offset) {
+            Some(scheduled_time) => {
+                posts
+                    .update_one(
+                        doc! { "_id": id.clone() },
+                        doc! { "$set": { "scheduled_time": scheduled_time } },
+                    )
+                    .await?;
+                info!("Converted post {} from {:?} to {}", id, value, scheduled_time);
+                converted += 1;
+            }
+            None => {
+                warn!("Could not convert scheduled_time {:?} of post {}", value, id);
+                skipped += 1;
+            }
+        }
+    }
+
+    // Posts created before the status state machine used "pending" for what is now "scheduled".
+    let renamed = posts
+        .update_many(
+            doc! { "status": "pending" },
+            doc! { "$set": { "status": PostStatus::Scheduled.as_str() } },
+        )
+        .await?;
+    info!("Renamed status of {} post(s) from pending to scheduled", renamed.modified_count);
+
+    if skipped > 0 {
+        error!("{} post(s) could not be migrated and need manual review", skipped);
+    }
+    info!("Migration finished: {} converted, {} skipped", converted, skipped);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn brasilia() -> FixedOffset {
+        FixedOffset::west_opt(3 * 3600).unwrap()
+    }
+
+    /// Tests that every legacy representation is converted to the same instant.
+    ///
+    /// The instant used is 2024-05-10 12:00 UTC, which is 09:00 in Brasília time (UTC-3).
+    #[test]
+    fn test_legacy_values_are_converted() {
+        let expected = BsonDateTime::from_chrono(Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap());
+        let millis = expected.timestamp_millis();
+
+        let values = [
+            Bson::DateTime(expected),
+            Bson::Int64(millis),
+            Bson::Double(millis as f64),
+            Bson::String("2024-05-10T12:00:00Z".to_string()),
+            Bson::String("2024-05-10T09:00:00-03:00".to_string()),
+            Bson::String("2024-05-10 09:00".to_string()),
+        ];
+
+        for value in values {
+            assert_eq!(to_bson_datetime(&value, brasilia()), Some(expected), "value: {:?}", value);
+        }
+    }
+
+    /// Tests that unsupported or unparsable values are reported as not convertible.
+    #[test]
+    fn test_invalid_values_are_skipped() {
+        let values = [
+            Bson::String("tomorrow morning".to_string()),
+            Bson::Boolean(true),
+            Bson::Null,
+            Bson::Double(f64::NAN),
+        ];
+
+        for value in values {
+            assert_eq!(to_bson_datetime(&value, brasilia()), None, "value: {:?}", value);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+/// Prefix of the URN of a LinkedIn member, the kind of account a bare author ID stands for.
+pub const PERSON_URN_PREFIX: &str = "urn:li:person:";
+
+/// Prefix of the URN of a LinkedIn organization, such as a company page.
+pub const ORGANIZATION_URN_PREFIX: &str = "urn:li:organization:";
+
+/// Returns the account key of `author`, as given by a client or in the settings.
+///
+/// # Functionality
+///
+/// An account is the `author` a post, or one of its targets, is published on behalf of.
+/// Pauses, pacing rules, catch-up policies, access tokens and the scheduler's queues are all
+/// keyed by it, so the same account must always be written the same way:
+///
+/// - a member is its bare profile ID, e.g. `"abc123"`; `"urn:li:person:abc123"` is the same
+///   account and is shortened to it, which is how authors were stored before organizations;
+/// - an organization is its full URN, e.g. `"urn:li:organization:2414183"`.
+///
+/// # Errors
+///
+/// Returns a message if `author` is empty, is another kind of URN, or is a URN without an ID.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// assert_eq!(parse_account("urn:li:person:abc123")?, "abc123");
+/// assert_eq!(parse_account("urn:li:organization:2414183")?, "urn:li:organization:2414183");
+/// ```
+pub fn parse_account(author: &str) -> Result<String, String> {
+    let author = author.trim();
+    let (prefix, id) = if let Some(id) = author.strip_prefix(PERSON_URN_PREFIX) {
+        (PERSON_URN_PREFIX, id)
+    } else if let Some(id) = author.strip_prefix(ORGANIZATION_URN_PREFIX) {
+        (ORGANIZATION_URN_PREFIX, id)
+    } else if author.starts_with("urn:") {
+        return Err(format!(
+            "{} is neither a member ({}...) nor an organization ({}...)",
+            author, PERSON_URN_PREFIX, ORGANIZATION_URN_PREFIX
+        ));
+    } else {
+        ("", author)
+    };
+    if id.is_empty() || id.contains(':') {
+        return Err(format!("{:?} is not a LinkedIn account", author));
+    }
+    Ok(if prefix == ORGANIZATION_URN_PREFIX { author.to_string() } else { id.to_string() })
+}
+
+/// Returns the URN LinkedIn knows `account` by: organizations are already URNs, the other
+/// accounts are member IDs.
+pub fn account_urn(account: &str) -> String {
+    if account.starts_with(ORGANIZATION_URN_PREFIX) || account.starts_with(PERSON_URN_PREFIX) {
+        account.to_string()
+    } else {
+        format!("{}{}", PERSON_URN_PREFIX, account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that members and organizations get one account key and the URN of their kind.
+    #[test]
+    fn test_accounts() {
+        assert_eq!(parse_account("abc123").unwrap(), "abc123");
+        assert_eq!(parse_account(" urn:li:person:abc123 ").unwrap(), "abc123");
+        assert_eq!(parse_account("urn:li:organization:2414183").unwrap(), "urn:li:organization:2414183");
+        for invalid in ["", "urn:li:organization:", "urn:li:group:1", "urn:li:person:a:b"] {
+            assert!(parse_account(invalid).is_err(), "{:?}", invalid);
+        }
+
+        assert_eq!(account_urn("abc123"), "urn:li:person:abc123");
+        assert_eq!(account_urn("urn:li:organization:2414183"), "urn:li:organization:2414183");
+    }
+}
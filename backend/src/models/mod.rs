@@ -0,0 +1,4 @@
+pub mod account;
+pub mod pause;
+pub mod post;
+pub mod secret;
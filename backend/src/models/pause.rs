@@ -0,0 +1,118 @@
+use crate::models::post::Post;
+use mongodb::bson::DateTime as BsonDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Scope of a pause stopping the publications of every account.
+pub const GLOBAL_SCOPE: &str = "global";
+
+/// Prefix of the scope of a pause stopping the publications of one account.
+const ACCOUNT_SCOPE_PREFIX: &str = "account:";
+
+/// An emergency stop of publishing, as stored in the `pauses` collection.
+///
+/// While a pause is stored, the scheduler claims no post in its scope; the posts themselves
+/// are left untouched and are published once the pause is removed. Pauses are stored rather
+/// than kept in memory so that a restart of the scheduler does not resume publishing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pause {
+    /// What is paused: `"global"` for every account, `"account:<author>"` for the posts of
+    /// one `author`.
+    #[serde(rename = "_id")]
+    pub scope: String,
+    /// Why publishing was paused.
+    pub reason: String,
+    /// Who paused publishing.
+    pub actor: String,
+    pub paused_at: BsonDateTime,
+}
+
+impl Pause {
+    /// Creates a pause of the posts of `account`, or of every post if `None`.
+    pub fn new(account: Option<&str>, reason: &str, actor: &str) -> Self {
+        Pause {
+            scope: Pause::scope(account),
+            reason: reason.to_string(),
+            actor: actor.to_string(),
+            paused_at: BsonDateTime::now(),
+        }
+    }
+
+    /// Returns the scope of a pause of the posts of `account`, or of every post if `None`.
+    pub fn scope(account: Option<&str>) -> String {
+        match account {
+            Some(account) => format!("{}{}", ACCOUNT_SCOPE_PREFIX, account),
+            None => GLOBAL_SCOPE.to_string(),
+        }
+    }
+
+    /// Returns the paused account, or `None` for a global pause.
+    pub fn account(&self) -> Option<&str> {
+        self.scope.strip_prefix(ACCOUNT_SCOPE_PREFIX)
+    }
+
+    /// Returns whether the pause stops the publication of the posts of `author`.
+    ///
+    /// Posts without an author are published on behalf of the owner of the access token and
+    /// are only stopped by a global pause.
+    pub fn applies_to(&self, author: Option<&str>) -> bool {
+        self.scope == GLOBAL_SCOPE || (author.is_some() && self.account() == author)
+    }
+}
+
+/// Returns the pause stopping the publication of the posts of `author`, if any.
+pub fn pause_for<'a>(pauses: &'a [Pause], author: Option<&str>) -> Option<&'a Pause> {
+    pauses.iter().find(|pause| pause.applies_to(author))
+}
+
+/// Returns the pause stopping the publication of `post`, if any: a pause of any of the
+/// accounts its targets not published yet go out on behalf of (see `Post::pending_accounts`).
+pub fn pause_for_post<'a>(pauses: &'a [Pause], post: &Post) -> Option<&'a Pause> {
+    post.pending_accounts().into_iter().find_map(|account| pause_for(pauses, account))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    /// Tests which posts global and per-account pauses apply to.
+    #[test]
+    fn test_pause_scopes() {
+        let global = Pause::new(None, "Crisis", "alice");
+        let company = Pause::new(Some("company"), "Rebranding", "bob");
+
+        assert_eq!(global.scope, "global");
+        assert_eq!(global.account(), None);
+        assert!(global.applies_to(None));
+        assert!(global.applies_to(Some("company")));
+
+        assert_eq!(company.scope, "account:company");
+        assert_eq!(company.account(), Some("company"));
+        assert!(company.applies_to(Some("company")));
+        assert!(!company.applies_to(Some("personal")));
+        assert!(!company.applies_to(None));
+
+        let pauses = [company.clone()];
+        assert_eq!(pause_for(&pauses, Some("company")), Some(&company));
+        assert_eq!(pause_for(&pauses, None), None);
+    }
+
+    /// Tests that a post is paused by a pause of the account of any of its pending targets.
+    #[test]
+    fn test_pause_for_post() {
+        use crate::models::post::{Target, TargetStatus};
+
+        let pauses = [Pause::new(Some("company"), "Rebranding", "bob")];
+        let mut post = Post::new("Hiring".to_string(), "Content".to_string(), BsonDateTime::now(), Utc::now());
+        post.author = Some("alice".to_string());
+        assert_eq!(pause_for_post(&pauses, &post), None);
+
+        let mut company = Target::new("linkedin");
+        company.author = Some("company".to_string());
+        post.targets = vec![Target::new("linkedin"), company];
+        assert_eq!(pause_for_post(&pauses, &post), Some(&pauses[0]));
+
+        post.targets[1].status = TargetStatus::Published;
+        assert_eq!(pause_for_post(&pauses, &post), None);
+    }
+}
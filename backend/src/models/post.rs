@@ -0,0 +1,691 @@
+use crate::scheduling::catch_up::{CatchUpOutcome, CatchUpPolicy};
+use chrono::{DateTime, TimeDelta, Utc};
+use mongodb::bson::{doc, oid::ObjectId, to_bson, Bson, DateTime as BsonDateTime, Document};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Publication status of a scheduled post.
+///
+/// The status is stored in MongoDB as a snake_case string (`"scheduled"`, `"pending_approval"`, ...).
+/// Documents written by older versions with the `"pending"` status are read as `Scheduled`.
+///
+/// Status changes must follow the transition table implemented by `can_transition_to`:
+///
+/// | From               | Allowed targets                                  |
+/// |--------------------|--------------------------------------------------|
+/// | `draft`            | `pending_approval`, `scheduled`, `cancelled`     |
+/// | `pending_approval` | `draft`, `scheduled`, `cancelled`                |
+/// | `scheduled`        | `draft`, `pending_approval`, `publishing`, `missed`, `cancelled` |
+/// | `publishing`       | `published`, `partially_published`, `failed`, `scheduled` |
+/// | `published`        | none (terminal)                                  |
+/// | `partially_published` | `scheduled`, `cancelled`                      |
+/// | `failed`           | `draft`, `scheduled`, `cancelled`                |
+/// | `missed`           | `draft`, `scheduled`, `cancelled`                |
+/// | `cancelled`        | `draft`, `scheduled`                             |
+///
+/// Posts in an editable status (every status except `publishing` and `published`) may also
+/// keep their current status, which is what happens when only their content is edited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostStatus {
+    /// The post is being written and will not be published.
+    Draft,
+    /// The post is waiting for someone to approve it.
+    PendingApproval,
+    /// The post is waiting for its scheduled time.
+    #[default]
+    #[serde(alias = "pending")]
+    Scheduled,
+    /// The scheduler has claimed the post and is publishing it.
+    Publishing,
+    /// The post has been published on all of its targets.
+    Published,
+    /// The post has been published on some of its targets and failed on the others. Scheduling
+    /// it again retries the failed targets only.
+    PartiallyPublished,
+    /// Publishing the post failed.
+    Failed,
+    /// The post was found late and its catch-up policy chose not to publish it.
+    Missed,
+    /// The post was cancelled and will not be published.
+    Cancelled,
+}
+
+impl PostStatus {
+    /// Returns the string stored in MongoDB for this status.
+    ///
+    /// Useful when building query documents with the `doc!` macro, e.g.
+    /// `doc! { "status": PostStatus::Scheduled.as_str() }`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostStatus::Draft => "draft",
+            PostStatus::PendingApproval => "pending_approval",
+            PostStatus::Scheduled => "scheduled",
+            PostStatus::Publishing => "publishing",
+            PostStatus::Published => "published",
+            PostStatus::PartiallyPublished => "partially_published",
+            PostStatus::Failed => "failed",
+            PostStatus::Missed => "missed",
+            PostStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Returns whether a new post may be created in this status.
+    pub fn is_initial(&self) -> bool {
+        matches!(self, PostStatus::Draft | PostStatus::PendingApproval | PostStatus::Scheduled)
+    }
+
+    /// Returns whether only the scheduler may move a post to this status: claiming it, recording
+    /// the outcome of its publication or giving up on it when it is found late.
+    pub fn is_set_by_scheduler(&self) -> bool {
+        matches!(
+            self,
+            PostStatus::Publishing | PostStatus::Published | PostStatus::PartiallyPublished | PostStatus::Missed
+        )
+    }
+
+    /// Returns whether a post in this status can still be edited.
+    pub fn is_editable(&self) -> bool {
+        !matches!(self, PostStatus::Publishing | PostStatus::Published)
+    }
+
+    /// Returns whether the post is out on at least one of its targets.
+    pub fn is_published(&self) -> bool {
+        matches!(self, PostStatus::Published | PostStatus::PartiallyPublished)
+    }
+
+    /// Returns whether a post may move from this status to `to`.
+    pub fn can_transition_to(&self, to: PostStatus) -> bool {
+        use PostStatus::*;
+
+        if *self == to {
+            return self.is_editable();
+        }
+
+        matches!(
+            (self, to),
+            (Draft, PendingApproval | Scheduled | Cancelled)
+                | (PendingApproval, Draft | Scheduled | Cancelled)
+                | (Scheduled, Draft | PendingApproval | Publishing | Missed | Cancelled)
+                | (Publishing, Published | PartiallyPublished | Failed | Scheduled)
+                | (PartiallyPublished, Scheduled | Cancelled)
+                | (Failed | Missed, Draft | Scheduled | Cancelled)
+                | (Cancelled, Draft | Scheduled)
+        )
+    }
+
+    /// Checks a transition against the transition table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidTransition` error if the post may not move from this status to `to`.
+    pub fn transition_to(&self, to: PostStatus) -> Result<PostStatus, InvalidTransition> {
+        if self.can_transition_to(to) {
+            Ok(to)
+        } else {
+            Err(InvalidTransition { from: *self, to })
+        }
+    }
+}
+
+impl fmt::Display for PostStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when a status change is not allowed by the transition table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: PostStatus,
+    pub to: PostStatus,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid status transition from {} to {}", self.from, self.to)
+    }
+}
+
+impl std::error::Error for InvalidTransition {}
+
+/// A recorded status change of a post.
+///
+/// Every status change is appended to the post's `status_history`, including the initial
+/// status given when the post is created (with `from` set to `None`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusTransition {
+    pub from: Option<PostStatus>,
+    pub to: PostStatus,
+    pub at: BsonDateTime,
+    /// Who made the change, e.g. the value of the `X-Actor` header or `"scheduler"`.
+    pub actor: String,
+    /// Optional explanation, such as the error that made a publication fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl StatusTransition {
+    /// Creates a transition happening `at`, usually the current time of the caller's `Clock`.
+    pub fn new(from: Option<PostStatus>, to: PostStatus, actor: &str, at: DateTime<Utc>) -> Self {
+        StatusTransition {
+            from,
+            to,
+            at: BsonDateTime::from_chrono(at),
+            actor: actor.to_string(),
+            reason: None,
+        }
+    }
+
+    /// Sets the reason of the transition.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Builds the MongoDB update applying this transition to a stored post.
+    ///
+    /// The update sets `status` and `updated_at`, and appends the transition to
+    /// `status_history`. Callers should filter on the current status so that a
+    /// concurrent change is detected instead of being overwritten.
+    pub fn to_update(&self) -> Document {
+        let transition = to_bson(self).unwrap_or(Bson::Null);
+        doc! {
+            "$set": { "status": self.to.as_str(), "updated_at": self.at },
+            "$push": { "status_history": transition },
+        }
+    }
+}
+
+/// A media attachment published together with a post.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Media {
+    /// Public URL of the media (image, article link, ...).
+    pub url: String,
+    /// Optional title shown by LinkedIn for the media.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Optional description shown by LinkedIn for the media.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Channel of the posts that do not name any, such as those created before channels existed:
+/// LinkedIn, with the configured access token.
+pub const DEFAULT_CHANNEL: &str = "linkedin";
+
+/// Publication status of a post on one of its channels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetStatus {
+    /// The post has not been published on the channel yet.
+    #[default]
+    Pending,
+    /// The post has been published on the channel.
+    Published,
+    /// The last attempt to publish the post on the channel failed.
+    Failed,
+}
+
+/// A destination of a post: a channel, optionally with its own account, text and hashtags,
+/// and how publishing the post there went.
+///
+/// The scheduler publishes a post on each of its targets that is not published yet, so a post
+/// that failed on one target only and is scheduled again is not published twice on the others.
+/// A post can target the same channel several times with different accounts, e.g. a personal
+/// profile and a company page on LinkedIn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Target {
+    /// Name of the channel, a key of the scheduler's publishers (e.g. `"linkedin"`).
+    pub channel: String,
+    /// Account the post is published on behalf of on this target, instead of the post's `author`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Text published on this target instead of the post's `content`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Hashtags appended to the text on this target, without the leading `#`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hashtags: Vec<String>,
+    #[serde(default)]
+    pub status: TargetStatus,
+    /// ID of the post on the channel once published, e.g. the URN of the LinkedIn share.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<BsonDateTime>,
+    /// Error of the last failed attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Target {
+    /// Creates a pending target on `channel`.
+    pub fn new(channel: impl Into<String>) -> Self {
+        Target {
+            channel: channel.into(),
+            author: None,
+            content: None,
+            hashtags: Vec::new(),
+            status: TargetStatus::Pending,
+            remote_id: None,
+            published_at: None,
+            error: None,
+        }
+    }
+
+    /// Returns the name of the target in logs and errors: the channel, followed by the account
+    /// if the target has its own, e.g. `linkedin (company-page)`.
+    pub fn label(&self) -> String {
+        match &self.author {
+            Some(author) => format!("{} ({})", self.channel, author),
+            None => self.channel.clone(),
+        }
+    }
+
+    /// Returns the text published on this target: its own content, or `base`, followed by its
+    /// hashtags on a separate paragraph.
+    pub fn text(&self, base: &str) -> String {
+        let text = self.content.as_deref().unwrap_or(base);
+        if self.hashtags.is_empty() {
+            return text.to_string();
+        }
+        let hashtags: Vec<String> = self.hashtags.iter().map(|hashtag| format!("#{}", hashtag)).collect();
+        format!("{}\n\n{}", text, hashtags.join(" "))
+    }
+}
+
+/// Returns the status of a post once each of its `targets` has been attempted: `published` if
+/// every target is published, `partially_published` if some are, `failed` if none is.
+pub fn overall_status(targets: &[Target]) -> PostStatus {
+    let published = targets.iter().filter(|target| target.status == TargetStatus::Published).count();
+    if published == targets.len() {
+        PostStatus::Published
+    } else if published > 0 {
+        PostStatus::PartiallyPublished
+    } else {
+        PostStatus::Failed
+    }
+}
+
+/// A post scheduled to be published on LinkedIn, as stored in the `posts` collection.
+///
+/// This is the single domain model shared by the `web_server` and `scheduler` binaries
+/// through a typed `Collection<Post>`. Field names are the MongoDB field names, so
+/// renaming a field here is a schema change that must come with a data migration.
+///
+/// `scheduled_time` is a UTC instant stored as a native BSON `DateTime`. Fields added
+/// after the first version (`media`, `author`, the timestamps and `status_history`) are
+/// optional so that documents created by older versions still deserialize.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Post {
+    /// MongoDB identifier, `None` until the post is inserted.
+    #[serde(rename = "_id", default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub title: String,
+    pub content: String,
+    pub scheduled_time: BsonDateTime,
+    #[serde(default)]
+    pub status: PostStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<Media>,
+    /// LinkedIn profile ID of the author. When `None`, the post is published on behalf
+    /// of the profile owning the configured access token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<BsonDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<BsonDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<BsonDateTime>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status_history: Vec<StatusTransition>,
+    /// Catch-up policy of this post, overriding the configured ones when it is found late.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catch_up_policy: Option<CatchUpPolicy>,
+    /// What the catch-up policy did the last time this post was found late.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub catch_up: Option<CatchUpOutcome>,
+    /// When a scheduler in dry-run mode last reported this post, instead of publishing it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run_at: Option<BsonDateTime>,
+    /// Channels the post is published to, with their status. Empty for posts created before
+    /// channels existed, which are published to `DEFAULT_CHANNEL` (see `targets_or_default`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<Target>,
+}
+
+impl Post {
+    /// Creates a new post in the `scheduled` status, scheduled at `scheduled_time`.
+    ///
+    /// `created_at` is the current time of the caller's `Clock`; the post has no ID until it
+    /// is inserted.
+    pub fn new(title: String, content: String, scheduled_time: BsonDateTime, created_at: DateTime<Utc>) -> Self {
+        Post {
+            id: None,
+            title,
+            content,
+            scheduled_time,
+            status: PostStatus::Scheduled,
+            media: Vec::new(),
+            author: None,
+            created_at: Some(BsonDateTime::from_chrono(created_at)),
+            updated_at: None,
+            published_at: None,
+            status_history: Vec::new(),
+            catch_up_policy: None,
+            catch_up: None,
+            dry_run_at: None,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Returns when the scheduler claimed the post, if it is "publishing".
+    ///
+    /// This is the time of its last transition to "publishing", or its scheduled time for posts
+    /// without a status history.
+    pub fn claimed_at(&self) -> Option<DateTime<Utc>> {
+        if self.status != PostStatus::Publishing {
+            return None;
+        }
+        let claim = self.status_history.iter().rev().find(|transition| transition.to == PostStatus::Publishing);
+        Some(claim.map_or(self.scheduled_time, |transition| transition.at).to_chrono())
+    }
+
+    /// Returns when the post went out: when it was claimed while it is "publishing", and its
+    /// `published_at` once it is published, even partially, falling back to its
+    /// `scheduled_time` for posts published before `published_at` was recorded. `None` in the
+    /// other statuses.
+    pub fn publication_time(&self) -> Option<DateTime<Utc>> {
+        match self.status {
+            PostStatus::Publishing => self.claimed_at(),
+            status if status.is_published() => Some(self.published_at.unwrap_or(self.scheduled_time).to_chrono()),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the post has been "publishing" for longer than `timeout` at `now`.
+    ///
+    /// A stopping scheduler gives its publications `scheduler.shutdown_timeout_secs` to complete
+    /// and releases the others, so a post still "publishing" after that was left by a scheduler
+    /// that crashed, and no scheduler will complete it.
+    pub fn is_abandoned(&self, now: DateTime<Utc>, timeout: TimeDelta) -> bool {
+        self.claimed_at().is_some_and(|claimed_at| now - claimed_at > timeout)
+    }
+
+    /// Returns the targets of the post, or a pending target on `DEFAULT_CHANNEL` if it has none.
+    pub fn targets_or_default(&self) -> Vec<Target> {
+        if self.targets.is_empty() {
+            vec![Target::new(DEFAULT_CHANNEL)]
+        } else {
+            self.targets.clone()
+        }
+    }
+
+    /// Returns the account the post is published on behalf of on `target`: the target's own
+    /// `author`, or the post's. `None` stands for the owner of the channel's credentials.
+    pub fn account_of<'a>(&'a self, target: &'a Target) -> Option<&'a str> {
+        target.author.as_deref().or(self.author.as_deref())
+    }
+
+    /// Returns the accounts the post is published on behalf of, one per distinct account of
+    /// its targets, in the order of the targets.
+    pub fn accounts(&self) -> Vec<Option<&str>> {
+        self.accounts_where(|_| true)
+    }
+
+    /// Returns the accounts the post is still to be published on behalf of: those of its
+    /// targets not published yet. Pauses, pacing rules and the scheduler's per-account
+    /// queues apply to these accounts.
+    pub fn pending_accounts(&self) -> Vec<Option<&str>> {
+        self.accounts_where(|target| target.status != TargetStatus::Published)
+    }
+
+    fn accounts_where(&self, keep: impl Fn(&Target) -> bool) -> Vec<Option<&str>> {
+        if self.targets.is_empty() {
+            return vec![self.author.as_deref()];
+        }
+        let mut accounts = Vec::new();
+        for target in self.targets.iter().filter(|target| keep(target)) {
+            let account = self.account_of(target);
+            if !accounts.contains(&account) {
+                accounts.push(account);
+            }
+        }
+        accounts
+    }
+
+    /// Returns whether a dry run already reported this post at its current `scheduled_time`.
+    ///
+    /// A post rescheduled after being reported is reported again when it is due.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run_at.is_some_and(|at| at >= self.scheduled_time)
+    }
+
+    /// Applies a status transition: sets the status and `updated_at`, sets `published_at`
+    /// for a transition to `published` or `partially_published`, and appends the transition to `status_history`.
+    ///
+    /// The transition is not checked against the current status; callers are expected to
+    /// have done so.
+    pub fn record_transition(&mut self, transition: StatusTransition) {
+        self.status = transition.to;
+        self.updated_at = Some(transition.at);
+        if transition.to.is_published() {
+            self.published_at = Some(transition.at);
+        }
+        self.status_history.push(transition);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{from_document, to_document};
+
+    /// Tests that a document created by an older version deserializes into a `Post`.
+    ///
+    /// Legacy documents only carry `title`, `content`, `scheduled_time` and `status`.
+    #[test]
+    fn test_legacy_document_deserializes() {
+        let id = ObjectId::new();
+        let scheduled_time = BsonDateTime::from_millis(1_715_342_400_000);
+        let document = doc! {
+            "_id": id,
+            "title": "Legacy",
+            "content": "Legacy content",
+            "scheduled_time": scheduled_time,
+            "status": "pending",
+        };
+
+        let post: Post = from_document(document).unwrap();
+
+        assert_eq!(post.id, Some(id));
+        assert_eq!(post.scheduled_time, scheduled_time);
+        assert_eq!(post.status, PostStatus::Scheduled);
+        assert!(post.media.is_empty());
+        assert_eq!(post.author, None);
+        assert_eq!(post.created_at, None);
+        assert_eq!(post.targets_or_default(), vec![Target::new(DEFAULT_CHANNEL)]);
+    }
+
+    /// Tests that the MongoDB field names used by queries in both binaries stay stable.
+    #[test]
+    fn test_serialized_field_names() {
+        let mut post = Post::new(
+            "Title".to_string(),
+            "Content".to_string(),
+            BsonDateTime::from_millis(0),
+            Utc::now(),
+        );
+        post.id = Some(ObjectId::new());
+        post.status = PostStatus::Published;
+
+        let document = to_document(&post).unwrap();
+
+        assert!(document.get_object_id("_id").is_ok());
+        assert!(document.get_datetime("scheduled_time").is_ok());
+        assert!(document.get_datetime("created_at").is_ok());
+        assert_eq!(document.get_str("status").unwrap(), PostStatus::Published.as_str());
+        assert!(!document.contains_key("media"));
+        assert!(!document.contains_key("author"));
+        assert!(!document.contains_key("targets"));
+
+        post.targets.push(Target::new("mastodon"));
+        let document = to_document(&post).unwrap();
+        let target = document.get_array("targets").unwrap()[0].as_document().unwrap();
+        assert_eq!(target.get_str("channel").unwrap(), "mastodon");
+        assert_eq!(target.get_str("status").unwrap(), "pending");
+    }
+
+    /// Tests that an unknown status is rejected instead of being silently accepted.
+    #[test]
+    fn test_unknown_status_is_rejected() {
+        let document = doc! {
+            "title": "Title",
+            "content": "Content",
+            "scheduled_time": BsonDateTime::now(),
+            "status": "archived",
+        };
+
+        assert!(from_document::<Post>(document).is_err());
+    }
+
+    /// Tests the transition table, including the transitions that must be rejected.
+    #[test]
+    fn test_transition_table() {
+        use PostStatus::*;
+
+        let allowed = [
+            (Draft, Scheduled),
+            (Draft, PendingApproval),
+            (PendingApproval, Scheduled),
+            (Scheduled, Publishing),
+            (Scheduled, Scheduled),
+            (Publishing, Published),
+            (Publishing, Failed),
+            (Publishing, Scheduled),
+            (Publishing, PartiallyPublished),
+            (PartiallyPublished, Scheduled),
+            (PartiallyPublished, Cancelled),
+            (Failed, Scheduled),
+            (Scheduled, Missed),
+            (Missed, Scheduled),
+            (Cancelled, Draft),
+        ];
+        for (from, to) in allowed {
+            assert!(from.can_transition_to(to), "{} -> {} should be allowed", from, to);
+        }
+
+        let rejected = [
+            (Published, Scheduled),
+            (Published, Draft),
+            (Published, Published),
+            (Publishing, Publishing),
+            (Draft, Published),
+            (Draft, Publishing),
+            (Scheduled, Published),
+            (Cancelled, Published),
+            (Failed, Published),
+            (Missed, Publishing),
+            (Publishing, Missed),
+            (PartiallyPublished, Published),
+            (PartiallyPublished, Draft),
+        ];
+        for (from, to) in rejected {
+            assert_eq!(
+                from.transition_to(to),
+                Err(InvalidTransition { from, to }),
+                "{} -> {} should be rejected",
+                from,
+                to
+            );
+        }
+    }
+
+    /// Tests that the status stored by older versions is read as `scheduled`.
+    #[test]
+    fn test_legacy_pending_status_is_scheduled() {
+        let status: PostStatus = serde_json::from_str(r#""pending""#).unwrap();
+        assert_eq!(status, PostStatus::Scheduled);
+        assert_eq!(serde_json::to_string(&status).unwrap(), r#""scheduled""#);
+    }
+
+    /// Tests that a transition update sets the status and appends to the history.
+    #[test]
+    fn test_transition_update_document() {
+        let transition =
+            StatusTransition::new(Some(PostStatus::Publishing), PostStatus::Failed, "scheduler", Utc::now())
+                .with_reason("LinkedIn returned 500");
+
+        let update = transition.to_update();
+
+        let set = update.get_document("$set").unwrap();
+        assert_eq!(set.get_str("status").unwrap(), "failed");
+        let pushed = update.get_document("$push").unwrap().get_document("status_history").unwrap();
+        assert_eq!(pushed.get_str("from").unwrap(), "publishing");
+        assert_eq!(pushed.get_str("actor").unwrap(), "scheduler");
+        assert_eq!(pushed.get_str("reason").unwrap(), "LinkedIn returned 500");
+    }
+
+    /// Tests the text of a target and the status derived from the targets.
+    #[test]
+    fn test_targets() {
+        let mut company = Target::new("linkedin");
+        company.author = Some("company".to_string());
+        company.content = Some("We are hiring".to_string());
+        company.hashtags = vec!["jobs".to_string(), "rust".to_string()];
+        assert_eq!(company.text("I am hiring"), "We are hiring\n\n#jobs #rust");
+        assert_eq!(company.label(), "linkedin (company)");
+        let personal = Target::new("linkedin");
+        assert_eq!(personal.text("I am hiring"), "I am hiring");
+
+        let mut targets = vec![company, personal];
+        assert_eq!(overall_status(&targets), PostStatus::Failed);
+        targets[0].status = TargetStatus::Published;
+        targets[1].status = TargetStatus::Failed;
+        assert_eq!(overall_status(&targets), PostStatus::PartiallyPublished);
+        targets[1].status = TargetStatus::Published;
+        assert_eq!(overall_status(&targets), PostStatus::Published);
+    }
+
+    /// Tests that the accounts of a post are those of its targets, falling back to its author.
+    #[test]
+    fn test_accounts() {
+        let mut post = Post::new("Hiring".to_string(), "I am hiring".to_string(), BsonDateTime::now(), Utc::now());
+        assert_eq!(post.accounts(), vec![None]);
+        post.author = Some("alice".to_string());
+        assert_eq!(post.pending_accounts(), vec![Some("alice")]);
+
+        let mut company = Target::new("linkedin");
+        company.author = Some("company".to_string());
+        post.targets = vec![Target::new("linkedin"), company, Target::new("crm")];
+        assert_eq!(post.account_of(&post.targets[1]), Some("company"));
+        assert_eq!(post.accounts(), vec![Some("alice"), Some("company")]);
+
+        post.targets[0].status = TargetStatus::Published;
+        post.targets[2].status = TargetStatus::Published;
+        assert_eq!(post.pending_accounts(), vec![Some("company")]);
+        assert_eq!(post.accounts(), vec![Some("alice"), Some("company")]);
+    }
+
+    /// Tests that a post is abandoned once it has been "publishing" for longer than the timeout.
+    #[test]
+    fn test_is_abandoned() {
+        let now = BsonDateTime::now().to_chrono();
+        let timeout = TimeDelta::seconds(30);
+        let mut post = Post::new("Hiring".to_string(), "I am hiring".to_string(), BsonDateTime::from_chrono(now), now);
+        assert!(!post.is_abandoned(now + TimeDelta::hours(1), timeout));
+
+        // Without a status history, the post counts as claimed at its scheduled time.
+        post.status = PostStatus::Publishing;
+        assert_eq!(post.claimed_at(), Some(post.scheduled_time.to_chrono()));
+        assert!(post.is_abandoned(now + TimeDelta::seconds(31), timeout));
+
+        let claimed_at = now + TimeDelta::minutes(10);
+        let claim = StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Publishing, "scheduler", claimed_at);
+        post.status_history.push(claim);
+        assert!(!post.is_abandoned(now + TimeDelta::minutes(10) + timeout, timeout));
+        assert!(post.is_abandoned(now + TimeDelta::minutes(11), timeout));
+    }
+}
@@ -0,0 +1,20 @@
+use mongodb::bson::DateTime as BsonDateTime;
+use serde::{Deserialize, Serialize};
+
+/// A secret encrypted at rest, as stored in the `secrets` collection.
+///
+/// Secrets are encrypted with AES-256-GCM by `SecretCipher`; the stored values are
+/// base64 encoded so the document is the same in every storage backend. The name is
+/// authenticated along with the ciphertext, so a ciphertext copied to another name does
+/// not decrypt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedSecret {
+    /// Name of the secret, e.g. `linkedin_access_token`.
+    #[serde(rename = "_id")]
+    pub name: String,
+    /// Base64-encoded 96-bit nonce, unique per encryption.
+    pub nonce: String,
+    /// Base64-encoded ciphertext followed by the authentication tag.
+    pub ciphertext: String,
+    pub updated_at: BsonDateTime,
+}
@@ -0,0 +1,321 @@
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use warp::http::header::{HeaderValue, CONTENT_TYPE};
+use warp::http::StatusCode;
+use warp::reject::Reject;
+use warp::reply::Response;
+use warp::{Rejection, Reply};
+
+/// Media type of the error responses of the web server (RFC 9457).
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// A validation message about one field of a request, shown next to that field by the forms.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Name of the field in the request, e.g. `"scheduled_time"`.
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    /// Creates the error of `field`.
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// An error answered by the web server, as a JSON problem-details body (see `Problem`).
+///
+/// Handlers return it as a reply with `into_response`, and `recover` turns the rejections of
+/// warp's filters (unknown route, malformed body, ...) into one. The messages are shown to the
+/// user: internal causes, such as database errors, are logged instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// The request is invalid (422 Unprocessable Entity). `errors` lists the fields at fault,
+    /// and `detail` sums them up.
+    Validation { detail: String, errors: Vec<FieldError> },
+    /// The resource does not exist (404 Not Found).
+    NotFound(String),
+    /// The request conflicts with the current state (409 Conflict), such as a forbidden status
+    /// transition or a pacing rule; `suggested_times` are times the post could be scheduled at
+    /// instead, if any.
+    Conflict { detail: String, suggested_times: Vec<String> },
+    /// The request lacks valid credentials (401 Unauthorized).
+    Unauthorized(String),
+    /// The server failed to handle the request (500 Internal Server Error).
+    Internal(String),
+}
+
+impl ApiError {
+    /// Returns a validation error of the given fields, summed up by their messages.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust,ignore
+    /// let error = ApiError::invalid(vec![FieldError::new("title", "A title is required")]);
+    /// assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    /// ```
+    pub fn invalid(errors: Vec<FieldError>) -> Self {
+        let detail = errors.iter().map(|error| error.message.as_str()).collect::<Vec<_>>().join("; ");
+        ApiError::Validation { detail, errors }
+    }
+
+    /// Returns a validation error of one field.
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        ApiError::invalid(vec![FieldError::new(field, message)])
+    }
+
+    /// Returns a conflict without suggested times.
+    pub fn conflict(detail: impl Into<String>) -> Self {
+        ApiError::Conflict {
+            detail: detail.into(),
+            suggested_times: Vec::new(),
+        }
+    }
+
+    /// Returns the HTTP status of the error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict { .. } => StatusCode::CONFLICT,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Returns the message describing this occurrence of the error.
+    pub fn detail(&self) -> &str {
+        match self {
+            ApiError::Validation { detail, .. } | ApiError::Conflict { detail, .. } => detail,
+            ApiError::NotFound(detail) | ApiError::Unauthorized(detail) | ApiError::Internal(detail) => detail,
+        }
+    }
+
+    /// Returns the problem-details body of the error.
+    pub fn to_problem(&self) -> Problem {
+        let mut problem = Problem::new(self.status(), self.detail());
+        match self {
+            ApiError::Validation { errors, .. } => problem.errors = errors.clone(),
+            ApiError::Conflict { suggested_times, .. } => problem.suggested_times = suggested_times.clone(),
+            _ => {}
+        }
+        problem
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.status(), self.detail())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl Reject for ApiError {}
+
+impl Reply for ApiError {
+    fn into_response(self) -> Response {
+        self.to_problem().into_response()
+    }
+}
+
+/// A JSON problem-details body (RFC 9457), with the extension members of `ApiError`.
+///
+/// # Example Usage
+///
+/// ```json
+/// {
+///   "type": "about:blank",
+///   "title": "Unprocessable Entity",
+///   "status": 422,
+///   "detail": "A title is required; 2024-05-10 08:59 is in the past, it is 2024-05-10 09:00",
+///   "errors": [
+///     {"field": "title", "message": "A title is required"},
+///     {"field": "scheduled_time", "message": "2024-05-10 08:59 is in the past, it is 2024-05-10 09:00"}
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    /// Always `about:blank`: the status says what kind of problem it is.
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// The reason phrase of the status.
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// The fields at fault, for validation errors.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+    /// Times the post could be scheduled at instead, for pacing conflicts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggested_times: Vec<String>,
+}
+
+impl Problem {
+    /// Creates the problem of the given status, without extension members.
+    pub fn new(status: StatusCode, detail: &str) -> Self {
+        Problem {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: detail.to_string(),
+            errors: Vec::new(),
+            suggested_times: Vec::new(),
+        }
+    }
+}
+
+impl Reply for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = warp::reply::with_status(warp::reply::json(&self), status).into_response();
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_CONTENT_TYPE));
+        response
+    }
+}
+
+/// Returns the field named in a serde error message, such as "missing field `title`".
+fn field_of(message: &str) -> Option<&str> {
+    let (_, rest) = message.split_once("field `")?;
+    rest.split_once('`').map(|(field, _)| field)
+}
+
+/// Turns the rejections of the routes into problem-details responses.
+///
+/// # Functionality
+///
+/// To be used with `Filter::recover` after every route, and before CORS so that the frontend
+/// can read the errors:
+///
+/// - An `ApiError` rejection is answered as such.
+/// - A request matching no route gets a 404, or a 405 if only the method is wrong.
+/// - A body or query string that cannot be deserialized gets a 422, with the field named in
+///   the error (a missing or unknown field) if any, and a body of the wrong type a 415.
+/// - Any other rejection is logged and answered with a 500.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// let routes = schedule_post.or(query_posts).recover(recover).with(cors);
+/// ```
+///
+/// # Errors
+///
+/// Never fails: every rejection gets a response.
+pub async fn recover(rejection: Rejection) -> Result<Response, Infallible> {
+    let error = if let Some(error) = rejection.find::<ApiError>() {
+        error.clone()
+    } else if rejection.is_not_found() {
+        ApiError::NotFound("No such resource".to_string())
+    } else if let Some(e) = rejection.find::<warp::filters::body::BodyDeserializeError>() {
+        invalid_request(&e.to_string())
+    } else if let Some(e) = rejection.find::<warp::reject::InvalidQuery>() {
+        invalid_request(&e.to_string())
+    } else if rejection.find::<warp::reject::MethodNotAllowed>().is_some() {
+        let problem = Problem::new(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed for this resource");
+        return Ok(problem.into_response());
+    } else if let Some(e) = rejection.find::<warp::reject::UnsupportedMediaType>() {
+        return Ok(Problem::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, &e.to_string()).into_response());
+    } else {
+        error!("Unhandled rejection: {:?}", rejection);
+        ApiError::Internal("Internal server error".to_string())
+    };
+    if error.status().is_server_error() {
+        error!("Request failed: {}", error);
+    } else {
+        warn!("Request rejected: {}", error);
+    }
+    Ok(error.into_response())
+}
+
+/// Returns the validation error of a body or query string that cannot be deserialized.
+fn invalid_request(message: &str) -> ApiError {
+    match field_of(message) {
+        Some(field) => ApiError::invalid_field(field, message),
+        None => ApiError::Validation {
+            detail: message.to_string(),
+            errors: Vec::new(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::Filter;
+
+    /// Tests the problem-details body and status of each kind of error.
+    #[test]
+    fn test_problem_details() {
+        let validation = ApiError::invalid(vec![
+            FieldError::new("title", "A title is required"),
+            FieldError::new("content", "Content is required"),
+        ]);
+        assert_eq!(validation.detail(), "A title is required; Content is required");
+        assert_eq!(validation.to_problem().errors.len(), 2);
+        let response = validation.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_CONTENT_TYPE);
+
+        let problem = ApiError::conflict("Published posts cannot be scheduled again").to_problem();
+        let json = serde_json::to_value(&problem).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "about:blank",
+                "title": "Conflict",
+                "status": 409,
+                "detail": "Published posts cannot be scheduled again",
+            })
+        );
+
+        assert_eq!(ApiError::NotFound("Post not found".to_string()).status(), StatusCode::NOT_FOUND);
+        assert_eq!(ApiError::Unauthorized("Token expired".to_string()).status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(ApiError::Internal("Failed".to_string()).status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    /// Tests that `recover` answers warp's rejections and `ApiError` rejections as problems.
+    #[tokio::test]
+    async fn test_recover() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Body {
+            title: String,
+        }
+        let missing = warp::get().and(warp::path("missing")).and_then(|| async {
+            Err::<&str, _>(warp::reject::custom(ApiError::NotFound("Post not found".to_string())))
+        });
+        let routes = warp::post()
+            .and(warp::path("posts"))
+            .and(warp::body::json())
+            .map(|_: Body| "Created")
+            .or(missing)
+            .recover(recover);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/posts")
+            .json(&serde_json::json!({}))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(problem.errors[0].field, "title");
+
+        let response = warp::test::request().path("/missing").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let problem: Problem = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(problem.detail, "Post not found");
+
+        let response = warp::test::request().method("DELETE").path("/posts").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_CONTENT_TYPE);
+    }
+}
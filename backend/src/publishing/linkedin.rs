@@ -0,0 +1,197 @@
+use crate::api::connections::get_profile_id;
+use crate::api::post::{article_request_body, delete_article, fetch_article_metrics, publish_article, ugc_posts_url};
+use crate::config::secret::Secret;
+use crate::publishing::publisher::{PostMetrics, Publication, PublishError, Publisher};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+
+/// Publishes posts on LinkedIn as UGC shares, through the functions of `api::post`.
+///
+/// Posts with an `author` are published on behalf of that account: a member's profile ID, or
+/// an organization URN for a company page (see `account_urn`). The others are published on
+/// behalf of the profile owning the access token, which is looked up for each post.
+///
+/// An account with its own token in `account_tokens` (`linkedin.access_tokens`) is published
+/// to with it, e.g. the token of an administrator of a company page; the other accounts with
+/// the default access token.
+pub struct LinkedInPublisher {
+    access_token: Secret,
+    account_tokens: BTreeMap<String, Secret>,
+    api_base_url: String,
+}
+
+impl LinkedInPublisher {
+    /// Creates a publisher using `access_token` against the LinkedIn API at `api_base_url`.
+    pub fn new(access_token: Secret, api_base_url: &str) -> Self {
+        LinkedInPublisher {
+            access_token,
+            account_tokens: BTreeMap::new(),
+            api_base_url: api_base_url.to_string(),
+        }
+    }
+
+    /// Uses the given access tokens, keyed by account, for the posts of these accounts.
+    pub fn with_account_tokens(mut self, account_tokens: BTreeMap<String, Secret>) -> Self {
+        self.account_tokens = account_tokens;
+        self
+    }
+
+    /// Returns the access token used to publish on behalf of `author`.
+    fn access_token_for(&self, author: Option<&str>) -> &Secret {
+        author.and_then(|author| self.account_tokens.get(author)).unwrap_or(&self.access_token)
+    }
+
+    /// Returns the account the publication is published on behalf of.
+    async fn author(&self, publication: &Publication<'_>) -> Result<String, PublishError> {
+        match publication.author {
+            Some(author) => Ok(author.to_string()),
+            None => Ok(get_profile_id(self.access_token.expose(), Some(self.api_base_url.clone())).await?),
+        }
+    }
+}
+
+#[async_trait]
+impl Publisher for LinkedInPublisher {
+    async fn publish(&self, publication: &Publication<'_>) -> Result<Option<String>, PublishError> {
+        let author = self.author(publication).await?;
+        Ok(publish_article(
+            self.access_token_for(publication.author).expose(),
+            &author,
+            publication.title,
+            &publication.content,
+            Some(&self.api_base_url),
+        )
+        .await?)
+    }
+
+    async fn dry_run(&self, publication: &Publication<'_>) -> Result<String, PublishError> {
+        let author = self.author(publication).await?;
+        let body = article_request_body(&author, &publication.content);
+        Ok(format!("POST {} {}", ugc_posts_url(Some(&self.api_base_url)), body))
+    }
+
+    async fn delete(&self, remote_id: &str) -> Result<(), PublishError> {
+        Ok(delete_article(self.access_token.expose(), remote_id, Some(&self.api_base_url)).await?)
+    }
+
+    async fn fetch_metrics(&self, remote_id: &str) -> Result<PostMetrics, PublishError> {
+        let metrics = fetch_article_metrics(self.access_token.expose(), remote_id, Some(&self.api_base_url)).await?;
+        Ok(PostMetrics {
+            likes: Some(metrics.likes),
+            comments: Some(metrics.comments),
+            ..PostMetrics::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    fn publication(author: Option<&str>) -> Publication<'_> {
+        Publication {
+            title: "Title",
+            content: "Hello LinkedIn!".into(),
+            author,
+            media: &[],
+        }
+    }
+
+    /// Tests that a post is published on behalf of its author and its share URN returned.
+    #[tokio::test]
+    async fn test_publish_returns_share_urn() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v2/ugcPosts")
+            .match_header("authorization", "Bearer mock-token")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"author": "urn:li:person:alice"}"#.to_string(),
+            ))
+            .with_status(201)
+            .with_header("x-restli-id", "urn:li:share:42")
+            .create_async()
+            .await;
+
+        let publisher = LinkedInPublisher::new(Secret::new("mock-token"), &server.url());
+        let urn = publisher.publish(&publication(Some("alice"))).await.unwrap();
+
+        assert_eq!(urn.as_deref(), Some("urn:li:share:42"));
+        mock.assert_async().await;
+    }
+
+    /// Tests that a company page target is published as its organization, with the token of
+    /// its account, while members keep the default token.
+    #[tokio::test]
+    async fn test_publish_as_organization() {
+        let mut server = Server::new_async().await;
+        let organization = server
+            .mock("POST", "/v2/ugcPosts")
+            .match_header("authorization", "Bearer page-admin-token")
+            .match_body(Matcher::PartialJsonString(
+                r#"{"author": "urn:li:organization:2414183"}"#.to_string(),
+            ))
+            .with_status(201)
+            .with_header("x-restli-id", "urn:li:share:43")
+            .create_async()
+            .await;
+        let member = server
+            .mock("POST", "/v2/ugcPosts")
+            .match_header("authorization", "Bearer mock-token")
+            .match_body(Matcher::PartialJsonString(r#"{"author": "urn:li:person:alice"}"#.to_string()))
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let tokens = BTreeMap::from([("urn:li:organization:2414183".to_string(), Secret::new("page-admin-token"))]);
+        let publisher = LinkedInPublisher::new(Secret::new("mock-token"), &server.url()).with_account_tokens(tokens);
+        let urn = publisher.publish(&publication(Some("urn:li:organization:2414183"))).await.unwrap();
+        publisher.publish(&publication(Some("alice"))).await.unwrap();
+
+        assert_eq!(urn.as_deref(), Some("urn:li:share:43"));
+        let body = article_request_body("urn:li:organization:2414183", "Hello LinkedIn!");
+        assert_eq!(body["author"], "urn:li:organization:2414183");
+        organization.assert_async().await;
+        member.assert_async().await;
+    }
+
+    /// Tests that a dry run describes the exact request body without sending it.
+    #[tokio::test]
+    async fn test_dry_run_sends_nothing() {
+        let mut server = Server::new_async().await;
+        let mock = server.mock("POST", "/v2/ugcPosts").expect(0).create_async().await;
+
+        let publisher = LinkedInPublisher::new(Secret::new("mock-token"), &server.url());
+        let request = publisher.dry_run(&publication(Some("alice"))).await.unwrap();
+
+        let body = article_request_body("alice", "Hello LinkedIn!");
+        assert_eq!(request, format!("POST {}/v2/ugcPosts {}", server.url(), body));
+        assert!(!request.contains("mock-token"));
+        mock.assert_async().await;
+    }
+
+    /// Tests that deleting and fetching metrics address the share by its encoded URN.
+    #[tokio::test]
+    async fn test_delete_and_fetch_metrics() {
+        let mut server = Server::new_async().await;
+        let delete = server
+            .mock("DELETE", "/v2/ugcPosts/urn%3Ali%3Ashare%3A42")
+            .with_status(204)
+            .create_async()
+            .await;
+        let metrics = server
+            .mock("GET", "/v2/socialActions/urn%3Ali%3Ashare%3A42")
+            .with_status(200)
+            .with_body(r#"{"likesSummary": {"totalLikes": 7}, "commentsSummary": {"aggregatedTotalComments": 2}}"#)
+            .create_async()
+            .await;
+
+        let publisher = LinkedInPublisher::new(Secret::new("mock-token"), &server.url());
+        publisher.delete("urn:li:share:42").await.unwrap();
+        let fetched = publisher.fetch_metrics("urn:li:share:42").await.unwrap();
+
+        assert_eq!((fetched.likes, fetched.comments, fetched.shares), (Some(7), Some(2), None));
+        delete.assert_async().await;
+        metrics.assert_async().await;
+    }
+}
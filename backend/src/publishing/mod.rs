@@ -0,0 +1,3 @@
+pub mod linkedin;
+pub mod publisher;
+pub mod webhook;
@@ -0,0 +1,239 @@
+use crate::clock::Clock;
+use crate::config::secret::Secret;
+use crate::config::settings::{is_http_url, Settings};
+use crate::models::post::{Media, Post, Target, DEFAULT_CHANNEL};
+use crate::publishing::linkedin::LinkedInPublisher;
+use crate::publishing::webhook::{WebhookPublisher, DEFAULT_TIMEOUT_SECS};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Error returned by publisher operations.
+///
+/// Only the message of the backend's error is kept, so that it can be recorded on the post
+/// and the error can be sent between tasks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishError(String);
+
+impl fmt::Display for PublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PublishError {}
+
+impl From<Box<dyn std::error::Error>> for PublishError {
+    fn from(error: Box<dyn std::error::Error>) -> Self {
+        PublishError(error.to_string())
+    }
+}
+
+impl From<String> for PublishError {
+    fn from(message: String) -> Self {
+        PublishError(message)
+    }
+}
+
+impl From<&str> for PublishError {
+    fn from(message: &str) -> Self {
+        PublishError(message.to_string())
+    }
+}
+
+/// What a publisher publishes: the parts of a post that end up on one of its targets.
+#[derive(Debug, Clone)]
+pub struct Publication<'a> {
+    pub title: &'a str,
+    /// The text of the target (see `Target::text`).
+    pub content: Cow<'a, str>,
+    /// The account the post is published on behalf of, `None` for the owner of the channel's
+    /// credentials.
+    pub author: Option<&'a str>,
+    pub media: &'a [Media],
+}
+
+impl<'a> Publication<'a> {
+    /// Returns the publication of `post` on `target`, with the target's account and text.
+    pub fn of(post: &'a Post, target: &'a Target) -> Self {
+        Publication {
+            title: &post.title,
+            content: Cow::Owned(target.text(&post.content)),
+            author: post.account_of(target),
+            media: &post.media,
+        }
+    }
+}
+
+/// Engagement counters of a published post; `None` for the counters a channel does not have.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PostMetrics {
+    pub likes: Option<u64>,
+    pub comments: Option<u64>,
+    pub shares: Option<u64>,
+    pub impressions: Option<u64>,
+}
+
+/// A social network, or any other destination, posts are published to.
+///
+/// # Functionality
+///
+/// The scheduler publishes each post through the publisher of every channel it targets (see
+/// `Post::targets`), so supporting a new network only takes a new implementation of this trait
+/// registered in `publishers_for`. Implementations hold their own credentials and endpoints.
+///
+/// The ID returned by `publish` is recorded on the post's target and is what `delete` and
+/// `fetch_metrics` expect.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// let publishers = publishers_for(&settings, &access_token, clock);
+/// let publisher = &publishers["linkedin"];
+/// let remote_id = publisher.publish(&Publication::of(&post, &post.targets[0])).await?;
+/// let metrics = publisher.fetch_metrics(remote_id.as_deref().unwrap()).await?;
+/// ```
+#[async_trait]
+pub trait Publisher: Send + Sync {
+    /// Publishes `publication` and returns its ID on the channel, if the channel returns one.
+    async fn publish(&self, publication: &Publication<'_>) -> Result<Option<String>, PublishError>;
+
+    /// Describes the request `publish` would send for `publication`, without sending it, for
+    /// the scheduler's dry-run mode. Secrets must not appear in the description.
+    async fn dry_run(&self, publication: &Publication<'_>) -> Result<String, PublishError>;
+
+    /// Deletes the published post with the given ID from the channel.
+    async fn delete(&self, remote_id: &str) -> Result<(), PublishError>;
+
+    /// Fetches the engagement counters of the published post with the given ID.
+    async fn fetch_metrics(&self, remote_id: &str) -> Result<PostMetrics, PublishError>;
+}
+
+/// A channel configured in the `[channels]` table, keyed by its name, which posts target.
+///
+/// It is written as a table tagged by `kind`:
+///
+/// ```toml
+/// [channels.crm]
+/// kind = "webhook"
+/// url = "https://crm.example.com/hooks/posts"
+/// secret = "shared-secret"
+/// timeout_secs = 10
+/// ```
+///
+/// The secret is better given by the `LINKEDIN_AUTOMATION__CHANNELS__CRM__SECRET` environment
+/// variable; it is redacted by `--print-config`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ChannelSettings {
+    /// POSTs posts as JSON to `url`, signed with `secret` (see `WebhookPublisher`). Each request
+    /// fails after `timeout_secs` seconds, `DEFAULT_TIMEOUT_SECS` by default.
+    Webhook {
+        url: String,
+        secret: Secret,
+        #[serde(default = "default_webhook_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+/// Returns the default `timeout_secs` of a webhook channel.
+fn default_webhook_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl ChannelSettings {
+    /// Checks the channel named `name`, reporting errors under the settings path `key`.
+    pub fn validate(&self, name: &str, key: &str, errors: &mut Vec<String>) {
+        if name == DEFAULT_CHANNEL {
+            errors.push(format!("{}: the {:?} channel name is reserved", key, DEFAULT_CHANNEL));
+        }
+        match self {
+            ChannelSettings::Webhook { url, secret, timeout_secs } => {
+                if !is_http_url(url) {
+                    errors.push(format!("{}.url: {:?} is not an http(s) URL", key, url));
+                }
+                if secret.is_empty() {
+                    errors.push(format!("{}.secret: must not be empty", key));
+                }
+                if *timeout_secs == 0 {
+                    errors.push(format!("{}.timeout_secs: must be greater than 0", key));
+                }
+            }
+        }
+    }
+}
+
+/// The publishers of the available channels, keyed by channel name.
+pub type Publishers = BTreeMap<String, Arc<dyn Publisher>>;
+
+/// Returns the names of the channels available with `settings`, which posts may target:
+/// `DEFAULT_CHANNEL` and the configured `[channels]`.
+pub fn channel_names(settings: &Settings) -> Vec<String> {
+    let mut names = vec![DEFAULT_CHANNEL.to_string()];
+    names.extend(settings.channels.keys().cloned());
+    names
+}
+
+/// Returns the publishers of the channels available with `settings`.
+///
+/// `DEFAULT_CHANNEL` is LinkedIn, published to with `access_token`, or the account's token in
+/// `linkedin.access_tokens`, through the `linkedin.api_base_url` endpoint; the other channels
+/// come from the `[channels]` table. Webhooks time and sign their requests with `clock`.
+pub fn publishers_for(settings: &Settings, access_token: &Secret, clock: Arc<dyn Clock>) -> Publishers {
+    let mut publishers = Publishers::new();
+    let linkedin = LinkedInPublisher::new(access_token.clone(), &settings.linkedin.api_base_url)
+        .with_account_tokens(settings.linkedin.access_tokens.clone());
+    publishers.insert(DEFAULT_CHANNEL.to_string(), Arc::new(linkedin));
+    for (name, channel) in &settings.channels {
+        let publisher: Arc<dyn Publisher> = match channel {
+            ChannelSettings::Webhook { url, secret, timeout_secs } => {
+                let timeout = Duration::from_secs(*timeout_secs);
+                Arc::new(WebhookPublisher::new(name, url, secret.clone(), timeout, clock.clone()))
+            }
+        };
+        publishers.insert(name.clone(), publisher);
+    }
+    publishers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+
+    /// Tests that the configured channels are available next to LinkedIn and validated.
+    #[test]
+    fn test_configured_channels() {
+        let settings = Settings::from_sources(
+            Some("[channels.crm]\nkind = \"webhook\"\nurl = \"http://localhost:9000/hooks\""),
+            vec![("LINKEDIN_AUTOMATION__CHANNELS__CRM__SECRET".to_string(), "shared-secret".to_string())],
+        )
+        .unwrap();
+
+        assert_eq!(channel_names(&settings), vec!["linkedin".to_string(), "crm".to_string()]);
+        let publishers = publishers_for(&settings, &Secret::new("token"), Arc::new(SystemClock));
+        assert_eq!(publishers.keys().collect::<Vec<_>>(), vec!["crm", "linkedin"]);
+        assert!(!settings.redacted().to_toml().contains("shared-secret"));
+
+        let mut errors = Vec::new();
+        let invalid = ChannelSettings::Webhook {
+            url: "ftp://example.com".to_string(),
+            secret: Secret::default(),
+            timeout_secs: 0,
+        };
+        invalid.validate("linkedin", "channels.linkedin", &mut errors);
+        assert_eq!(
+            errors,
+            vec![
+                "channels.linkedin: the \"linkedin\" channel name is reserved",
+                "channels.linkedin.url: \"ftp://example.com\" is not an http(s) URL",
+                "channels.linkedin.secret: must not be empty",
+                "channels.linkedin.timeout_secs: must be greater than 0",
+            ]
+        );
+    }
+}
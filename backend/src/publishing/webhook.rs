@@ -0,0 +1,309 @@
+use crate::clock::Clock;
+use crate::config::secret::Secret;
+use crate::publishing::publisher::{PostMetrics, Publication, PublishError, Publisher};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Client;
+use ring::hmac;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Header carrying the signature of a webhook request.
+///
+/// Its value is `sha256=` followed by the hex-encoded HMAC-SHA256 of the request's timestamp
+/// and body (see `sign`), keyed with the channel's secret, so that the receiver can check that
+/// the request comes from the scheduler.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Header carrying the time a webhook request was sent, in seconds since the Unix epoch.
+///
+/// It is covered by the signature, so a receiver rejecting requests whose timestamp is more
+/// than a few minutes old cannot be replayed a captured request later.
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
+/// Default time, in seconds, a webhook request may take, connection included, before it fails.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Publishes posts by POSTing them as signed JSON to a URL, to feed internal tools or to test
+/// the scheduling pipeline against a local HTTP receiver.
+///
+/// # Functionality
+///
+/// Each post is sent as a `post.published` event:
+///
+/// ```json
+/// {
+///   "event": "post.published",
+///   "channel": "crm",
+///   "title": "Hiring",
+///   "content": "We are hiring!\n\n#jobs",
+///   "author": "company-page-id",
+///   "media": [],
+///   "sent_at": "2024-05-10T12:00:00+00:00"
+/// }
+/// ```
+///
+/// `content` is the text of the target and `author` its account, `null` if none. Any 2xx
+/// response is a success; if its body is a JSON object with a string `id`, that is the post's
+/// ID on the channel. Deleting a post sends a `post.deleted` event with that `id`. Webhooks have
+/// no metrics.
+///
+/// Every request carries the `X-Signature-Timestamp` and `X-Signature-256` headers (see
+/// `TIMESTAMP_HEADER` and `SIGNATURE_HEADER`); the timestamp and `sent_at` are the current time
+/// of the publisher's `Clock`. Requests go through one HTTP client, reusing its connections,
+/// and fail after the channel's timeout so that an unresponsive receiver cannot hold up the
+/// scheduler.
+pub struct WebhookPublisher {
+    channel: String,
+    url: String,
+    secret: Secret,
+    client: Client,
+    clock: Arc<dyn Clock>,
+}
+
+impl WebhookPublisher {
+    /// Creates the publisher of the channel named `channel`, sending to `url` and signing with
+    /// `secret` the requests it sends at the time of `clock`. Connecting and sending each
+    /// request must take less than `timeout`.
+    pub fn new(channel: &str, url: &str, secret: Secret, timeout: Duration, clock: Arc<dyn Clock>) -> Self {
+        let client = Client::builder()
+            .connect_timeout(timeout)
+            .timeout(timeout)
+            .build()
+            .unwrap_or_else(|e| {
+                error!("Cannot configure the {} webhook client, using the defaults: {}", channel, e);
+                Client::new()
+            });
+        WebhookPublisher {
+            channel: channel.to_string(),
+            url: url.to_string(),
+            secret,
+            client,
+            clock,
+        }
+    }
+
+    /// Returns the JSON event sent at `now` to publish `publication`.
+    fn published_event(&self, publication: &Publication<'_>, now: DateTime<Utc>) -> serde_json::Value {
+        serde_json::json!({
+            "event": "post.published",
+            "channel": self.channel,
+            "title": publication.title,
+            "content": publication.content,
+            "author": publication.author,
+            "media": publication.media,
+            "sent_at": now.to_rfc3339(),
+        })
+    }
+
+    /// Sends `event`, signed with the timestamp `now`, and returns the body of the response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or times out, or the receiver responds with a
+    /// non-2xx status.
+    async fn send(&self, event: &serde_json::Value, now: DateTime<Utc>) -> Result<String, PublishError> {
+        let body = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+        info!("Sending {} to the {} webhook at {}", event["event"], self.channel, self.url);
+
+        let timestamp = now.timestamp();
+        let response = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(&self.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let status = response.status();
+        let text = response.text().await.map_err(|e| e.to_string())?;
+        if status.is_success() {
+            Ok(text)
+        } else {
+            error!("The {} webhook responded {}: {:?}", self.channel, status, text);
+            Err(format!("The webhook responded {}", status).into())
+        }
+    }
+}
+
+#[async_trait]
+impl Publisher for WebhookPublisher {
+    async fn publish(&self, publication: &Publication<'_>) -> Result<Option<String>, PublishError> {
+        let now = self.clock.now();
+        let response = self.send(&self.published_event(publication, now), now).await?;
+        let id = serde_json::from_str::<serde_json::Value>(&response)
+            .ok()
+            .and_then(|body| body["id"].as_str().map(str::to_string));
+        Ok(id)
+    }
+
+    async fn dry_run(&self, publication: &Publication<'_>) -> Result<String, PublishError> {
+        Ok(format!("POST {} {}", self.url, self.published_event(publication, self.clock.now())))
+    }
+
+    async fn delete(&self, remote_id: &str) -> Result<(), PublishError> {
+        let now = self.clock.now();
+        let event = serde_json::json!({
+            "event": "post.deleted",
+            "channel": self.channel,
+            "id": remote_id,
+            "sent_at": now.to_rfc3339(),
+        });
+        self.send(&event, now).await.map(|_| ())
+    }
+
+    async fn fetch_metrics(&self, _remote_id: &str) -> Result<PostMetrics, PublishError> {
+        Ok(PostMetrics::default())
+    }
+}
+
+/// Returns the value of the `X-Signature-256` header of a request with the given
+/// `X-Signature-Timestamp` and body: `sha256=` and the hex-encoded HMAC-SHA256, keyed with
+/// `secret`, of the timestamp, a `.` and `body`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// // On the receiving side, with the raw body of the request:
+/// let timestamp: i64 = request.headers()["X-Signature-Timestamp"].parse()?;
+/// assert!((Utc::now().timestamp() - timestamp).abs() <= 300, "replayed request");
+/// let expected = sign(&Secret::new("shared-secret"), timestamp, body);
+/// assert_eq!(request.headers()["X-Signature-256"], expected);
+/// ```
+pub fn sign(secret: &Secret, timestamp: i64, body: &[u8]) -> String {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    hmac_sha256(secret, &message)
+}
+
+/// Returns `sha256=` and the hex-encoded HMAC-SHA256 of `message` keyed with `secret`.
+fn hmac_sha256(secret: &Secret, message: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.expose().as_bytes());
+    let tag = hmac::sign(&key, message);
+    let hex: String = tag.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{SystemClock, TestClock};
+    use chrono::TimeZone;
+    use mockito::{Matcher, Server};
+
+    /// Tests the HMAC against the test vector of RFC 4231 (test case 2), and that the signature
+    /// covers the timestamp.
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            hmac_sha256(&Secret::new("Jefe"), b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let secret = Secret::new("shared-secret");
+        assert_eq!(sign(&secret, 1715342400, b"{}"), hmac_sha256(&secret, b"1715342400.{}"));
+        assert_ne!(sign(&secret, 1715342400, b"{}"), sign(&secret, 1715342401, b"{}"));
+    }
+
+    /// Tests that a post is sent as a `post.published` event signed with the time of the
+    /// publisher's clock, and its ID read back.
+    #[tokio::test]
+    async fn test_publish_sends_signed_event() {
+        let sent_at = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/hooks/posts")
+            .match_header("content-type", "application/json")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "event": "post.published",
+                "channel": "crm",
+                "content": "We are hiring!",
+                "author": "company",
+            })))
+            .match_request(move |request| {
+                let (timestamp, signature) = (request.header(TIMESTAMP_HEADER), request.header(SIGNATURE_HEADER));
+                let (Some(timestamp), Some(signature)) = (timestamp.first(), signature.first()) else {
+                    return false;
+                };
+                let Some(timestamp) = timestamp.to_str().ok().and_then(|value| value.parse::<i64>().ok()) else {
+                    return false;
+                };
+                let body = request.body().unwrap();
+                let event: serde_json::Value = serde_json::from_slice(body).unwrap();
+                let event_time = DateTime::parse_from_rfc3339(event["sent_at"].as_str().unwrap()).unwrap();
+                // The clock moves on with the test, so only the minute is certain.
+                (0..60).contains(&(timestamp - sent_at.timestamp()))
+                    && event_time.timestamp() == timestamp
+                    && *signature == sign(&Secret::new("shared-secret"), timestamp, body).as_str()
+            })
+            .with_status(200)
+            .with_body(r#"{"id": "crm-42"}"#)
+            .create_async()
+            .await;
+
+        let url = format!("{}/hooks/posts", server.url());
+        let secret = Secret::new("shared-secret");
+        let clock = Arc::new(TestClock::new(sent_at));
+        let publisher = WebhookPublisher::new("crm", &url, secret, Duration::from_secs(10), clock);
+        let publication = Publication {
+            title: "Hiring",
+            content: "We are hiring!".into(),
+            author: Some("company"),
+            media: &[],
+        };
+
+        assert_eq!(publisher.publish(&publication).await.unwrap().as_deref(), Some("crm-42"));
+        mock.assert_async().await;
+    }
+
+    /// Tests that a non-2xx response is a publication error.
+    #[tokio::test]
+    async fn test_publish_failure() {
+        let mut server = Server::new_async().await;
+        let mock = server.mock("POST", "/").with_status(503).create_async().await;
+
+        let secret = Secret::new("shared-secret");
+        let url = server.url();
+        let publisher = WebhookPublisher::new("crm", &url, secret, Duration::from_secs(10), Arc::new(SystemClock));
+        let publication = Publication {
+            title: "Hiring",
+            content: "We are hiring!".into(),
+            author: None,
+            media: &[],
+        };
+
+        let error = publisher.publish(&publication).await.unwrap_err();
+        assert!(error.to_string().contains("503"), "{}", error);
+        mock.assert_async().await;
+    }
+
+    /// Tests that a receiver accepting the connection but never responding fails the
+    /// publication once the timeout has elapsed.
+    #[tokio::test]
+    async fn test_publish_timeout() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+        let receiver = tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+
+        let secret = Secret::new("shared-secret");
+        let publisher = WebhookPublisher::new("crm", &url, secret, Duration::from_millis(200), Arc::new(SystemClock));
+        let publication = Publication {
+            title: "Hiring",
+            content: "We are hiring!".into(),
+            author: None,
+            media: &[],
+        };
+
+        let started = std::time::Instant::now();
+        assert!(publisher.publish(&publication).await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        receiver.abort();
+    }
+}
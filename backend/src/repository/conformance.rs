@@ -0,0 +1,263 @@
+//! Behavioral tests shared by every `PostRepository` implementation.
+//!
+//! Each backend's test module calls these functions with a fresh, empty repository, so
+//! that all storage backends are held to the same semantics.
+
+use crate::models::pause::{Pause, GLOBAL_SCOPE};
+use crate::models::post::{Post, PostStatus, StatusTransition};
+use crate::models::secret::EncryptedSecret;
+use crate::repository::pauses::PauseRepository;
+use crate::repository::posts::{PostChanges, PostRepository};
+use crate::repository::secrets::SecretRepository;
+use chrono::{TimeZone, Utc};
+use futures_util::StreamExt;
+use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+use std::time::Duration;
+
+fn post_at(hour: u32) -> Post {
+    let scheduled_time = Utc.with_ymd_and_hms(2024, 5, 10, hour, 0, 0).unwrap();
+    Post::new(
+        format!("Post at {}", hour),
+        "Content".to_string(),
+        BsonDateTime::from_chrono(scheduled_time),
+        Utc::now(),
+    )
+}
+
+/// Tests that an inserted post is returned unchanged by `find`.
+pub async fn insert_and_find(repository: &dyn PostRepository) {
+    let mut post = post_at(9);
+    post.author = Some("urn:li:person:abc".to_string());
+    let id = repository.insert(post.clone()).await.unwrap();
+    post.id = Some(id);
+
+    assert_eq!(repository.find(id).await.unwrap(), Some(post));
+    assert_eq!(repository.find(ObjectId::new()).await.unwrap(), None);
+}
+
+/// Tests that only scheduled posts at or before `now` are due, in chronological order.
+pub async fn find_due(repository: &dyn PostRepository) {
+    let late = repository.insert(post_at(11)).await.unwrap();
+    let early = repository.insert(post_at(9)).await.unwrap();
+    repository.insert(post_at(13)).await.unwrap();
+    let mut draft = post_at(8);
+    draft.status = PostStatus::Draft;
+    repository.insert(draft).await.unwrap();
+
+    let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+    let due: Vec<_> = repository
+        .find_due(now)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|post| post.id.unwrap())
+        .collect();
+
+    assert_eq!(due, vec![early, late]);
+}
+
+/// Tests that the next scheduled time is the earliest one among `scheduled` posts only.
+pub async fn next_scheduled_time(repository: &dyn PostRepository) {
+    assert_eq!(repository.next_scheduled_time().await.unwrap(), None);
+
+    repository.insert(post_at(11)).await.unwrap();
+    let early = repository.insert(post_at(9)).await.unwrap();
+    let mut draft = post_at(8);
+    draft.status = PostStatus::Draft;
+    repository.insert(draft).await.unwrap();
+
+    let nine = Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap();
+    assert_eq!(repository.next_scheduled_time().await.unwrap(), Some(nine));
+
+    repository.claim(early, "scheduler", Utc::now()).await.unwrap();
+    let eleven = Utc.with_ymd_and_hms(2024, 5, 10, 11, 0, 0).unwrap();
+    assert_eq!(repository.next_scheduled_time().await.unwrap(), Some(eleven));
+}
+
+/// Tests that inserting and rescheduling a post wake up the subscribers of `watch`.
+pub async fn watch_notifies_writes(repository: &dyn PostRepository) {
+    let mut changes = repository.watch().await.unwrap();
+
+    let id = repository.insert(post_at(9)).await.unwrap();
+    assert_eq!(next_change(&mut changes).await, Some(()));
+
+    let mut post = repository.find(id).await.unwrap().unwrap();
+    post.scheduled_time = post_at(10).scheduled_time;
+    repository.update(&post, PostStatus::Scheduled).await.unwrap();
+    assert_eq!(next_change(&mut changes).await, Some(()));
+}
+
+async fn next_change(changes: &mut PostChanges) -> Option<()> {
+    tokio::time::timeout(Duration::from_secs(5), changes.next())
+        .await
+        .expect("no change notification within 5 seconds")
+}
+
+/// Tests that `find_in_range` filters by status and includes both bounds.
+pub async fn find_in_range(repository: &dyn PostRepository) {
+    let first = repository.insert(post_at(9)).await.unwrap();
+    let last = repository.insert(post_at(12)).await.unwrap();
+    repository.insert(post_at(13)).await.unwrap();
+    let mut draft = post_at(10);
+    draft.status = PostStatus::Draft;
+    repository.insert(draft).await.unwrap();
+
+    let start = Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+    let found: Vec<_> = repository
+        .find_in_range(start, end, PostStatus::Scheduled)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|post| post.id.unwrap())
+        .collect();
+
+    assert_eq!(found, vec![first, last]);
+}
+
+/// Tests that `find_published_in_range` selects posts by when they went out, whenever they
+/// were scheduled.
+pub async fn find_published_in_range(repository: &dyn PostRepository) {
+    let at = |hour: u32| Utc.with_ymd_and_hms(2024, 5, 10, hour, 0, 0).unwrap();
+    let published = repository.insert(post_at(9)).await.unwrap();
+    repository.claim(published, "scheduler", at(12)).await.unwrap();
+    let transition = StatusTransition::new(Some(PostStatus::Publishing), PostStatus::Published, "scheduler", at(13));
+    repository.transition(published, transition).await.unwrap();
+    let claimed = repository.insert(post_at(10)).await.unwrap();
+    repository.claim(claimed, "scheduler", at(13)).await.unwrap();
+    let claimed_later = repository.insert(post_at(11)).await.unwrap();
+    repository.claim(claimed_later, "scheduler", at(15)).await.unwrap();
+    repository.insert(post_at(13)).await.unwrap();
+
+    let found = |status: PostStatus| async move {
+        let posts = repository.find_published_in_range(at(12), at(14), status).await.unwrap();
+        posts.into_iter().map(|post| post.id.unwrap()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(found(PostStatus::Published).await, vec![published]);
+    assert_eq!(found(PostStatus::Publishing).await, vec![claimed]);
+    assert_eq!(found(PostStatus::Scheduled).await, Vec::new());
+}
+
+/// Tests that a post can only be claimed once.
+pub async fn claim_is_exclusive(repository: &dyn PostRepository) {
+    let id = repository.insert(post_at(9)).await.unwrap();
+
+    assert!(repository.claim(id, "scheduler-a", Utc::now()).await.unwrap());
+    assert!(!repository.claim(id, "scheduler-b", Utc::now()).await.unwrap());
+
+    let post = repository.find(id).await.unwrap().unwrap();
+    assert_eq!(post.status, PostStatus::Publishing);
+    assert_eq!(post.status_history.len(), 1);
+    assert_eq!(post.status_history[0].actor, "scheduler-a");
+}
+
+/// Tests that a transition to `published` records `published_at` and the history entry.
+pub async fn transition_to_published(repository: &dyn PostRepository) {
+    let id = repository.insert(post_at(9)).await.unwrap();
+    repository.claim(id, "scheduler", Utc::now()).await.unwrap();
+
+    let transition =
+        StatusTransition::new(Some(PostStatus::Publishing), PostStatus::Published, "scheduler", Utc::now());
+    let at = transition.at;
+    assert!(repository.transition(id, transition).await.unwrap());
+
+    let post = repository.find(id).await.unwrap().unwrap();
+    assert_eq!(post.status, PostStatus::Published);
+    assert_eq!(post.published_at, Some(at));
+    assert_eq!(post.updated_at, Some(at));
+    assert_eq!(post.status_history.len(), 2);
+}
+
+/// Tests that an update is rejected when the stored status is not the expected one.
+pub async fn update_checks_expected_status(repository: &dyn PostRepository) {
+    let id = repository.insert(post_at(9)).await.unwrap();
+    let mut post = repository.find(id).await.unwrap().unwrap();
+    post.title = "Edited".to_string();
+
+    assert!(!repository.update(&post, PostStatus::Draft).await.unwrap());
+    assert!(repository.update(&post, PostStatus::Scheduled).await.unwrap());
+    assert_eq!(repository.find(id).await.unwrap().unwrap().title, "Edited");
+}
+
+/// Tests that a deleted post is gone and that deleting it again reports it as missing.
+pub async fn delete(repository: &dyn PostRepository) {
+    let id = repository.insert(post_at(9)).await.unwrap();
+
+    assert!(repository.delete(id).await.unwrap());
+    assert!(!repository.delete(id).await.unwrap());
+    assert_eq!(repository.find(id).await.unwrap(), None);
+}
+
+/// Tests that a stored secret is returned unchanged and replaced when stored again.
+pub async fn secrets_round_trip(repository: &dyn SecretRepository) {
+    let secret = |ciphertext: &str| EncryptedSecret {
+        name: "linkedin_access_token".to_string(),
+        nonce: "bm9uY2U=".to_string(),
+        ciphertext: ciphertext.to_string(),
+        updated_at: BsonDateTime::from_millis(1_715_342_400_000),
+    };
+
+    assert_eq!(repository.get("linkedin_access_token").await.unwrap(), None);
+    repository.put(secret("Zmlyc3Q=")).await.unwrap();
+    repository.put(secret("c2Vjb25k")).await.unwrap();
+    assert_eq!(repository.get("linkedin_access_token").await.unwrap(), Some(secret("c2Vjb25k")));
+}
+
+/// Checks that pauses are stored, replaced per scope, listed in scope order and removed.
+pub async fn pauses_round_trip(repository: &dyn PauseRepository) {
+    let pause = |account: Option<&str>, reason: &str| Pause {
+        paused_at: BsonDateTime::from_millis(1_715_342_400_000),
+        ..Pause::new(account, reason, "alice")
+    };
+
+    assert_eq!(repository.list().await.unwrap(), vec![]);
+    repository.put(pause(None, "Crisis")).await.unwrap();
+    repository.put(pause(Some("company"), "Rebranding")).await.unwrap();
+    repository.put(pause(None, "Still a crisis")).await.unwrap();
+    assert_eq!(
+        repository.list().await.unwrap(),
+        vec![pause(Some("company"), "Rebranding"), pause(None, "Still a crisis")]
+    );
+
+    assert!(repository.remove(GLOBAL_SCOPE).await.unwrap());
+    assert!(!repository.remove(GLOBAL_SCOPE).await.unwrap());
+    assert_eq!(repository.list().await.unwrap(), vec![pause(Some("company"), "Rebranding")]);
+}
+
+/// Generates one `#[tokio::test]` per conformance test, each running against the
+/// repository produced by `$repository` (evaluated in an async context, so it may `.await`).
+/// Attributes listed after the repository, such as `#[ignore]`, are applied to every
+/// generated test.
+macro_rules! conformance_tests {
+    ($repository:expr $(, #[$attribute:meta])* $(,)?) => {
+        conformance_tests!(@test test_insert_and_find, insert_and_find, $repository $(, #[$attribute])*);
+        conformance_tests!(@test test_find_due, find_due, $repository $(, #[$attribute])*);
+        conformance_tests!(@test test_next_scheduled_time, next_scheduled_time, $repository $(, #[$attribute])*);
+        conformance_tests!(@test test_find_in_range, find_in_range, $repository $(, #[$attribute])*);
+        conformance_tests!(
+            @test test_find_published_in_range,
+            find_published_in_range,
+            $repository $(, #[$attribute])*
+        );
+        conformance_tests!(@test test_claim_is_exclusive, claim_is_exclusive, $repository $(, #[$attribute])*);
+        conformance_tests!(@test test_transition_to_published, transition_to_published, $repository $(, #[$attribute])*);
+        conformance_tests!(
+            @test test_update_checks_expected_status,
+            update_checks_expected_status,
+            $repository $(, #[$attribute])*
+        );
+        conformance_tests!(@test test_delete, delete, $repository $(, #[$attribute])*);
+        conformance_tests!(@test test_watch_notifies_writes, watch_notifies_writes, $repository $(, #[$attribute])*);
+    };
+    (@test $name:ident, $check:ident, $repository:expr $(, #[$attribute:meta])*) => {
+        #[tokio::test]
+        $(#[$attribute])*
+        async fn $name() {
+            let repository = $repository;
+            crate::repository::conformance::$check(&repository).await;
+        }
+    };
+}
+
+pub(crate) use conformance_tests;
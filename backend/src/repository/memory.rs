@@ -0,0 +1,213 @@
+use crate::models::pause::Pause;
+use crate::models::post::{Post, PostStatus, StatusTransition};
+use crate::models::secret::EncryptedSecret;
+use crate::repository::pauses::PauseRepository;
+use crate::repository::posts::{notifications, PostChanges, PostRepository, RepositoryError};
+use crate::repository::secrets::SecretRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// `PostRepository` keeping posts in memory.
+///
+/// Used by the test suite so that the web routes and the scheduler can be exercised
+/// without a running MongoDB. It follows the same conditional-update semantics as
+/// `MongoPostRepository`. Every write notifies the subscribers of `watch`.
+#[derive(Default)]
+pub struct InMemoryPostRepository {
+    posts: Mutex<HashMap<ObjectId, Post>>,
+    changes: watch::Sender<()>,
+}
+
+impl InMemoryPostRepository {
+    /// Creates an empty repository.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of every stored post, ordered by `scheduled_time`.
+    pub fn all(&self) -> Vec<Post> {
+        self.filter_sorted(|_| true)
+    }
+
+    fn filter_sorted(&self, predicate: impl Fn(&Post) -> bool) -> Vec<Post> {
+        let posts = self.posts.lock().unwrap();
+        let mut results: Vec<Post> = posts.values().filter(|post| predicate(post)).cloned().collect();
+        results.sort_by_key(|post| post.scheduled_time);
+        results
+    }
+}
+
+#[async_trait]
+impl PostRepository for InMemoryPostRepository {
+    async fn insert(&self, mut post: Post) -> Result<ObjectId, RepositoryError> {
+        let id = *post.id.get_or_insert_with(ObjectId::new);
+        let mut posts = self.posts.lock().unwrap();
+        if posts.contains_key(&id) {
+            return Err(format!("Duplicate post ID {}", id).into());
+        }
+        posts.insert(id, post);
+        self.changes.send_replace(());
+        Ok(id)
+    }
+
+    async fn find(&self, id: ObjectId) -> Result<Option<Post>, RepositoryError> {
+        Ok(self.posts.lock().unwrap().get(&id).cloned())
+    }
+
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Post>, RepositoryError> {
+        Ok(self.filter_sorted(|post| {
+            post.status == PostStatus::Scheduled && post.scheduled_time.to_chrono() <= now
+        }))
+    }
+
+    async fn next_scheduled_time(&self) -> Result<Option<DateTime<Utc>>, RepositoryError> {
+        let posts = self.posts.lock().unwrap();
+        Ok(posts
+            .values()
+            .filter(|post| post.status == PostStatus::Scheduled)
+            .map(|post| post.scheduled_time.to_chrono())
+            .min())
+    }
+
+    async fn find_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        status: PostStatus,
+    ) -> Result<Vec<Post>, RepositoryError> {
+        Ok(self.filter_sorted(|post| {
+            let scheduled_time = post.scheduled_time.to_chrono();
+            post.status == status && scheduled_time >= start && scheduled_time <= end
+        }))
+    }
+
+    async fn find_published_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        status: PostStatus,
+    ) -> Result<Vec<Post>, RepositoryError> {
+        Ok(self.filter_sorted(|post| {
+            post.status == status && post.publication_time().is_some_and(|time| time >= start && time <= end)
+        }))
+    }
+
+    async fn update(&self, post: &Post, expected_status: PostStatus) -> Result<bool, RepositoryError> {
+        let Some(id) = post.id else {
+            return Ok(false);
+        };
+        let mut posts = self.posts.lock().unwrap();
+        match posts.get_mut(&id) {
+            Some(stored) if stored.status == expected_status => {
+                *stored = post.clone();
+                self.changes.send_replace(());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn transition(&self, id: ObjectId, transition: StatusTransition) -> Result<bool, RepositoryError> {
+        let mut posts = self.posts.lock().unwrap();
+        let Some(stored) = posts.get_mut(&id) else {
+            return Ok(false);
+        };
+        if transition.from.is_some_and(|from| from != stored.status) {
+            return Ok(false);
+        }
+
+        stored.record_transition(transition);
+        self.changes.send_replace(());
+        Ok(true)
+    }
+
+    async fn delete(&self, id: ObjectId) -> Result<bool, RepositoryError> {
+        let deleted = self.posts.lock().unwrap().remove(&id).is_some();
+        if deleted {
+            self.changes.send_replace(());
+        }
+        Ok(deleted)
+    }
+
+    async fn watch(&self) -> Result<PostChanges, RepositoryError> {
+        Ok(notifications(self.changes.subscribe()))
+    }
+}
+
+/// `SecretRepository` keeping secrets in memory, for tests.
+#[derive(Default)]
+pub struct InMemorySecretRepository {
+    secrets: Mutex<HashMap<String, EncryptedSecret>>,
+}
+
+impl InMemorySecretRepository {
+    /// Creates an empty repository.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SecretRepository for InMemorySecretRepository {
+    async fn get(&self, name: &str) -> Result<Option<EncryptedSecret>, RepositoryError> {
+        Ok(self.secrets.lock().unwrap().get(name).cloned())
+    }
+
+    async fn put(&self, secret: EncryptedSecret) -> Result<(), RepositoryError> {
+        self.secrets.lock().unwrap().insert(secret.name.clone(), secret);
+        Ok(())
+    }
+}
+
+/// `PauseRepository` keeping pauses in memory, for tests.
+#[derive(Default)]
+pub struct InMemoryPauseRepository {
+    pauses: Mutex<BTreeMap<String, Pause>>,
+}
+
+impl InMemoryPauseRepository {
+    /// Creates an empty repository.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PauseRepository for InMemoryPauseRepository {
+    async fn list(&self) -> Result<Vec<Pause>, RepositoryError> {
+        Ok(self.pauses.lock().unwrap().values().cloned().collect())
+    }
+
+    async fn put(&self, pause: Pause) -> Result<(), RepositoryError> {
+        self.pauses.lock().unwrap().insert(pause.scope.clone(), pause);
+        Ok(())
+    }
+
+    async fn remove(&self, scope: &str) -> Result<bool, RepositoryError> {
+        Ok(self.pauses.lock().unwrap().remove(scope).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::conformance::conformance_tests;
+
+    conformance_tests!(InMemoryPostRepository::new());
+
+    /// Tests the secret store against the shared behavioral test.
+    #[tokio::test]
+    async fn test_secrets_round_trip() {
+        crate::repository::conformance::secrets_round_trip(&InMemorySecretRepository::new()).await;
+    }
+
+    /// Tests the pause store against the shared behavioral test.
+    #[tokio::test]
+    async fn test_pauses_round_trip() {
+        crate::repository::conformance::pauses_round_trip(&InMemoryPauseRepository::new()).await;
+    }
+}
@@ -0,0 +1,9 @@
+#[cfg(test)]
+pub(crate) mod conformance;
+pub mod memory;
+pub mod mongo;
+pub mod pauses;
+pub mod posts;
+pub mod secrets;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
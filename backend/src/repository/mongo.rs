@@ -0,0 +1,342 @@
+use crate::config::database::DatabaseConfig;
+use crate::models::pause::Pause;
+use crate::models::post::{Post, PostStatus, StatusTransition};
+use crate::models::secret::EncryptedSecret;
+use crate::repository::pauses::PauseRepository;
+use crate::repository::posts::{PostChanges, PostRepository, RepositoryError};
+use crate::repository::secrets::SecretRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{future, StreamExt, TryStreamExt};
+use log::error;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime as BsonDateTime, Document},
+    options::{ClientOptions, FindOneOptions, FindOptions},
+    Client, Collection, Database,
+};
+
+/// Connects to the MongoDB database described by `config`.
+///
+/// The connection pool size and server selection timeout are applied on top of the options
+/// parsed from `config.url`.
+///
+/// # Errors
+///
+/// Returns an error if `config.url` is not a valid MongoDB connection string.
+pub async fn connect_database(config: &DatabaseConfig) -> Result<Database, RepositoryError> {
+    let mut options = ClientOptions::parse(&config.url).await?;
+    if let Some(max_pool_size) = config.max_pool_size {
+        options.max_pool_size = Some(max_pool_size);
+    }
+    if let Some(timeout) = config.server_selection_timeout() {
+        options.server_selection_timeout = Some(timeout);
+    }
+    Ok(Client::with_options(options)?.database(&config.name))
+}
+
+/// `PostRepository` backed by a MongoDB collection.
+#[derive(Clone)]
+pub struct MongoPostRepository {
+    posts: Collection<Post>,
+}
+
+impl MongoPostRepository {
+    /// Creates a repository storing posts in the given collection.
+    pub fn new(posts: Collection<Post>) -> Self {
+        MongoPostRepository { posts }
+    }
+
+    /// Checks that the `migrate` tool has renamed the legacy `"pending"` status.
+    ///
+    /// Such posts are read as `scheduled`, but the queries and the status checks of the updates
+    /// only match the `"scheduled"` string, so they would never be published.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error telling the operator to run `migrate` if any post is still `"pending"`,
+    /// or if the collection cannot be queried.
+    pub async fn check_migrated(&self) -> Result<(), RepositoryError> {
+        let pending = self.posts.count_documents(doc! { "status": "pending" }).await?;
+        if pending > 0 {
+            return Err(format!(
+                "{} post(s) still have the legacy \"pending\" status; run `cargo run --bin migrate` first",
+                pending
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Runs `filter` sorted by `scheduled_time`, skipping documents that do not match the
+    /// `Post` model instead of failing the whole query.
+    async fn find_sorted(&self, filter: Document) -> Result<Vec<Post>, RepositoryError> {
+        let options = FindOptions::builder().sort(doc! { "scheduled_time": 1 }).build();
+        let mut cursor = self.posts.find(filter).with_options(options).await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            match result {
+                Ok(post) => results.push(post),
+                Err(e) => error!("Skipping post that does not match the Post model: {}", e),
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl PostRepository for MongoPostRepository {
+    async fn insert(&self, post: Post) -> Result<ObjectId, RepositoryError> {
+        let result = self.posts.insert_one(post).await?;
+        result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| "Inserted post has no ObjectId".into())
+    }
+
+    async fn find(&self, id: ObjectId) -> Result<Option<Post>, RepositoryError> {
+        Ok(self.posts.find_one(doc! { "_id": id }).await?)
+    }
+
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Post>, RepositoryError> {
+        self.find_sorted(doc! {
+            "scheduled_time": { "$lte": BsonDateTime::from_chrono(now) },
+            "status": PostStatus::Scheduled.as_str(),
+        })
+        .await
+    }
+
+    async fn next_scheduled_time(&self) -> Result<Option<DateTime<Utc>>, RepositoryError> {
+        let options = FindOneOptions::builder()
+            .sort(doc! { "scheduled_time": 1 })
+            .projection(doc! { "scheduled_time": 1 })
+            .build();
+        let next = self
+            .posts
+            .clone_with_type::<Document>()
+            .find_one(doc! { "status": PostStatus::Scheduled.as_str() })
+            .with_options(options)
+            .await?;
+        Ok(next
+            .and_then(|document| document.get_datetime("scheduled_time").ok().copied())
+            .map(|scheduled_time| scheduled_time.to_chrono()))
+    }
+
+    async fn find_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        status: PostStatus,
+    ) -> Result<Vec<Post>, RepositoryError> {
+        self.find_sorted(doc! {
+            "scheduled_time": {
+                "$gte": BsonDateTime::from_chrono(start),
+                "$lte": BsonDateTime::from_chrono(end),
+            },
+            "status": status.as_str(),
+        })
+        .await
+    }
+
+    /// Matches the claims recorded in `status_history` for "publishing" posts and `published_at`
+    /// for published ones, or `scheduled_time` for posts without them, like
+    /// `Post::publication_time`. A post claimed more than once may match on an earlier claim, so
+    /// the results are checked against the last one.
+    async fn find_published_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        status: PostStatus,
+    ) -> Result<Vec<Post>, RepositoryError> {
+        let range = doc! { "$gte": BsonDateTime::from_chrono(start), "$lte": BsonDateTime::from_chrono(end) };
+        let times = if status == PostStatus::Publishing {
+            let publishing = PostStatus::Publishing.as_str();
+            vec![
+                doc! { "status_history": { "$elemMatch": { "to": publishing, "at": range.clone() } } },
+                doc! { "status_history.to": { "$ne": publishing }, "scheduled_time": range },
+            ]
+        } else if status.is_published() {
+            vec![
+                doc! { "published_at": range.clone() },
+                doc! { "published_at": null, "scheduled_time": range },
+            ]
+        } else {
+            return Ok(Vec::new());
+        };
+
+        let mut posts = self.find_sorted(doc! { "status": status.as_str(), "$or": times }).await?;
+        posts.retain(|post| post.publication_time().is_some_and(|time| time >= start && time <= end));
+        Ok(posts)
+    }
+
+    async fn update(&self, post: &Post, expected_status: PostStatus) -> Result<bool, RepositoryError> {
+        let Some(id) = post.id else {
+            return Ok(false);
+        };
+        let result = self
+            .posts
+            .replace_one(doc! { "_id": id, "status": expected_status.as_str() }, post)
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
+    async fn transition(&self, id: ObjectId, transition: StatusTransition) -> Result<bool, RepositoryError> {
+        let mut filter = doc! { "_id": id };
+        if let Some(from) = transition.from {
+            filter.insert("status", from.as_str());
+        }
+
+        let mut update = transition.to_update();
+        if transition.to.is_published() {
+            if let Ok(set) = update.get_document_mut("$set") {
+                set.insert("published_at", transition.at);
+            }
+        }
+
+        let result = self.posts.update_one(filter, update).await?;
+        Ok(result.matched_count > 0)
+    }
+
+    async fn delete(&self, id: ObjectId) -> Result<bool, RepositoryError> {
+        let result = self.posts.delete_one(doc! { "_id": id }).await?;
+        Ok(result.deleted_count > 0)
+    }
+
+    /// Opens a change stream on the posts collection.
+    ///
+    /// Change streams require MongoDB to run as a replica set (a single-node replica set is
+    /// enough); on a standalone server this returns an error. The stream ends on the first
+    /// error that the driver cannot resume from.
+    async fn watch(&self) -> Result<PostChanges, RepositoryError> {
+        let changes = self.posts.clone_with_type::<Document>().watch().await?;
+        Ok(changes
+            .take_while(|event| {
+                if let Err(e) = event {
+                    error!("Post change stream stopped: {}", e);
+                }
+                future::ready(event.is_ok())
+            })
+            .map(|_| ())
+            .boxed())
+    }
+}
+
+/// `SecretRepository` backed by a MongoDB collection.
+#[derive(Clone)]
+pub struct MongoSecretRepository {
+    secrets: Collection<EncryptedSecret>,
+}
+
+impl MongoSecretRepository {
+    /// Creates a repository storing secrets in the given collection.
+    pub fn new(secrets: Collection<EncryptedSecret>) -> Self {
+        MongoSecretRepository { secrets }
+    }
+}
+
+#[async_trait]
+impl SecretRepository for MongoSecretRepository {
+    async fn get(&self, name: &str) -> Result<Option<EncryptedSecret>, RepositoryError> {
+        Ok(self.secrets.find_one(doc! { "_id": name }).await?)
+    }
+
+    async fn put(&self, secret: EncryptedSecret) -> Result<(), RepositoryError> {
+        self.secrets
+            .replace_one(doc! { "_id": &secret.name }, &secret)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+}
+
+/// `PauseRepository` backed by a MongoDB collection.
+#[derive(Clone)]
+pub struct MongoPauseRepository {
+    pauses: Collection<Pause>,
+}
+
+impl MongoPauseRepository {
+    /// Creates a repository storing pauses in the given collection.
+    pub fn new(pauses: Collection<Pause>) -> Self {
+        MongoPauseRepository { pauses }
+    }
+}
+
+#[async_trait]
+impl PauseRepository for MongoPauseRepository {
+    async fn list(&self) -> Result<Vec<Pause>, RepositoryError> {
+        let options = FindOptions::builder().sort(doc! { "_id": 1 }).build();
+        let cursor = self.pauses.find(doc! {}).with_options(options).await?;
+        Ok(cursor.try_collect().await?)
+    }
+
+    async fn put(&self, pause: Pause) -> Result<(), RepositoryError> {
+        self.pauses
+            .replace_one(doc! { "_id": &pause.scope }, &pause)
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove(&self, scope: &str) -> Result<bool, RepositoryError> {
+        let result = self.pauses.delete_one(doc! { "_id": scope }).await?;
+        Ok(result.deleted_count > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::conformance::conformance_tests;
+
+    /// Returns a repository on a fresh collection of the MongoDB at `MONGODB_TEST_URI`.
+    async fn test_repository() -> MongoPostRepository {
+        let uri = std::env::var("MONGODB_TEST_URI").expect("MONGODB_TEST_URI must be set");
+        let client = mongodb::Client::with_uri_str(&uri).await.unwrap();
+        let collection = format!("posts-test-{}", ObjectId::new());
+        MongoPostRepository::new(client.database("lkdin-posts-test").collection(&collection))
+    }
+
+    conformance_tests!(
+        test_repository().await,
+        #[ignore = "requires a MongoDB server at MONGODB_TEST_URI"]
+    );
+
+    /// Tests the secret store against the shared behavioral test.
+    #[tokio::test]
+    #[ignore = "requires a MongoDB server at MONGODB_TEST_URI"]
+    async fn test_secrets_round_trip() {
+        let uri = std::env::var("MONGODB_TEST_URI").expect("MONGODB_TEST_URI must be set");
+        let client = mongodb::Client::with_uri_str(&uri).await.unwrap();
+        let collection = format!("secrets-test-{}", ObjectId::new());
+        let repository = MongoSecretRepository::new(client.database("lkdin-posts-test").collection(&collection));
+        crate::repository::conformance::secrets_round_trip(&repository).await;
+    }
+
+    /// Tests the pause store against the shared behavioral test.
+    #[tokio::test]
+    #[ignore = "requires a MongoDB server at MONGODB_TEST_URI"]
+    async fn test_pauses_round_trip() {
+        let uri = std::env::var("MONGODB_TEST_URI").expect("MONGODB_TEST_URI must be set");
+        let client = mongodb::Client::with_uri_str(&uri).await.unwrap();
+        let collection = format!("pauses-test-{}", ObjectId::new());
+        let repository = MongoPauseRepository::new(client.database("lkdin-posts-test").collection(&collection));
+        crate::repository::conformance::pauses_round_trip(&repository).await;
+    }
+
+    /// Tests that a collection is only accepted once its legacy "pending" posts are migrated.
+    #[tokio::test]
+    #[ignore = "requires a MongoDB server at MONGODB_TEST_URI"]
+    async fn test_check_migrated() {
+        let repository = test_repository().await;
+        repository.check_migrated().await.unwrap();
+
+        let legacy = repository.posts.clone_with_type::<Document>();
+        legacy
+            .insert_one(doc! { "title": "Legacy", "content": "Content", "status": "pending" })
+            .await
+            .unwrap();
+        let error = repository.check_migrated().await.unwrap_err();
+        assert!(error.to_string().contains("run `cargo run --bin migrate`"));
+    }
+}
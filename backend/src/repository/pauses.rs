@@ -0,0 +1,60 @@
+use crate::config::database::DatabaseConfig;
+use crate::models::pause::Pause;
+use crate::repository::posts::RepositoryError;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Storage of the publishing pauses.
+///
+/// The web server writes pauses through `POST /admin/pause` and `POST /admin/resume`; the
+/// scheduler reads them before claiming each post.
+#[async_trait]
+pub trait PauseRepository: Send + Sync {
+    /// Returns every stored pause, ordered by scope.
+    async fn list(&self) -> Result<Vec<Pause>, RepositoryError>;
+
+    /// Stores `pause`, replacing any pause with the same scope.
+    async fn put(&self, pause: Pause) -> Result<(), RepositoryError>;
+
+    /// Removes the pause with the given scope.
+    ///
+    /// # Returns
+    ///
+    /// `true` if there was such a pause.
+    async fn remove(&self, scope: &str) -> Result<bool, RepositoryError>;
+}
+
+/// Opens the pause store of the database described by the database settings.
+///
+/// The backend is selected like in `repository::posts::connect`; with MongoDB, pauses are
+/// kept in the `database.pauses_collection` collection.
+///
+/// # Errors
+///
+/// Returns an error if the scheme is not supported, the SQLite backend was not compiled in,
+/// or the database cannot be opened.
+pub async fn connect_pauses(database: &DatabaseConfig) -> Result<Arc<dyn PauseRepository>, RepositoryError> {
+    let database_url = database.url.as_str();
+    if database_url.starts_with("mongodb://") || database_url.starts_with("mongodb+srv://") {
+        let db = crate::repository::mongo::connect_database(database).await?;
+        let pauses = db.collection(&database.pauses_collection);
+        return Ok(Arc::new(crate::repository::mongo::MongoPauseRepository::new(pauses)));
+    }
+
+    if let Some(path) = database_url.strip_prefix("sqlite:") {
+        let path = path.strip_prefix("//").unwrap_or(path);
+        return open_sqlite(path);
+    }
+
+    Err(format!("Unsupported database URL: {}", database_url).into())
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(path: &str) -> Result<Arc<dyn PauseRepository>, RepositoryError> {
+    Ok(Arc::new(crate::repository::sqlite::SqlitePostRepository::open(path)?.pauses()))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(_path: &str) -> Result<Arc<dyn PauseRepository>, RepositoryError> {
+    Err("SQLite support is not compiled in; rebuild with `--features sqlite`".into())
+}
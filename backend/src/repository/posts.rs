@@ -0,0 +1,246 @@
+use crate::config::database::DatabaseConfig;
+use crate::models::post::{Post, PostStatus, StatusTransition};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use mongodb::bson::oid::ObjectId;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Error returned by repository operations.
+///
+/// The error wraps the storage backend's error. It is `Send + Sync` so that repository
+/// calls can be awaited from warp handlers and spawned tasks.
+#[derive(Debug)]
+pub struct RepositoryError(Box<dyn std::error::Error + Send + Sync>);
+
+impl RepositoryError {
+    /// Wraps a storage backend error.
+    pub fn new(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        RepositoryError(Box::new(error))
+    }
+}
+
+impl fmt::Display for RepositoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Repository error: {}", self.0)
+    }
+}
+
+impl std::error::Error for RepositoryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+impl From<mongodb::error::Error> for RepositoryError {
+    fn from(error: mongodb::error::Error) -> Self {
+        RepositoryError::new(error)
+    }
+}
+
+impl From<String> for RepositoryError {
+    fn from(message: String) -> Self {
+        RepositoryError(message.into())
+    }
+}
+
+impl From<&str> for RepositoryError {
+    fn from(message: &str) -> Self {
+        RepositoryError(message.into())
+    }
+}
+
+/// Stream yielding an item whenever posts are inserted, edited, rescheduled or deleted.
+///
+/// Items carry no data: a notification only tells the scheduler to look at the posts again.
+/// The stream ends when the backend can no longer deliver notifications.
+pub type PostChanges = BoxStream<'static, ()>;
+
+/// Turns an in-process notification channel into `PostChanges`.
+///
+/// Backends without native change notifications call `send_replace(())` on the sender after
+/// every write; each write wakes the subscribers at least once.
+pub(crate) fn notifications(receiver: watch::Receiver<()>) -> PostChanges {
+    stream::unfold(receiver, |mut receiver| async move {
+        receiver.changed().await.ok()?;
+        Some(((), receiver))
+    })
+    .boxed()
+}
+
+/// Storage of scheduled posts.
+///
+/// Both binaries access posts exclusively through this trait, so the web routes and the
+/// scheduler loop can run against MongoDB (`MongoPostRepository`) in production and against
+/// memory (`InMemoryPostRepository`) in tests.
+///
+/// Methods that change a stored post are conditional on the status the caller expects the
+/// post to have, and return `Ok(false)` when the post does not exist or its status changed
+/// in the meantime. This is what prevents a post from being published twice or edited while
+/// it is being published.
+#[async_trait]
+pub trait PostRepository: Send + Sync {
+    /// Inserts a new post and returns its ID.
+    async fn insert(&self, post: Post) -> Result<ObjectId, RepositoryError>;
+
+    /// Returns the post with the given ID, if any.
+    async fn find(&self, id: ObjectId) -> Result<Option<Post>, RepositoryError>;
+
+    /// Returns the `scheduled` posts whose `scheduled_time` is at or before `now`,
+    /// ordered by `scheduled_time`.
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Post>, RepositoryError>;
+
+    /// Returns the earliest `scheduled_time` of the `scheduled` posts, or `None` if no post
+    /// is scheduled. Posts that are already due count too, so the result may be in the past.
+    async fn next_scheduled_time(&self) -> Result<Option<DateTime<Utc>>, RepositoryError>;
+
+    /// Returns the posts with the given status whose `scheduled_time` is within
+    /// `start..=end`, ordered by `scheduled_time`.
+    async fn find_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        status: PostStatus,
+    ) -> Result<Vec<Post>, RepositoryError>;
+
+    /// Returns the posts with the given status that went out within `start..=end` (see
+    /// `Post::publication_time`), ordered by `scheduled_time`. Nothing is returned for the
+    /// statuses of posts that have not gone out.
+    async fn find_published_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        status: PostStatus,
+    ) -> Result<Vec<Post>, RepositoryError>;
+
+    /// Replaces a stored post with `post`, provided it still has `expected_status`.
+    ///
+    /// Returns `Ok(false)` if `post` has no ID, no post has that ID, or its status is no
+    /// longer `expected_status`.
+    async fn update(&self, post: &Post, expected_status: PostStatus) -> Result<bool, RepositoryError>;
+
+    /// Applies a status transition to a stored post, provided its status is still
+    /// `transition.from`.
+    ///
+    /// The new status and `updated_at` are set and the transition is appended to
+    /// `status_history`. A transition to `published` or `partially_published` also sets
+    /// `published_at`.
+    async fn transition(&self, id: ObjectId, transition: StatusTransition) -> Result<bool, RepositoryError>;
+
+    /// Claims a due post for publication by moving it from `scheduled` to `publishing` at `at`.
+    ///
+    /// Returns `Ok(false)` if the post is no longer `scheduled`, e.g. because another
+    /// scheduler instance claimed it or it was edited.
+    async fn claim(&self, id: ObjectId, actor: &str, at: DateTime<Utc>) -> Result<bool, RepositoryError> {
+        self.transition(id, StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Publishing, actor, at))
+            .await
+    }
+
+    /// Deletes the post with the given ID. Returns `Ok(false)` if there was no such post.
+    async fn delete(&self, id: ObjectId) -> Result<bool, RepositoryError>;
+
+    /// Subscribes to changes of the stored posts, so that the scheduler can wake up as soon
+    /// as a post is scheduled or rescheduled instead of polling.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend cannot deliver notifications, which is the default.
+    /// Callers are expected to fall back to polling in that case.
+    async fn watch(&self) -> Result<PostChanges, RepositoryError> {
+        Err("Change notifications are not supported by this repository".into())
+    }
+}
+
+/// Opens the post repository described by the database settings.
+///
+/// # Functionality
+///
+/// The scheme of `database.url` selects the storage backend:
+///
+/// - `mongodb://` or `mongodb+srv://`: `MongoPostRepository`, using the
+///   `database.posts_collection` collection of the `database.name` database, with the
+///   configured pool size and server selection timeout. The collection must not hold posts
+///   with the legacy `"pending"` status (see `MongoPostRepository::check_migrated`).
+/// - `sqlite://<path>` or `sqlite::memory:`: `SqlitePostRepository`, which applies pending
+///   schema migrations when opened. Only available when built with the `sqlite` feature.
+///
+/// # Parameters
+///
+/// - `database`: The `[database]` configuration settings.
+///
+/// # Returns
+///
+/// Returns the repository shared behind an `Arc`, ready to be handed to the web routes or
+/// the scheduler loop.
+///
+/// # Errors
+///
+/// Returns an error if the scheme is not supported, the SQLite backend was not compiled in,
+/// the database cannot be opened, or a MongoDB database has not been migrated.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// let settings = Settings::load()?;
+/// let posts = connect(&settings.database).await?;
+/// ```
+pub async fn connect(database: &DatabaseConfig) -> Result<Arc<dyn PostRepository>, RepositoryError> {
+    let database_url = database.url.as_str();
+    if database_url.starts_with("mongodb://") || database_url.starts_with("mongodb+srv://") {
+        let db = crate::repository::mongo::connect_database(database).await?;
+        let posts = crate::repository::mongo::MongoPostRepository::new(db.collection(&database.posts_collection));
+        posts.check_migrated().await?;
+        return Ok(Arc::new(posts));
+    }
+
+    if let Some(path) = database_url.strip_prefix("sqlite:") {
+        let path = path.strip_prefix("//").unwrap_or(path);
+        return open_sqlite(path);
+    }
+
+    Err(format!("Unsupported database URL: {}", database_url).into())
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(path: &str) -> Result<Arc<dyn PostRepository>, RepositoryError> {
+    Ok(Arc::new(crate::repository::sqlite::SqlitePostRepository::open(path)?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(_path: &str) -> Result<Arc<dyn PostRepository>, RepositoryError> {
+    Err("SQLite support is not compiled in; rebuild with `--features sqlite`".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database(url: &str) -> DatabaseConfig {
+        DatabaseConfig {
+            url: url.to_string(),
+            ..DatabaseConfig::default()
+        }
+    }
+
+    /// Tests that an unknown URL scheme is rejected.
+    #[tokio::test]
+    async fn test_connect_rejects_unknown_scheme() {
+        let error = connect(&database("postgres://localhost/posts")).await.err().unwrap();
+        assert!(error.to_string().contains("Unsupported database URL"));
+    }
+
+    /// Tests that both SQLite URL forms open a database when the feature is enabled.
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_connect_sqlite() {
+        let posts = connect(&database("sqlite::memory:")).await.unwrap();
+        assert!(posts.find(mongodb::bson::oid::ObjectId::new()).await.unwrap().is_none());
+
+        let path = std::env::temp_dir().join(format!("posts-{}.sqlite", mongodb::bson::oid::ObjectId::new()));
+        connect(&database(&format!("sqlite://{}", path.display()))).await.unwrap();
+        assert!(path.exists());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,53 @@
+use crate::config::database::DatabaseConfig;
+use crate::models::secret::EncryptedSecret;
+use crate::repository::posts::RepositoryError;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Storage of encrypted secrets.
+///
+/// Repositories only ever see `EncryptedSecret`s: encryption and decryption happen in
+/// `SecretCipher`, with a key that is never stored in the database.
+#[async_trait]
+pub trait SecretRepository: Send + Sync {
+    /// Returns the secret with the given name, if any.
+    async fn get(&self, name: &str) -> Result<Option<EncryptedSecret>, RepositoryError>;
+
+    /// Stores `secret`, replacing any secret with the same name.
+    async fn put(&self, secret: EncryptedSecret) -> Result<(), RepositoryError>;
+}
+
+/// Opens the secret store of the database described by the database settings.
+///
+/// The backend is selected like in `repository::posts::connect`; with MongoDB, secrets are
+/// kept in the `database.secrets_collection` collection.
+///
+/// # Errors
+///
+/// Returns an error if the scheme is not supported, the SQLite backend was not compiled in,
+/// or the database cannot be opened.
+pub async fn connect_secrets(database: &DatabaseConfig) -> Result<Arc<dyn SecretRepository>, RepositoryError> {
+    let database_url = database.url.as_str();
+    if database_url.starts_with("mongodb://") || database_url.starts_with("mongodb+srv://") {
+        let db = crate::repository::mongo::connect_database(database).await?;
+        let secrets = db.collection(&database.secrets_collection);
+        return Ok(Arc::new(crate::repository::mongo::MongoSecretRepository::new(secrets)));
+    }
+
+    if let Some(path) = database_url.strip_prefix("sqlite:") {
+        let path = path.strip_prefix("//").unwrap_or(path);
+        return open_sqlite(path);
+    }
+
+    Err(format!("Unsupported database URL: {}", database_url).into())
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(path: &str) -> Result<Arc<dyn SecretRepository>, RepositoryError> {
+    Ok(Arc::new(crate::repository::sqlite::SqlitePostRepository::open(path)?.secrets()))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(_path: &str) -> Result<Arc<dyn SecretRepository>, RepositoryError> {
+    Err("SQLite support is not compiled in; rebuild with `--features sqlite`".into())
+}
@@ -0,0 +1,516 @@
+use crate::models::pause::Pause;
+use crate::models::post::{Post, PostStatus, StatusTransition};
+use crate::models::secret::EncryptedSecret;
+use crate::repository::pauses::PauseRepository;
+use crate::repository::posts::{notifications, PostChanges, PostRepository, RepositoryError};
+use crate::repository::secrets::SecretRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use log::{error, info};
+use mongodb::bson::{self, oid::ObjectId};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Schema migrations, applied in order.
+///
+/// The index of the last applied migration plus one is stored in SQLite's `user_version`
+/// pragma, so opening a database only runs the migrations it has not seen yet. Existing
+/// entries must never be edited; schema changes are added as new entries.
+///
+/// Posts are stored as BSON documents in the `document` column, so the `Post` model can
+/// evolve without a migration. `status`, `scheduled_time` and `published_time` (the
+/// `Post::publication_time`), in milliseconds since the Unix epoch, UTC, are copied into their
+/// own columns because they are used in queries. `published_time` is only filled when a post
+/// is written, so rows left untouched since it was added fall back to `scheduled_time`.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE posts (
+        id TEXT PRIMARY KEY NOT NULL,
+        status TEXT NOT NULL,
+        scheduled_time INTEGER NOT NULL,
+        document BLOB NOT NULL
+    );
+    CREATE INDEX posts_status_scheduled_time ON posts (status, scheduled_time);",
+    "CREATE TABLE secrets (
+        name TEXT PRIMARY KEY NOT NULL,
+        document BLOB NOT NULL
+    );",
+    "CREATE TABLE pauses (
+        scope TEXT PRIMARY KEY NOT NULL,
+        document BLOB NOT NULL
+    );",
+    "ALTER TABLE posts ADD COLUMN published_time INTEGER;
+    CREATE INDEX posts_status_published_time ON posts (status, published_time);",
+];
+
+/// How often `watch` checks whether another process wrote to the database.
+const DATA_VERSION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+impl From<rusqlite::Error> for RepositoryError {
+    fn from(error: rusqlite::Error) -> Self {
+        RepositoryError::new(error)
+    }
+}
+
+/// `PostRepository` backed by a SQLite database.
+///
+/// Available with the `sqlite` cargo feature. SQLite calls are blocking, so every
+/// operation runs on tokio's blocking thread pool while holding the connection lock.
+/// Holding the lock for the whole operation is also what makes the conditional updates
+/// atomic.
+#[derive(Clone)]
+pub struct SqlitePostRepository {
+    connection: Arc<Mutex<Connection>>,
+    changes: Arc<watch::Sender<()>>,
+}
+
+impl SqlitePostRepository {
+    /// Opens (or creates) the SQLite database at `path` and applies pending migrations.
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: The database file, or `:memory:` for a private in-memory database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or a migration fails.
+    pub fn open(path: &str) -> Result<Self, RepositoryError> {
+        let mut connection = if path == ":memory:" {
+            Connection::open_in_memory()?
+        } else {
+            Connection::open(path)?
+        };
+        migrate(&mut connection)?;
+        Ok(SqlitePostRepository {
+            connection: Arc::new(Mutex::new(connection)),
+            changes: Arc::new(watch::Sender::default()),
+        })
+    }
+
+    /// Returns the secret store of the same database.
+    pub fn secrets(&self) -> SqliteSecretRepository {
+        SqliteSecretRepository {
+            connection: Arc::clone(&self.connection),
+        }
+    }
+
+    /// Returns the pause store of the same database.
+    pub fn pauses(&self) -> SqlitePauseRepository {
+        SqlitePauseRepository {
+            connection: Arc::clone(&self.connection),
+        }
+    }
+
+    async fn run<T, F>(&self, operation: F) -> Result<T, RepositoryError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, RepositoryError> + Send + 'static,
+    {
+        run(&self.connection, operation).await
+    }
+
+    /// Wakes the subscribers of `watch` after a successful write.
+    fn notify(&self, written: bool) -> bool {
+        if written {
+            self.changes.send_replace(());
+        }
+        written
+    }
+}
+
+/// `SecretRepository` backed by the `secrets` table of a SQLite database.
+///
+/// Obtained from `SqlitePostRepository::secrets`, with which it shares the connection.
+#[derive(Clone)]
+pub struct SqliteSecretRepository {
+    connection: Arc<Mutex<Connection>>,
+}
+
+/// `PauseRepository` backed by the `pauses` table of a SQLite database.
+///
+/// Obtained from `SqlitePostRepository::pauses`, with which it shares the connection.
+#[derive(Clone)]
+pub struct SqlitePauseRepository {
+    connection: Arc<Mutex<Connection>>,
+}
+
+/// Runs `operation` with the connection on the blocking thread pool.
+async fn run<T, F>(connection: &Arc<Mutex<Connection>>, operation: F) -> Result<T, RepositoryError>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Connection) -> Result<T, RepositoryError> + Send + 'static,
+{
+    let connection = Arc::clone(connection);
+    tokio::task::spawn_blocking(move || {
+        let mut connection = connection.lock().unwrap();
+        operation(&mut connection)
+    })
+    .await
+    .map_err(RepositoryError::new)?
+}
+
+/// Applies the migrations the database has not seen yet, each in its own transaction.
+fn migrate(connection: &mut Connection) -> Result<(), RepositoryError> {
+    let version: usize = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", index + 1)?;
+        transaction.commit()?;
+        info!("Applied SQLite migration {}", index + 1);
+    }
+    Ok(())
+}
+
+/// Returns SQLite's `data_version`, which changes whenever another connection commits.
+fn data_version(connection: &mut Connection) -> Result<i64, RepositoryError> {
+    Ok(connection.query_row("PRAGMA data_version", [], |row| row.get(0))?)
+}
+
+fn encode(post: &Post) -> Result<Vec<u8>, RepositoryError> {
+    bson::to_vec(post).map_err(RepositoryError::new)
+}
+
+/// Returns the `published_time` column of `post`.
+fn published_time(post: &Post) -> Option<i64> {
+    post.publication_time().map(|time| time.timestamp_millis())
+}
+
+fn decode(document: &[u8]) -> Result<Post, RepositoryError> {
+    bson::from_slice(document).map_err(RepositoryError::new)
+}
+
+/// Runs a query returning `document` columns, skipping rows that do not match the `Post`
+/// model instead of failing the whole query.
+fn query_posts(
+    connection: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<Post>, RepositoryError> {
+    let mut statement = connection.prepare(sql)?;
+    let rows = statement.query_map(params, |row| row.get::<_, Vec<u8>>(0))?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        match decode(&row?) {
+            Ok(post) => results.push(post),
+            Err(e) => error!("Skipping post that does not match the Post model: {}", e),
+        }
+    }
+    Ok(results)
+}
+
+fn find_post(connection: &Connection, id: ObjectId) -> Result<Option<Post>, RepositoryError> {
+    let document: Option<Vec<u8>> = connection
+        .query_row("SELECT document FROM posts WHERE id = ?1", [id.to_hex()], |row| row.get(0))
+        .optional()?;
+    document.as_deref().map(decode).transpose()
+}
+
+fn write_post(connection: &Connection, id: ObjectId, post: &Post) -> Result<(), RepositoryError> {
+    connection.execute(
+        "UPDATE posts SET status = ?2, scheduled_time = ?3, published_time = ?4, document = ?5 WHERE id = ?1",
+        params![
+            id.to_hex(),
+            post.status.as_str(),
+            post.scheduled_time.timestamp_millis(),
+            published_time(post),
+            encode(post)?
+        ],
+    )?;
+    Ok(())
+}
+
+#[async_trait]
+impl PostRepository for SqlitePostRepository {
+    async fn insert(&self, mut post: Post) -> Result<ObjectId, RepositoryError> {
+        let id = *post.id.get_or_insert_with(ObjectId::new);
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT INTO posts (id, status, scheduled_time, published_time, document)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    id.to_hex(),
+                    post.status.as_str(),
+                    post.scheduled_time.timestamp_millis(),
+                    published_time(&post),
+                    encode(&post)?
+                ],
+            )?;
+            Ok(())
+        })
+        .await?;
+        self.notify(true);
+        Ok(id)
+    }
+
+    async fn find(&self, id: ObjectId) -> Result<Option<Post>, RepositoryError> {
+        self.run(move |connection| find_post(connection, id)).await
+    }
+
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Post>, RepositoryError> {
+        self.run(move |connection| {
+            query_posts(
+                connection,
+                "SELECT document FROM posts WHERE status = ?1 AND scheduled_time <= ?2
+                 ORDER BY scheduled_time",
+                params![PostStatus::Scheduled.as_str(), now.timestamp_millis()],
+            )
+        })
+        .await
+    }
+
+    async fn next_scheduled_time(&self) -> Result<Option<DateTime<Utc>>, RepositoryError> {
+        self.run(|connection| {
+            let next: Option<i64> = connection.query_row(
+                "SELECT MIN(scheduled_time) FROM posts WHERE status = ?1",
+                [PostStatus::Scheduled.as_str()],
+                |row| row.get(0),
+            )?;
+            Ok(next.and_then(DateTime::from_timestamp_millis))
+        })
+        .await
+    }
+
+    async fn find_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        status: PostStatus,
+    ) -> Result<Vec<Post>, RepositoryError> {
+        self.run(move |connection| {
+            query_posts(
+                connection,
+                "SELECT document FROM posts
+                 WHERE status = ?1 AND scheduled_time >= ?2 AND scheduled_time <= ?3
+                 ORDER BY scheduled_time",
+                params![status.as_str(), start.timestamp_millis(), end.timestamp_millis()],
+            )
+        })
+        .await
+    }
+
+    async fn find_published_in_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        status: PostStatus,
+    ) -> Result<Vec<Post>, RepositoryError> {
+        let mut posts = self
+            .run(move |connection| {
+                query_posts(
+                    connection,
+                    "SELECT document FROM posts
+                     WHERE status = ?1 AND (published_time >= ?2 AND published_time <= ?3
+                        OR published_time IS NULL AND scheduled_time >= ?2 AND scheduled_time <= ?3)
+                     ORDER BY scheduled_time",
+                    params![status.as_str(), start.timestamp_millis(), end.timestamp_millis()],
+                )
+            })
+            .await?;
+        posts.retain(|post| post.publication_time().is_some_and(|time| time >= start && time <= end));
+        Ok(posts)
+    }
+
+    async fn update(&self, post: &Post, expected_status: PostStatus) -> Result<bool, RepositoryError> {
+        let Some(id) = post.id else {
+            return Ok(false);
+        };
+        let post = post.clone();
+        self.run(move |connection| {
+            let updated = connection.execute(
+                "UPDATE posts SET status = ?3, scheduled_time = ?4, published_time = ?5, document = ?6
+                 WHERE id = ?1 AND status = ?2",
+                params![
+                    id.to_hex(),
+                    expected_status.as_str(),
+                    post.status.as_str(),
+                    post.scheduled_time.timestamp_millis(),
+                    published_time(&post),
+                    encode(&post)?
+                ],
+            )?;
+            Ok(updated > 0)
+        })
+        .await
+        .map(|updated| self.notify(updated))
+    }
+
+    async fn transition(&self, id: ObjectId, transition: StatusTransition) -> Result<bool, RepositoryError> {
+        self.run(move |connection| {
+            let Some(mut post) = find_post(connection, id)? else {
+                return Ok(false);
+            };
+            if transition.from.is_some_and(|from| from != post.status) {
+                return Ok(false);
+            }
+
+            post.record_transition(transition);
+            write_post(connection, id, &post)?;
+            Ok(true)
+        })
+        .await
+        .map(|transitioned| self.notify(transitioned))
+    }
+
+    async fn delete(&self, id: ObjectId) -> Result<bool, RepositoryError> {
+        self.run(move |connection| Ok(connection.execute("DELETE FROM posts WHERE id = ?1", [id.to_hex()])? > 0))
+            .await
+            .map(|deleted| self.notify(deleted))
+    }
+
+    /// Notifies writes made through this repository immediately, and writes made by other
+    /// processes (e.g. the web server, when this is the scheduler) within
+    /// `DATA_VERSION_POLL_INTERVAL`, by checking SQLite's `data_version`.
+    async fn watch(&self) -> Result<PostChanges, RepositoryError> {
+        let local = notifications(self.changes.subscribe());
+
+        let connection = Arc::clone(&self.connection);
+        let version = run(&connection, data_version).await?;
+        let external = stream::unfold((connection, version), |(connection, version)| async move {
+            loop {
+                tokio::time::sleep(DATA_VERSION_POLL_INTERVAL).await;
+                match run(&connection, data_version).await {
+                    Ok(latest) if latest != version => return Some(((), (connection, latest))),
+                    Ok(_) => {}
+                    Err(e) => {
+                        error!("Stopped watching the SQLite database: {}", e);
+                        return None;
+                    }
+                }
+            }
+        });
+
+        Ok(stream::select(local, external).boxed())
+    }
+}
+
+#[async_trait]
+impl SecretRepository for SqliteSecretRepository {
+    async fn get(&self, name: &str) -> Result<Option<EncryptedSecret>, RepositoryError> {
+        let name = name.to_string();
+        run(&self.connection, move |connection| {
+            let document: Option<Vec<u8>> = connection
+                .query_row("SELECT document FROM secrets WHERE name = ?1", [name], |row| row.get(0))
+                .optional()?;
+            document
+                .map(|document| bson::from_slice(&document).map_err(RepositoryError::new))
+                .transpose()
+        })
+        .await
+    }
+
+    async fn put(&self, secret: EncryptedSecret) -> Result<(), RepositoryError> {
+        run(&self.connection, move |connection| {
+            let document = bson::to_vec(&secret).map_err(RepositoryError::new)?;
+            connection.execute(
+                "INSERT INTO secrets (name, document) VALUES (?1, ?2)
+                 ON CONFLICT (name) DO UPDATE SET document = excluded.document",
+                params![secret.name, document],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl PauseRepository for SqlitePauseRepository {
+    async fn list(&self) -> Result<Vec<Pause>, RepositoryError> {
+        run(&self.connection, |connection| {
+            let mut statement = connection.prepare("SELECT document FROM pauses ORDER BY scope")?;
+            let documents = statement
+                .query_map([], |row| row.get::<_, Vec<u8>>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            documents
+                .iter()
+                .map(|document| bson::from_slice(document).map_err(RepositoryError::new))
+                .collect()
+        })
+        .await
+    }
+
+    async fn put(&self, pause: Pause) -> Result<(), RepositoryError> {
+        run(&self.connection, move |connection| {
+            let document = bson::to_vec(&pause).map_err(RepositoryError::new)?;
+            connection.execute(
+                "INSERT INTO pauses (scope, document) VALUES (?1, ?2)
+                 ON CONFLICT (scope) DO UPDATE SET document = excluded.document",
+                params![pause.scope, document],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn remove(&self, scope: &str) -> Result<bool, RepositoryError> {
+        let scope = scope.to_string();
+        run(&self.connection, move |connection| {
+            Ok(connection.execute("DELETE FROM pauses WHERE scope = ?1", [scope])? > 0)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::conformance::conformance_tests;
+
+    conformance_tests!(SqlitePostRepository::open(":memory:").unwrap());
+
+    /// Tests the secret store against the shared behavioral test.
+    #[tokio::test]
+    async fn test_secrets_round_trip() {
+        let repository = SqlitePostRepository::open(":memory:").unwrap().secrets();
+        crate::repository::conformance::secrets_round_trip(&repository).await;
+    }
+
+    /// Tests the pause store against the shared behavioral test.
+    #[tokio::test]
+    async fn test_pauses_round_trip() {
+        let repository = SqlitePostRepository::open(":memory:").unwrap().pauses();
+        crate::repository::conformance::pauses_round_trip(&repository).await;
+    }
+
+    /// Tests that a write made through another connection, as the web server does while the
+    /// scheduler waits, is notified through `data_version`.
+    #[tokio::test]
+    async fn test_watch_notifies_writes_of_other_connections() {
+        let path = std::env::temp_dir().join(format!("posts-{}.sqlite", ObjectId::new()));
+        let path = path.to_str().unwrap();
+        let scheduler = SqlitePostRepository::open(path).unwrap();
+        let web_server = SqlitePostRepository::open(path).unwrap();
+
+        let mut changes = scheduler.watch().await.unwrap();
+        web_server
+            .insert(Post::new("New".to_string(), "Content".to_string(), bson::DateTime::now(), Utc::now()))
+            .await
+            .unwrap();
+        let woken = tokio::time::timeout(Duration::from_secs(5), changes.next()).await;
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(woken, Ok(Some(())));
+    }
+
+    /// Tests that reopening a migrated database does not run the migrations again.
+    #[test]
+    fn test_migrations_are_applied_once() {
+        let path = std::env::temp_dir().join(format!("posts-{}.sqlite", ObjectId::new()));
+        let path = path.to_str().unwrap();
+
+        SqlitePostRepository::open(path).unwrap();
+        let repository = SqlitePostRepository::open(path).unwrap();
+        let version: usize = repository
+            .connection
+            .lock()
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(version, MIGRATIONS.len());
+    }
+}
@@ -1,17 +1,343 @@
-use crate::config::settings::load_config;
-use api::{connections::get_profile_id, post::publish_article};
-use chrono::{TimeZone, Utc};
-use config::settings::get_local_time;
-use futures_util::TryStreamExt;
-use log::{error, info};
-use mongodb::{
-    bson::{doc, DateTime as BsonDateTime, Document},
-    Client,
-};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures_util::future;
+use futures_util::stream::{self, StreamExt};
+use linkedin_automation::clock::{Clock, SystemClock};
+use linkedin_automation::config::reload::{watch_settings, SharedSettings};
+use linkedin_automation::config::secret::{resolve_access_token, Secret, SecretCipher};
+use linkedin_automation::config::settings::{print_config_requested, Settings};
+use linkedin_automation::models::pause::pause_for_post;
+use linkedin_automation::models::post::{overall_status, Post, PostStatus, StatusTransition, TargetStatus};
+use linkedin_automation::publishing::publisher::{publishers_for, Publication, Publishers};
+use linkedin_automation::repository::pauses::{connect_pauses, PauseRepository};
+use linkedin_automation::repository::posts::{connect, PostChanges, PostRepository, RepositoryError};
+use linkedin_automation::repository::secrets::{connect_secrets, SecretRepository};
+use linkedin_automation::scheduling::blackout::{blackout_at, Blackout, BlackoutPolicy};
+use linkedin_automation::scheduling::catch_up::{plan_catch_up, CatchUpAction, CatchUpOutcome};
+use linkedin_automation::scheduling::pacing::{PostPacing, PUBLISHING_STATUSES};
+use linkedin_automation::shutdown::Shutdown;
+use log::{debug, error, info, warn};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::DateTime as BsonDateTime;
+use std::collections::HashSet;
+use std::env;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
 use tokio::time::{self, Duration};
 
-mod api;
-mod config;
+/// Actor recorded in the status history for transitions made by the scheduler.
+const SCHEDULER_ACTOR: &str = "scheduler";
+
+/// Command-line flag running the scheduler in dry-run mode, like `scheduler.dry_run = true`.
+const DRY_RUN_FLAG: &str = "--dry-run";
+
+/// Posts claimed by this scheduler that have not reached a final status yet.
+///
+/// If the scheduler has to stop while publishing them, they are released back to "scheduled"
+/// by `release_claimed_posts` instead of being left in "publishing".
+#[derive(Default)]
+struct ClaimedPosts(Mutex<HashSet<ObjectId>>);
+
+/// Returns whether a post scheduled at `scheduled_time` must be published at `now`.
+///
+/// Both values are UTC instants: the configured timezone offset is applied by the web
+/// server when the post is scheduled and is never used here, so the scheduler fires at
+/// the same instant regardless of the `timezone_offset` setting of the container it runs in.
+fn is_due(scheduled_time: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    scheduled_time <= now
+}
+
+/// Returns how long the scheduler may sleep at `now` before the next check.
+///
+/// The scheduler sleeps exactly until `next_scheduled_time`, the earliest upcoming post,
+/// but never longer than `max_wait` so that changes the database cannot notify are still
+/// noticed. It does not sleep at all if a post is already due.
+fn time_until_next_check(
+    next_scheduled_time: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    max_wait: Duration,
+) -> Duration {
+    match next_scheduled_time {
+        None => max_wait,
+        Some(next) if is_due(next, now) => Duration::ZERO,
+        Some(next) => (next - now).to_std().map_or(max_wait, |wait| wait.min(max_wait)),
+    }
+}
+
+/// Returns how long the scheduler loop may sleep at `now`, once the due posts are handled.
+///
+/// The loop sleeps until the next scheduled post (see `time_until_next_check`), except for
+/// posts that stay due: paused and dry-run posts are checked again after the poll interval,
+/// when the pause may have been lifted or new posts may be due, and posts held by a blackout
+/// are waited for until it ends rather than checked again.
+async fn wait_before_next_check<R: PostRepository + ?Sized>(
+    posts: &R,
+    pauses: &dyn PauseRepository,
+    now: DateTime<Utc>,
+    settings: &Settings,
+) -> Result<Duration, RepositoryError> {
+    let mut next_scheduled_time = posts.next_scheduled_time().await?;
+    if next_scheduled_time.is_some_and(|next| is_due(next, now))
+        && (settings.scheduler.dry_run || !pauses.list().await?.is_empty())
+    {
+        next_scheduled_time = None;
+    }
+    if settings.scheduler.blackout_policy == BlackoutPolicy::Hold {
+        if let Some(blackout) = blackout_at(&settings.scheduler.blackouts, now, settings.timezone()) {
+            next_scheduled_time = next_scheduled_time.map(|next| next.max(blackout.until));
+        }
+    }
+    let max_wait = Duration::from_secs(settings.scheduler.poll_interval_secs);
+    let wait = time_until_next_check(next_scheduled_time, now, max_wait);
+    debug!("Next scheduled post: {:?}, sleeping for {:?}", next_scheduled_time, wait);
+    Ok(wait)
+}
+
+/// Returns whether the scheduler was started with `--dry-run`.
+fn dry_run_requested() -> bool {
+    env::args().skip(1).any(|arg| arg == DRY_RUN_FLAG)
+}
+
+/// Returns the settings the scheduler runs with: `settings`, in dry-run mode if `--dry-run`
+/// was given, whatever the configuration says.
+fn with_dry_run_flag(settings: Arc<Settings>, dry_run_flag: bool) -> Arc<Settings> {
+    if !dry_run_flag || settings.scheduler.dry_run {
+        return settings;
+    }
+    let mut settings = (*settings).clone();
+    settings.scheduler.dry_run = true;
+    Arc::new(settings)
+}
+
+/// Subscribes to post changes, or returns a stream that never yields if the repository
+/// cannot notify them, in which case the scheduler relies on `poll_interval_secs` alone.
+async fn watch_posts<R: PostRepository + ?Sized>(posts: &R) -> PostChanges {
+    match posts.watch().await {
+        Ok(changes) => changes,
+        Err(e) => {
+            warn!("Not watching post changes, checking every poll interval instead: {}", e);
+            stream::pending().boxed()
+        }
+    }
+}
+
+/// Where the scheduler gets its publishers from, when it starts and after each reload.
+#[async_trait]
+trait PublisherSource: Send + Sync {
+    /// Returns the publishers to use with `settings`, keyed by channel name.
+    async fn publishers(&self, settings: &Settings) -> Publishers;
+}
+
+/// Fixed publishers, whatever the settings, e.g. in tests.
+#[async_trait]
+impl PublisherSource for Publishers {
+    async fn publishers(&self, _settings: &Settings) -> Publishers {
+        self.clone()
+    }
+}
+
+/// The publishers of the configured channels (see `publishers_for`), with the LinkedIn access
+/// token resolved again from the settings and the secret store on each reload.
+struct ConfiguredPublishers {
+    secrets: Arc<dyn SecretRepository>,
+    cipher: Option<SecretCipher>,
+    /// The last token resolved, kept if it cannot be resolved anymore.
+    access_token: Mutex<Secret>,
+    clock: Arc<dyn Clock>,
+}
+
+#[async_trait]
+impl PublisherSource for ConfiguredPublishers {
+    async fn publishers(&self, settings: &Settings) -> Publishers {
+        match resolve_access_token(settings, self.secrets.as_ref(), self.cipher.as_ref()).await {
+            Ok(token) => *self.access_token.lock().unwrap() = token,
+            Err(e) => error!("Keeping the current access token: {}", e),
+        }
+        publishers_for(settings, &self.access_token.lock().unwrap(), self.clock.clone())
+    }
+}
+
+/// The scheduler: publishes the posts of a repository when they are due.
+///
+/// # Functionality
+///
+/// `run_once` handles the posts due at the current time of the scheduler's `Clock`, and
+/// `run_until` calls it in a loop until a shutdown is requested:
+///
+/// - **Publishing**: Each iteration calls `publish_due_posts`, which claims, publishes and records
+///   the outcome of every due post, up to `scheduler.max_concurrent_publishes` at a time and in
+///   order within each account. Posts found late, e.g. after the scheduler was down, are
+///   published, rescheduled or marked missed according to their catch-up policy (see its
+///   documentation for details). Posts that would break the pacing rule of their account are
+///   deferred (see `apply_pacing`). During a blackout window, nothing is published: due posts
+///   are deferred to its end or held, per `scheduler.blackout_policy` (see `apply_blackout`).
+///   All comparisons are made between UTC instants; timezones only matter for the days and
+///   weeks of the pacing caps and the local times of the blackout windows.
+///
+/// - **Pauses**: While publishing is paused (`POST /admin/pause` on the web server), no post of
+///   the paused accounts is claimed; the pauses are read before each post, so a pause applies to
+///   the next post even in the middle of an iteration. Paused posts are left untouched.
+///
+/// - **Waiting**: The loop then sleeps until the earliest upcoming `scheduled_time`, so posts are
+///   published within about a second of their scheduled time (see `wait_before_next_check`).
+///   It is woken up early when posts are inserted, edited or rescheduled: through a MongoDB
+///   change stream (which requires a replica set) or the notifications of the other backends
+///   (see `PostRepository::watch`). It never sleeps longer than `scheduler.poll_interval_secs`
+///   (20 seconds by default), which is how changes are picked up when notifications are
+///   unavailable, and how a resume is noticed.
+///
+/// - **Graceful Shutdown**: Once a shutdown is requested, no new post is claimed and the posts
+///   being published get `scheduler.shutdown_timeout_secs` (30 by default) to complete, so that
+///   a post accepted by LinkedIn is also marked as published. Posts still being published after
+///   that are released back to "scheduled" (see `release_claimed_posts`). Posts left
+///   "publishing" by a scheduler that could not release them, e.g. because it crashed, are
+///   released when `run_until` starts (see `release_abandoned_posts`).
+///
+/// - **Hot Reload**: A change of the watched settings wakes the loop up; the new poll interval,
+///   pool size and channels are used from the next iteration, with publishers rebuilt by the
+///   `PublisherSource` (for `ConfiguredPublishers`, the access token is resolved again).
+///
+/// - **Dry Run**: With the dry-run flag or `scheduler.dry_run = true`, nothing is published and
+///   no status changes: each due post is reported once instead, with the exact request that
+///   would have been sent or the reason it would not have been published (see
+///   `dry_run_due_posts`). When `run_until` starts, the posts that would be published in the
+///   next `scheduler.dry_run_report_hours` hours are logged (see `report_upcoming_posts`).
+///   Reported posts stay due, so a dry run checks for new due posts every poll interval or
+///   post change.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// let scheduler = Scheduler::new(posts, pauses, Box::new(publishers), Arc::new(SystemClock), settings, false).await;
+/// scheduler.run_until(Shutdown::listen()).await?;
+/// ```
+struct Scheduler {
+    posts: Arc<dyn PostRepository>,
+    pauses: Arc<dyn PauseRepository>,
+    source: Box<dyn PublisherSource>,
+    clock: Arc<dyn Clock>,
+    settings: SharedSettings,
+    /// Whether `--dry-run` was given, overriding `scheduler.dry_run`.
+    dry_run_flag: bool,
+    /// The settings last read from `settings`, to detect reloads.
+    loaded: Arc<Settings>,
+    /// `loaded`, with the dry-run flag applied.
+    current: Arc<Settings>,
+    publishers: Publishers,
+    claimed: ClaimedPosts,
+}
+
+impl Scheduler {
+    /// Returns a scheduler publishing the posts of `posts` with the publishers of `source`,
+    /// at the times given by `clock`, with the current value of `settings` until it changes.
+    async fn new(
+        posts: Arc<dyn PostRepository>,
+        pauses: Arc<dyn PauseRepository>,
+        source: Box<dyn PublisherSource>,
+        clock: Arc<dyn Clock>,
+        mut settings: SharedSettings,
+        dry_run_flag: bool,
+    ) -> Self {
+        let loaded = settings.borrow_and_update().clone();
+        let current = with_dry_run_flag(Arc::clone(&loaded), dry_run_flag);
+        let publishers = source.publishers(&current).await;
+        Scheduler {
+            posts,
+            pauses,
+            source,
+            clock,
+            settings,
+            dry_run_flag,
+            loaded,
+            current,
+            publishers,
+            claimed: ClaimedPosts::default(),
+        }
+    }
+
+    /// Handles the posts due now, and returns how long to wait before the next run.
+    ///
+    /// The due posts are published, or reported in dry-run mode (see `publish_due_posts`). If
+    /// `shutdown` is requested meanwhile, the publications get `scheduler.shutdown_timeout_secs`
+    /// to complete; the posts still being published after that are released.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repositories cannot be read or updated. Publication errors are
+    /// recorded on the posts instead.
+    async fn run_once(&self, shutdown: &Shutdown) -> Result<Duration, RepositoryError> {
+        let publishing = publish_due_posts(
+            self.posts.as_ref(),
+            self.pauses.as_ref(),
+            &self.publishers,
+            self.clock.as_ref(),
+            &self.current,
+            shutdown,
+            &self.claimed,
+        );
+        let deadline = Duration::from_secs(self.current.scheduler.shutdown_timeout_secs);
+        match shutdown.finish_within(publishing, deadline).await {
+            Ok(result) => result?,
+            Err(_) => release_claimed_posts(self.posts.as_ref(), &self.claimed, self.clock.now()).await,
+        }
+        wait_before_next_check(self.posts.as_ref(), self.pauses.as_ref(), self.clock.now(), &self.current).await
+    }
+
+    /// Runs the scheduler until `shutdown` is requested.
+    ///
+    /// Releases the posts abandoned by a previous scheduler, then calls `run_once`, waits for
+    /// the time it returns, a post change, a settings reload or the shutdown, whichever comes
+    /// first, and starts over.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of the release, of `run_once`, or of the dry-run report.
+    async fn run_until(mut self, mut shutdown: Shutdown) -> Result<(), RepositoryError> {
+        let mut changes = watch_posts(self.posts.as_ref()).await;
+        if self.current.scheduler.dry_run {
+            warn!("Dry run: nothing will be published");
+            report_upcoming_posts(self.posts.as_ref(), self.pauses.as_ref(), self.clock.now(), &self.current).await?;
+        } else {
+            let timeout = ChronoDuration::seconds(self.current.scheduler.shutdown_timeout_secs as i64);
+            release_abandoned_posts(self.posts.as_ref(), self.clock.now(), timeout).await?;
+        }
+
+        loop {
+            let wait = self.run_once(&shutdown).await?;
+            if shutdown.is_requested() {
+                break;
+            }
+
+            // A post change or a reload wakes the loop up, so a new post, schedule, poll
+            // interval or token is taken into account right away.
+            tokio::select! {
+                _ = time::sleep(wait) => {}
+                change = changes.next() => {
+                    if change.is_none() {
+                        warn!("Post change notifications stopped, checking every poll interval instead");
+                        changes = stream::pending().boxed();
+                    }
+                }
+                // Fixed settings never change: their `changed` fails and the branch is skipped.
+                Ok(()) = self.settings.changed() => {}
+                _ = shutdown.requested() => break,
+            }
+            self.reload().await;
+        }
+        Ok(())
+    }
+
+    /// Switches to the latest settings if they changed, rebuilding the publishers.
+    async fn reload(&mut self) {
+        let latest = self.settings.borrow_and_update().clone();
+        if Arc::ptr_eq(&latest, &self.loaded) {
+            return;
+        }
+        self.current = with_dry_run_flag(Arc::clone(&latest), self.dry_run_flag);
+        self.loaded = latest;
+        self.publishers = self.source.publishers(&self.current).await;
+    }
+}
 
 /// The main entry point for the LinkedIn post scheduler.
 /// This function continuously checks the MongoDB collection for posts scheduled
@@ -26,42 +352,33 @@ mod config;
 ///
 /// This function will return an error in the following situations:
 ///
-/// - If the configuration file `config.toml` cannot be loaded.
-/// - If the connection to the MongoDB database fails.
+/// - If the settings are invalid (every invalid key is reported).
+/// - If no access token can be resolved (see `resolve_access_token`).
+/// - If the connection to the database fails.
 /// - If there's an error retrieving posts from the MongoDB collection.
-/// - If there's an error publishing an article to LinkedIn.
-/// - If there's an error updating the status of a post in MongoDB after publishing.
+/// - If there's an error updating the status of a post in MongoDB.
+///
+/// Errors publishing an article to LinkedIn do not stop the scheduler: the post is marked as "failed".
 ///
 /// # Functionality
 ///
 /// 1. **Logging Initialization**: The function initializes the logging environment
 ///    using `env_logger` to provide detailed runtime information for monitoring and debugging.
 ///
-/// 2. **Configuration Loading**: It loads the configuration settings from `config.toml`
-///    using the `load_config` function, including the `access_token` for LinkedIn API access.
-///
-/// 3. **MongoDB Connection**: It establishes a connection to a MongoDB database, specifically
-///    targeting the `lkdin-posts` database and the `posts` collection, where scheduled posts are stored.
-///
-/// 4. **Infinite Loop**: The function enters an infinite loop to continuously check for posts that
-///    need to be published:
-///
-///    - The current time is obtained in UTC and then converted to the local time in Brazil (UTC-3).
+/// 2. **Configuration Loading**: It loads the layered `Settings` (defaults, `config.toml` and
+///    `LINKEDIN_AUTOMATION__*` environment variables), including the LinkedIn API base URL.
+///    With `--print-config`, the settings are printed with secrets redacted and the scheduler exits.
+///    The LinkedIn access token is then resolved from `access_token_file`, the settings, or the
+///    encrypted secret store, in that order.
 ///
-///    - It constructs a MongoDB filter to find posts with a `scheduled_time` less than or equal to the current time
-///      and with a `status` of "pending".
+/// 3. **Database Connection**: It opens the storage backend described by the `[database]` settings
+///    (see `repository::posts::connect`), by default the `posts` collection of the `lkdin-posts`
+///    database at `mongodb://mongo:27017`.
 ///
-///    - The function retrieves these posts using a cursor and iterates through them.
-///
-///    - For each post:
-///      - The scheduled time is retrieved and converted to the local time in Brazil.
-///      - If the scheduled time is still in the future, the function waits until the post's scheduled time.
-///
-///      - The post's `title` and `content` are retrieved and used to publish the article to LinkedIn using the `publish_article` function.
-///
-///      - If the post is published successfully, the function updates the `status` of the post to "published" in MongoDB.
-///
-///    - The loop then waits for 20 seconds before checking for new posts to publish.
+/// 4. **Scheduling**: It runs a `Scheduler` on the system clock, with the publishers of the
+///    configured channels, until `SIGTERM` or `SIGINT`. The settings are watched (file changes
+///    and `SIGHUP`, see `watch_settings`), and `--dry-run` runs it in dry-run mode. See `Scheduler`
+///    for how posts are published, paced, paused and reported.
 ///
 /// # Example Usage
 ///
@@ -72,77 +389,667 @@ mod config;
 /// ```
 ///
 /// The scheduler will start running, continuously checking the MongoDB database for posts to publish
-/// and logging the results to the console.
+/// and logging the results to the console. To see what it would publish without publishing anything:
+///
+/// ```bash
+/// cargo run --bin scheduler -- --dry-run
+/// ```
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
-    let config = load_config("config.toml")?;
-    let mongo_client = Client::with_uri_str("mongodb://mongodb:27017").await?;
-    let db = mongo_client.database("lkdin-posts");
-    let posts: mongodb::Collection<Document> = db.collection("posts");
-    let access_token = config.access_token.clone();
+    let settings = Settings::load().inspect_err(|e| error!("{}", e))?;
+    if print_config_requested() {
+        print!("{}", settings.redacted().to_toml());
+        return Ok(());
+    }
+
+    let cipher = SecretCipher::from_env()?;
+    let secrets = connect_secrets(&settings.database).await?;
+    let access_token = resolve_access_token(&settings, secrets.as_ref(), cipher.as_ref()).await?;
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let source = ConfiguredPublishers {
+        secrets,
+        cipher,
+        access_token: Mutex::new(access_token),
+        clock: clock.clone(),
+    };
 
-    loop {
-        let now = Utc::now();
+    let posts = connect(&settings.database).await?;
+    let pauses = connect_pauses(&settings.database).await?;
+    let settings = watch_settings(settings);
+    let scheduler = Scheduler::new(posts, pauses, Box::new(source), clock, settings, dry_run_requested()).await;
+    scheduler.run_until(Shutdown::listen()).await?;
+
+    info!("Scheduler stopped");
+    Ok(())
+}
+
+/// Publishes every post that is due at `now`.
+///
+/// # Functionality
+///
+/// This is one iteration of the scheduler loop. The posts returned by
+/// `PostRepository::find_due` are split into one queue per account (see `queues_by_account`).
+/// The queues are worked on concurrently, while the posts of a queue are published one after
+/// the other, in chronological order, so that one account's posts stay ordered and a slow
+/// upload only delays the posts of its own account.
+///
+/// At most `max_concurrent_publishes` posts are published at the same time. A post is only
+/// claimed once a worker is free, so while the pool is saturated the remaining due posts stay
+/// "scheduled" and can be picked up by another scheduler instance. Each post is published by
+/// `publish_post`.
+///
+/// Posts more than `scheduler.late_after_secs` late are first handled by their catch-up policy
+/// (see `plan_catch_up` and `apply_catch_up`): only those it decides to publish are published.
+/// A post that would then break the pacing rule of its account is deferred instead (see
+/// `apply_pacing`). If `now` falls in a blackout window, no post is published at all (see
+/// `apply_blackout`); the paused posts are not deferred either.
+///
+/// Before each post, the pauses are read: if publishing is paused globally or for one of the
+/// accounts the post is published on behalf of, the rest of the queue is left untouched.
+///
+/// Once a shutdown is requested, no further post is claimed; the posts being published are
+/// left to finish.
+///
+/// In dry-run mode (`scheduler.dry_run`), the due posts are reported instead, see
+/// `dry_run_due_posts`.
+///
+/// # Parameters
+///
+/// - `posts`: The repository storing the posts.
+/// - `pauses`: The repository storing the publishing pauses.
+/// - `publishers`: The publishers of the channels posts are published to.
+/// - `clock`: The source of the current time: the posts due at its current time are published,
+///   and the times of their status changes and publications are read from it.
+/// - `settings`: The settings: the size of the worker pool (`scheduler.max_concurrent_publishes`),
+///   the catch-up policies, the pacing rules, the blackout windows and the timezone of their
+///   local times.
+/// - `shutdown`: Stops the claiming of new posts when requested.
+/// - `claimed`: Records the posts claimed until they reach a final status.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be queried or updated. Publication errors are
+/// not returned: they are logged and recorded on the post. A repository error stops the
+/// queue it happened in; the other queues are still worked on before it is returned. The pauses
+/// failing to be read stops the queue too, rather than risk publishing during a pause.
+async fn publish_due_posts<R: PostRepository + ?Sized>(
+    posts: &R,
+    pauses: &dyn PauseRepository,
+    publishers: &Publishers,
+    clock: &dyn Clock,
+    settings: &Settings,
+    shutdown: &Shutdown,
+    claimed: &ClaimedPosts,
+) -> Result<(), RepositoryError> {
+    let now = clock.now();
+    info!("Checking posts at {}", now);
 
-        let local_now = get_local_time(now)?;
-        info!("Checking posts at local time: {}", local_now);
+    let scheduler = &settings.scheduler;
+    if scheduler.dry_run {
+        return dry_run_due_posts(posts, pauses, publishers, now, settings).await;
+    }
+    let due = posts.find_due(now).await?;
+    if let Some(blackout) = blackout_at(&scheduler.blackouts, now, settings.timezone()) {
+        // Paused posts are left untouched, even by a deferral.
+        let paused = pauses.list().await?;
+        let due = due.into_iter().filter(|post| pause_for_post(&paused, post).is_none()).collect();
+        return apply_blackout(posts, due, &blackout, scheduler.blackout_policy, now).await;
+    }
 
-        let now_millis = local_now.timestamp_millis();
+    let workers = &Semaphore::new(scheduler.max_concurrent_publishes);
+    let queues = queues_by_account(due);
+    let results = future::join_all(queues.into_iter().map(|queue| async move {
+        let mut shutdown = shutdown.clone();
+        let outcomes = plan_catch_up(&queue, now, scheduler);
+        for (post, outcome) in queue.into_iter().zip(outcomes) {
+            if workers.available_permits() == 0 {
+                info!("All {} publishing workers are busy, waiting", scheduler.max_concurrent_publishes);
+            }
+            let _worker = tokio::select! {
+                biased;
+                _ = shutdown.requested() => break,
+                worker = workers.acquire() => worker.expect("the worker semaphore is never closed"),
+            };
+            if let Some(pause) = pause_for_post(&pauses.list().await?, &post) {
+                info!(
+                    "Publishing is paused ({}) by {} since {}: {}",
+                    pause.scope, pause.actor, pause.paused_at, pause.reason
+                );
+                break;
+            }
 
-        let filter = doc! { "scheduled_time": { "$lte": now_millis }, "status": "pending" };
-        let mut cursor = posts.find(filter).await?;
+            let post = match outcome {
+                Some(outcome) => match apply_catch_up(posts, post, outcome, now).await? {
+                    Some(post) => post,
+                    None => continue,
+                },
+                None => post,
+            };
+            let Some(post) = apply_pacing(posts, post, settings, now).await? else {
+                continue;
+            };
+            publish_post(posts, post, publishers, clock, claimed).await?;
+        }
+        Ok(())
+    }))
+    .await;
 
-        while let Ok(Some(post)) = cursor.try_next().await {
-            let scheduled_time_millis = post.get_i64("scheduled_time").unwrap_or(0);
-            let scheduled_time =
-                get_local_time(Utc.timestamp_millis_opt(scheduled_time_millis).unwrap())?;
+    results.into_iter().collect()
+}
 
-            info!("Scheduled time from MongoDB: {}", scheduled_time);
+/// Reports what publishing the posts due at `now` would do, without publishing any.
+///
+/// # Functionality
+///
+/// This is one iteration of the scheduler loop in dry-run mode. Each due post not reported yet
+/// (see `Post::is_dry_run`) goes through the same decisions as in `publish_due_posts`, in the
+/// same order: blackout, pause, catch-up policy and pacing rule. Either the reason it would not
+/// be published is logged, or, for each of its channels, the request that would publish it
+/// there (see `Publisher::dry_run`; for LinkedIn, the URL and the exact body of the request).
+/// For LinkedIn posts without an `author`, the profile owning the access token is looked up,
+/// which is the only request sent.
+///
+/// The post's status and `scheduled_time` are left untouched: only `dry_run_at` is set to
+/// `now`, so that the post is reported once. Within an account, the posts that would have been
+/// published count for the pacing rule of the next ones.
+///
+/// As in `publish_due_posts`, a paused post is reported as held even during a blackout.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be queried or updated.
+async fn dry_run_due_posts<R: PostRepository + ?Sized>(
+    posts: &R,
+    pauses: &dyn PauseRepository,
+    publishers: &Publishers,
+    now: DateTime<Utc>,
+    settings: &Settings,
+) -> Result<(), RepositoryError> {
+    let scheduler = &settings.scheduler;
+    let due: Vec<Post> = posts.find_due(now).await?.into_iter().filter(|post| !post.is_dry_run()).collect();
+    if due.is_empty() {
+        return Ok(());
+    }
+    let blackout = blackout_at(&scheduler.blackouts, now, settings.timezone());
+    let paused = pauses.list().await?;
 
-            if scheduled_time > local_now {
-                let delay_duration = scheduled_time.signed_duration_since(local_now);
-                info!("Delaying until scheduled time: {}", scheduled_time);
-                time::sleep(Duration::from_secs(delay_duration.num_seconds() as u64)).await;
+    for queue in queues_by_account(due) {
+        let outcomes = plan_catch_up(&queue, now, scheduler);
+        let mut published: Vec<Option<String>> = Vec::new();
+        for (mut post, outcome) in queue.into_iter().zip(outcomes) {
+            let Some(id) = post.id else {
+                continue;
+            };
+            let mut pacing = PostPacing::load(posts, &post, scheduler, PUBLISHING_STATUSES, now).await?;
+            for account in &published {
+                pacing.count(account.as_deref(), now);
             }
 
-            let title = post.get_str("title").unwrap_or("Untitled").to_string();
-            let content = post
-                .get_str("content")
-                .unwrap_or("No content provided")
-                .to_string();
-
-            if let Err(e) = publish_article(
-                &access_token,
-                &get_profile_id(&access_token, None).await?,
-                &title,
-                &content,
-                None,
-            )
-            .await
-            {
-                error!("Error publishing article: {}", e);
+            let skipped = if let Some(pause) = pause_for_post(&paused, &post) {
+                Some(format!("held, publishing is paused ({}): {}", pause.scope, pause.reason))
+            } else if let Some(blackout) = &blackout {
+                let verb = match scheduler.blackout_policy {
+                    BlackoutPolicy::Defer => "deferred",
+                    BlackoutPolicy::Hold => "held",
+                };
+                Some(format!("{} during {} until {}", verb, blackout.window, blackout.until))
+            } else if let Some(outcome) = outcome.filter(|outcome| outcome.action != CatchUpAction::Published) {
+                Some(outcome.describe())
+            } else if let Err(violation) = pacing.check(now, settings.timezone()) {
+                let deferred_to = pacing.next_allowed(now, settings.timezone());
+                Some(format!("{}; deferred to {} by the pacing rule", violation, deferred_to))
             } else {
-                let update = doc! { "$set": { "status": "published" } };
-                posts
-                    .update_one(doc! {"_id": post.get_object_id("_id").unwrap()}, update)
-                    .await?;
-                info!("Post published successfully: {}", title);
+                None
+            };
+
+            match skipped {
+                Some(reason) => info!("[dry run] Post {} would not be published: {}", id, reason),
+                None => {
+                    for target in post.targets_or_default() {
+                        if target.status == TargetStatus::Published {
+                            continue;
+                        }
+                        let publication = Publication::of(&post, &target);
+                        let request = match publishers.get(&target.channel) {
+                            Some(publisher) => publisher.dry_run(&publication).await.map_err(|e| e.to_string()),
+                            None => Err(format!("Unknown channel {}", target.channel)),
+                        };
+                        match request {
+                            Ok(request) => {
+                                info!("[dry run] Post {} would be published to {}: {}", id, target.label(), request)
+                            }
+                            Err(e) => info!("[dry run] Post {} would fail on {}: {}", id, target.label(), e),
+                        }
+                    }
+                    published.extend(post.pending_accounts().into_iter().map(|account| account.map(str::to_string)));
+                }
+            }
+
+            post.dry_run_at = Some(BsonDateTime::from_chrono(now));
+            if !posts.update(&post, PostStatus::Scheduled).await? {
+                info!("Post {} is no longer scheduled, skipping", id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Logs the posts that would be published within the next `scheduler.dry_run_report_hours`
+/// hours, when a dry run starts.
+///
+/// # Functionality
+///
+/// Every "scheduled" post up to that horizon is listed, in chronological order, with its
+/// scheduled time in the configured timezone, its account and its title. Posts that fall in a
+/// blackout window or belong to a paused account are flagged, as they would not be published at
+/// their scheduled time. Catch-up and pacing decisions depend on what is published in the
+/// meantime and are only reported once the posts are due (see `dry_run_due_posts`).
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be queried.
+async fn report_upcoming_posts<R: PostRepository + ?Sized>(
+    posts: &R,
+    pauses: &dyn PauseRepository,
+    now: DateTime<Utc>,
+    settings: &Settings,
+) -> Result<(), RepositoryError> {
+    let hours = settings.scheduler.dry_run_report_hours;
+    if hours == 0 {
+        return Ok(());
+    }
+    let end = now + ChronoDuration::hours(hours as i64);
+    let upcoming = posts.find_in_range(now, end, PostStatus::Scheduled).await?;
+    let paused = pauses.list().await?;
+    let offset = settings.timezone();
+
+    info!("[dry run] {} posts would be published in the next {} hours", upcoming.len(), hours);
+    for post in upcoming {
+        let scheduled_time = post.scheduled_time.to_chrono();
+        let mut notes = String::new();
+        if let Some(blackout) = blackout_at(&settings.scheduler.blackouts, scheduled_time, offset) {
+            notes.push_str(&format!(" (during {} until {})", blackout.window, blackout.until));
+        }
+        if let Some(pause) = pause_for_post(&paused, &post) {
+            notes.push_str(&format!(" (paused: {})", pause.reason));
+        }
+        let channels: Vec<String> = post.targets_or_default().iter().map(|target| target.label()).collect();
+        info!(
+            "[dry run]   {} {} \"{}\" on {}{}",
+            scheduled_time.with_timezone(&offset).format("%Y-%m-%d %H:%M"),
+            post.author.as_deref().unwrap_or("(access token owner)"),
+            post.title,
+            channels.join(", "),
+            notes
+        );
+    }
+    Ok(())
+}
+
+/// Records the catch-up outcome of a late post and applies it.
+///
+/// # Functionality
+///
+/// The outcome is stored in the post's `catch_up` field, then:
+///
+/// - `published`: the post is returned, to be published.
+/// - `rescheduled`: the post's `scheduled_time` moves to `rescheduled_to`; the change is
+///   recorded in `status_history` with the outcome as reason.
+/// - `missed`: the post moves to "missed", with the outcome as reason.
+///
+/// # Returns
+///
+/// The post to publish, or `None` if it must not be published now (including when it was
+/// edited or claimed by someone else in the meantime).
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be updated.
+async fn apply_catch_up<R: PostRepository + ?Sized>(
+    posts: &R,
+    mut post: Post,
+    outcome: CatchUpOutcome,
+    now: DateTime<Utc>,
+) -> Result<Option<Post>, RepositoryError> {
+    let Some(id) = post.id else {
+        return Ok(None);
+    };
+    let reason = outcome.describe();
+    info!("Post {} is {}", id, reason);
+
+    match outcome.action {
+        CatchUpAction::Published => {}
+        CatchUpAction::Rescheduled => {
+            post.scheduled_time = outcome.rescheduled_to.unwrap_or(post.scheduled_time);
+            post.record_transition(
+                StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Scheduled, SCHEDULER_ACTOR, now)
+                    .with_reason(&reason),
+            );
+        }
+        CatchUpAction::Missed => {
+            post.record_transition(
+                StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Missed, SCHEDULER_ACTOR, now)
+                    .with_reason(&reason),
+            );
+        }
+    }
+    let action = outcome.action;
+    post.catch_up = Some(outcome);
+
+    if !posts.update(&post, PostStatus::Scheduled).await? {
+        info!("Post {} is no longer scheduled, skipping", id);
+        return Ok(None);
+    }
+    Ok((action == CatchUpAction::Published).then_some(post))
+}
+
+/// Keeps the posts due during a blackout from being published.
+///
+/// # Functionality
+///
+/// - `defer`: each post's `scheduled_time` moves to the end of the blackout, which is recorded
+///   in its `status_history` with the blackout as reason.
+/// - `hold`: the posts are left untouched. They are published once the blackout ends, late,
+///   so their catch-up policy applies.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be updated.
+async fn apply_blackout<R: PostRepository + ?Sized>(
+    posts: &R,
+    due: Vec<Post>,
+    blackout: &Blackout,
+    policy: BlackoutPolicy,
+    now: DateTime<Utc>,
+) -> Result<(), RepositoryError> {
+    if due.is_empty() {
+        return Ok(());
+    }
+    if policy == BlackoutPolicy::Hold {
+        info!("Holding {} due posts during {} until {}", due.len(), blackout.window, blackout.until);
+        return Ok(());
+    }
+
+    let reason = format!("Due during {}; deferred to {}", blackout.window, blackout.until);
+    for mut post in due {
+        let Some(id) = post.id else {
+            continue;
+        };
+        info!("Post {}: {}", id, reason);
+        post.scheduled_time = BsonDateTime::from_chrono(blackout.until);
+        post.record_transition(
+            StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Scheduled, SCHEDULER_ACTOR, now)
+                .with_reason(&reason),
+        );
+        if !posts.update(&post, PostStatus::Scheduled).await? {
+            info!("Post {} is no longer scheduled, skipping", id);
+        }
+    }
+    Ok(())
+}
+
+/// Defers a due post that would break the pacing rule of one of its accounts if published at
+/// `now`.
+///
+/// # Functionality
+///
+/// The post is checked against the rule of every account it is still to be published on
+/// behalf of (see `PostPacing`), each with respect to the account's posts that are already
+/// out ("publishing" or "published"); posts still waiting are checked when they are due. If
+/// the rules allow publishing now, the post is returned. Otherwise its `scheduled_time` moves
+/// to the earliest time every rule allows (see `PostPacing::next_allowed`), which is recorded
+/// in `status_history` with the violated limit as reason.
+///
+/// # Returns
+///
+/// The post to publish, or `None` if it was deferred (or edited or claimed by someone else in
+/// the meantime).
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be queried or updated.
+async fn apply_pacing<R: PostRepository + ?Sized>(
+    posts: &R,
+    mut post: Post,
+    settings: &Settings,
+    now: DateTime<Utc>,
+) -> Result<Option<Post>, RepositoryError> {
+    let Some(id) = post.id else {
+        return Ok(None);
+    };
+    let pacing = PostPacing::load(posts, &post, &settings.scheduler, PUBLISHING_STATUSES, now).await?;
+    if pacing.is_unrestricted() {
+        return Ok(Some(post));
+    }
+
+    let offset = settings.timezone();
+    let Err(violation) = pacing.check(now, offset) else {
+        return Ok(Some(post));
+    };
+
+    let deferred_to = pacing.next_allowed(now, offset);
+    let reason = format!("{}; deferred to {} by the pacing rule", violation, deferred_to);
+    info!("Post {} is not published now: {}", id, reason);
+    post.scheduled_time = BsonDateTime::from_chrono(deferred_to);
+    post.record_transition(
+        StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Scheduled, SCHEDULER_ACTOR, now)
+            .with_reason(&reason),
+    );
+
+    if !posts.update(&post, PostStatus::Scheduled).await? {
+        info!("Post {} is no longer scheduled, skipping", id);
+    }
+    Ok(None)
+}
+
+/// Splits posts into one queue per account, preserving their order within each queue.
+///
+/// The accounts of a post are those its targets not published yet go out on behalf of (see
+/// `Post::pending_accounts`); targets without an account are published on behalf of the owner
+/// of the channel's credentials, which counts as one account. A post published on several
+/// accounts joins the queues of all of them into one, so that two posts never go out on the
+/// same account at the same time. Queues are returned in the order of their first post.
+fn queues_by_account(posts: Vec<Post>) -> Vec<Vec<Post>> {
+    // The accounts of each queue, and its posts with their position in `posts`.
+    type Queue = (Vec<Option<String>>, Vec<(usize, Post)>);
+    let mut queues: Vec<Queue> = Vec::new();
+    for (index, post) in posts.into_iter().enumerate() {
+        let mut accounts: Vec<Option<String>> =
+            post.pending_accounts().into_iter().map(|account| account.map(str::to_string)).collect();
+        let mut queue = vec![(index, post)];
+        let (shared, separate) = queues
+            .into_iter()
+            .partition(|(queued, _)| queued.iter().any(|account| accounts.contains(account)));
+        queues = separate;
+        for (queued, posts) in shared {
+            for account in queued {
+                if !accounts.contains(&account) {
+                    accounts.push(account);
+                }
+            }
+            queue.extend(posts);
+        }
+        queue.sort_by_key(|(index, _)| *index);
+        queues.push((accounts, queue));
+    }
+    queues.sort_by_key(|(_, queue)| queue[0].0);
+    queues
+        .into_iter()
+        .map(|(_, queue)| queue.into_iter().map(|(_, post)| post).collect())
+        .collect()
+}
+
+/// Publishes one due post.
+///
+/// # Functionality
+///
+/// 1. The post is claimed by moving it from "scheduled" to "publishing"; a post whose
+///    status changed in the meantime is skipped.
+/// 2. The post is published on each of its targets not published yet (see
+///    `Post::targets_or_default`), one after the other, through the publisher of the target's
+///    channel, with the target's text and account (see `Publication::of`). On LinkedIn, a post
+///    without an account is published on behalf of the profile owning the access token. The
+///    outcome of each target, its remote ID or the error, is saved as soon as the target is
+///    done, while the post is still "publishing": if the post is released by a shutdown before
+///    its last target is done, the targets already published are not published again.
+/// 3. The post moves to the status derived from its targets (see `overall_status`):
+///    "published", "partially_published" or "failed", with the errors of the failed targets as
+///    reason. A post scheduled again is only published on the targets that failed.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be updated. Publication errors are logged and
+/// recorded on the post.
+async fn publish_post<R: PostRepository + ?Sized>(
+    posts: &R,
+    mut post: Post,
+    publishers: &Publishers,
+    clock: &dyn Clock,
+    claimed: &ClaimedPosts,
+) -> Result<(), RepositoryError> {
+    let Some(id) = post.id else {
+        return Ok(());
+    };
+    info!("Publishing post {} scheduled at {}", id, post.scheduled_time);
+
+    // Claim the post so that it is not published twice and cannot be edited while
+    // it is being published.
+    let claim =
+        StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Publishing, SCHEDULER_ACTOR, clock.now());
+    if !posts.transition(id, claim.clone()).await? {
+        info!("Post {} is no longer scheduled, skipping", id);
+        return Ok(());
+    }
+    post.record_transition(claim);
+    claimed.0.lock().unwrap().insert(id);
+
+    post.targets = post.targets_or_default();
+    for index in 0..post.targets.len() {
+        if post.targets[index].status == TargetStatus::Published {
+            continue;
+        }
+        let publication = Publication::of(&post, &post.targets[index]);
+        let result = match publishers.get(&post.targets[index].channel) {
+            Some(publisher) => publisher.publish(&publication).await.map_err(|e| e.to_string()),
+            None => Err(format!("Unknown channel {}", post.targets[index].channel)),
+        };
+        let target = &mut post.targets[index];
+        match result {
+            Ok(remote_id) => {
+                info!("Post {} published to {}", id, target.label());
+                target.status = TargetStatus::Published;
+                target.remote_id = remote_id;
+                target.published_at = Some(BsonDateTime::from_chrono(clock.now()));
+                target.error = None;
             }
+            Err(e) => {
+                error!("Error publishing post {} to {}: {}", id, target.label(), e);
+                target.status = TargetStatus::Failed;
+                target.error = Some(e);
+            }
+        }
+        if !posts.update(&post, PostStatus::Publishing).await? {
+            warn!("Post {} was released while being published, its remaining targets are left", id);
+            claimed.0.lock().unwrap().remove(&id);
+            return Ok(());
+        }
+    }
+
+    let errors: Vec<String> = post
+        .targets
+        .iter()
+        .filter_map(|target| target.error.as_ref().map(|error| format!("{}: {}", target.label(), error)))
+        .collect();
+    let status = overall_status(&post.targets);
+    let mut transition = StatusTransition::new(Some(PostStatus::Publishing), status, SCHEDULER_ACTOR, clock.now());
+    if errors.is_empty() {
+        info!("Post published successfully: {}", post.title);
+    } else {
+        transition = transition.with_reason(errors.join("; "));
+    }
+    post.record_transition(transition);
+    if !posts.update(&post, PostStatus::Publishing).await? {
+        warn!("Post {} was released while being published, its outcome is not recorded", id);
+    }
+    claimed.0.lock().unwrap().remove(&id);
+
+    Ok(())
+}
+
+/// Moves the posts whose publication was interrupted by a shutdown back to "scheduled".
+///
+/// Such a post may or may not have been accepted by LinkedIn, so each release is logged as a
+/// warning for someone to check. Errors are logged, as the scheduler is exiting anyway.
+async fn release_claimed_posts<R: PostRepository + ?Sized>(posts: &R, claimed: &ClaimedPosts, now: DateTime<Utc>) {
+    let ids: Vec<ObjectId> = claimed.0.lock().unwrap().drain().collect();
+    for id in ids {
+        let transition =
+            StatusTransition::new(Some(PostStatus::Publishing), PostStatus::Scheduled, SCHEDULER_ACTOR, now)
+                .with_reason("The scheduler shut down while publishing the post");
+        match posts.transition(id, transition).await {
+            Ok(true) => warn!("Released post {} back to scheduled; check whether LinkedIn published it", id),
+            Ok(false) => {}
+            Err(e) => error!("Cannot release post {}: {}", id, e),
         }
+    }
+}
 
-        time::sleep(Duration::from_secs(20)).await;
+/// Moves the posts abandoned in "publishing" back to "scheduled", so that they are published.
+///
+/// A post is abandoned once it has been "publishing" for longer than `timeout`, the shutdown
+/// timeout of the scheduler (see `Post::is_abandoned`): the scheduler that claimed it stopped
+/// without releasing it, e.g. because it crashed. Posts claimed more recently may still be
+/// published by another scheduler instance and are left alone.
+///
+/// The targets already published are kept, but the one being published when the scheduler
+/// stopped may have been accepted by LinkedIn, so each release is logged as a warning for
+/// someone to check, and recorded with a reason in the status history.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be read or updated.
+async fn release_abandoned_posts<R: PostRepository + ?Sized>(
+    posts: &R,
+    now: DateTime<Utc>,
+    timeout: ChronoDuration,
+) -> Result<(), RepositoryError> {
+    for post in posts.find_in_range(DateTime::<Utc>::MIN_UTC, now, PostStatus::Publishing).await? {
+        let (Some(id), Some(claimed_at)) = (post.id, post.claimed_at()) else {
+            continue;
+        };
+        if !post.is_abandoned(now, timeout) {
+            continue;
+        }
+        let reason = format!("The scheduler publishing the post since {} stopped without releasing it", claimed_at);
+        let transition =
+            StatusTransition::new(Some(PostStatus::Publishing), PostStatus::Scheduled, SCHEDULER_ACTOR, now)
+                .with_reason(reason);
+        if posts.transition(id, transition).await? {
+            warn!("Released abandoned post {} back to scheduled; check whether LinkedIn published it", id);
+        }
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use mockito::Server;
-    use mongodb::bson::oid::ObjectId;
+    use chrono::{Duration as ChronoDuration, FixedOffset, TimeZone};
+    use linkedin_automation::clock::TestClock;
+    use linkedin_automation::config::reload::fixed;
+    use linkedin_automation::config::settings::{local_to_utc, SchedulerSettings};
+    use linkedin_automation::models::pause::Pause;
+    use linkedin_automation::models::post::{Target, DEFAULT_CHANNEL};
+    use linkedin_automation::publishing::publisher::{ChannelSettings, PostMetrics, PublishError, Publisher};
+    use linkedin_automation::publishing::webhook::{DEFAULT_TIMEOUT_SECS, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+    use linkedin_automation::repository::memory::{InMemoryPauseRepository, InMemoryPostRepository};
+    use linkedin_automation::scheduling::blackout::BlackoutWindow;
+    use linkedin_automation::scheduling::catch_up::CatchUpPolicy;
+    use linkedin_automation::scheduling::pacing::PacingRule;
+    use mockito::{Matcher, Server};
     use std::sync::Once;
-    use futures_util::StreamExt;
 
     static INIT: Once = Once::new();
 
@@ -169,36 +1076,31 @@ mod tests {
         });
     }
 
-    /// Creates a mock MongoDB client for testing purposes.
-    ///
-    /// This function sets up an in-memory MongoDB instance that can be used for testing without affecting a real database.
-    ///
-    /// # Returns
-    ///
-    /// Returns a `Result` containing a `mongodb::Client` if successful, or an error if the client creation fails.
-    async fn create_mock_mongo_client() -> Result<Client, mongodb::error::Error> {
-        Client::with_uri_str("mongodb://localhost:27017").await
+    /// Returns the publishers of the default settings, with LinkedIn at `url`.
+    fn linkedin(url: &str) -> Publishers {
+        let mut settings = Settings::default();
+        settings.linkedin.api_base_url = url.to_string();
+        publishers_for(&settings, &Secret::new("mock-token"), Arc::new(SystemClock))
     }
 
-    /// Tests the retrieval of scheduled posts from a MongoDB collection.
+    /// Tests the retrieval of scheduled posts from a post repository.
     ///
     /// This asynchronous test function performs the following steps:
     ///
     /// 1. **Initialization**: Calls the `initialize` function to set up the test environment,
     ///    which typically includes configuring logging or other necessary setups for testing.
     ///
-    /// 2. **MongoDB Client Setup**: Creates a mock MongoDB client using the `create_mock_mongo_client`
-    ///    function. This client is connected to a test database (`test_lkdin-posts`) to ensure that
-    ///    the test does not affect the production data.
+    /// 2. **Repository Setup**: Creates an `InMemoryPostRepository`, so that the test runs
+    ///    without a MongoDB server and does not affect any real data.
     ///
-    /// 3. **Document Insertion**: Inserts a test post document into the `posts` collection.
-    ///    The document includes a title, content, scheduled time (current UTC time), and a status of "pending".
+    /// 3. **Document Insertion**: Inserts a test post into the repository.
+    ///    The document includes a title, content, scheduled time (current UTC time), and a status of "scheduled".
     ///
-    /// 4. **Document Count Check**: Counts the number of documents in the collection after insertion
+    /// 4. **Document Count Check**: Counts the number of posts in the repository after insertion
     ///    to ensure the test setup is correct.
     ///
-    /// 5. **Document Retrieval**: Queries the `posts` collection to find documents that have a `scheduled_time`
-    ///    less than or equal to the current time and a status of "pending". This simulates the typical behavior
+    /// 5. **Document Retrieval**: Queries the repository with `find_due` to find posts that have a `scheduled_time`
+    ///    less than or equal to the current time and a status of "scheduled". This simulates the typical behavior
     ///    of retrieving posts that are scheduled to be published.
     ///
     /// 6. **Assertion**: Iterates over the retrieved documents to find a match with the inserted test post.
@@ -209,7 +1111,6 @@ mod tests {
     ///
     /// This function will panic if:
     ///
-    /// - The MongoDB client cannot be created.
     /// - The document insertion fails.
     /// - The document retrieval process encounters an error.
     /// - No matching documents are found after the retrieval query.
@@ -221,54 +1122,26 @@ mod tests {
     /// ```bash
     /// cargo test -- --test-threads=1
     /// ```
-    ///
-    /// The test will output information about the insertion result, the number of documents in the collection,
-    /// and details about the retrieved document(s).
-    ///
     #[tokio::test]
     async fn test_retrieve_scheduled_posts() {
         initialize();
 
-        let client = create_mock_mongo_client().await.unwrap();
-        let db = client.database("test_lkdin-posts");
-        let posts: mongodb::Collection<Document> = db.collection("posts");
+        let posts = InMemoryPostRepository::new();
 
         let now = Utc::now();
         let bson_now = BsonDateTime::from_chrono(now);
-        let test_post = doc! {
-            "_id": ObjectId::new(),
-            "title": "Test Post",
-            "content": "Test Content",
-            "scheduled_time": bson_now,
-            "status": "pending"
-        };
-
-        let insert_result = posts.insert_one(test_post.clone()).await.unwrap();
-        println!("Insert result: {:?}", insert_result);
-
-        let count = posts.count_documents(doc! {}).await.unwrap();
-        println!("Number of documents in collection: {}", count);
+        let test_post = Post::new("Test Post".to_string(), "Test Content".to_string(), bson_now, Utc::now());
 
-        let filter = doc! {
-            "scheduled_time": { "$lte": bson_now },
-            "status": "pending"
-        };
-        println!("Filter: {:?}", filter);
-        println!("Inserted document: {:?}", test_post);
+        let insert_result = posts.insert(test_post).await.unwrap();
+        assert_eq!(posts.all().len(), 1);
 
-        let mut cursor = posts.find(filter.clone()).await.unwrap();
         let mut found = false;
-        while let Some(doc_result) = cursor.next().await {
-            match doc_result {
-                Ok(doc) => {
-                    println!("Found document: {:?}", doc);
-                    assert_eq!(doc.get_str("title").unwrap(), "Test Post");
-                    assert_eq!(doc.get_str("content").unwrap(), "Test Content");
-                    assert_eq!(doc.get_str("status").unwrap(), "pending");
-                    found = true;
-                }
-                Err(e) => println!("Error while iterating: {:?}", e),
-            }
+        for post in posts.find_due(now).await.unwrap() {
+            assert_eq!(post.id, Some(insert_result));
+            assert_eq!(post.title, "Test Post");
+            assert_eq!(post.content, "Test Content");
+            assert_eq!(post.status, PostStatus::Scheduled);
+            found = true;
         }
 
         assert!(found, "No matching documents were found");
@@ -276,15 +1149,17 @@ mod tests {
 
     /// Tests error handling during post publication.
     ///
-    /// This test verifies that the scheduler correctly handles errors that occur during the publication process.
+    /// This test verifies that a scheduler run records a publication error on the post instead
+    /// of failing or retrying it.
     ///
     /// # Steps
     ///
     /// 1. Initializes the test environment.
-    /// 2. Creates a mock MongoDB client and a mock LinkedIn API server that returns an error.
-    /// 3. Inserts a test post into the database.
-    /// 4. Attempts to publish the post.
-    /// 5. Verifies that the post status remains "pending" in the database after a failed publication attempt.
+    /// 2. Creates an in-memory post repository and a mock LinkedIn API server that returns an error.
+    /// 3. Inserts a post due at 09:00.
+    /// 4. Runs the scheduler once at 09:00.
+    /// 5. Verifies that the post is "failed", with the error on its target and a status history
+    ///    entry from "publishing" to "failed" giving it as reason.
     ///
     /// # Panics
     ///
@@ -293,54 +1168,1235 @@ mod tests {
     async fn test_publication_error_handling() {
         initialize();
 
-        let client = create_mock_mongo_client().await.unwrap();
-        let db = client.database("test_lkdin-posts");
-        let posts: mongodb::Collection<Document> = db.collection("posts");
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let pauses = Arc::new(InMemoryPauseRepository::new());
 
         let mut server = Server::new_async().await;
         let mock = server
             .mock("POST", "/v2/ugcPosts")
             .with_status(500)
             .with_body("Internal Server Error")
+            .expect(1)
             .create_async()
             .await;
 
-        let test_post = doc! {
-            "_id": ObjectId::new(),
-            "title": "Error Test Post",
-            "content": "Error Test Content",
-            "scheduled_time": Utc::now().to_rfc3339(),
-            "status": "pending"
-        };
+        let clock = Arc::new(TestClock::new(nine()));
+        let mut test_post = Post::new(
+            "Error Test Post".to_string(),
+            "Error Test Content".to_string(),
+            BsonDateTime::from_chrono(nine()),
+            nine(),
+        );
+        test_post.author = Some("mock_profile_id".to_string());
+        let id = posts.insert(test_post).await.unwrap();
 
-        posts.insert_one(test_post.clone()).await.unwrap();
+        let scheduler = scheduler(&posts, &pauses, linkedin(&server.url()), &clock, Settings::default()).await;
+        scheduler.run_once(&Shutdown::channel().1).await.unwrap();
 
-        let filter = doc! { "status": "pending" };
-        let retrieved_post = posts.find_one(filter).await.unwrap().unwrap();
+        let failed = posts.find(id).await.unwrap().unwrap();
+        assert_eq!(failed.status, PostStatus::Failed);
+        assert_eq!(failed.targets[0].status, TargetStatus::Failed);
+        assert!(failed.targets[0].error.as_ref().unwrap().contains("Failed to publish article"));
+        let last = failed.status_history.last().unwrap();
+        assert_eq!((last.from, last.to), (Some(PostStatus::Publishing), PostStatus::Failed));
+        assert_eq!(last.actor, SCHEDULER_ACTOR);
+        assert!(last.reason.as_ref().unwrap().contains("Failed to publish article"));
 
-        let title = retrieved_post.get_str("title").unwrap();
-        let content = retrieved_post.get_str("content").unwrap();
+        mock.assert_async().await;
+    }
 
-        let access_token = "mock_token";
-        let profile_id = "mock_profile_id";
-        let result = publish_article(
-            access_token,
-            profile_id,
-            title,
-            content,
-            Some(&server.url()),
-        )
-        .await;
+    /// Tests that a post fires at the same UTC instant whatever offset it was scheduled with.
+    ///
+    /// A post scheduled for 09:00 local time by a web server configured with a given offset
+    /// must not be due one second before the corresponding UTC instant, and must be due
+    /// from that instant on. The scheduler itself never reads an offset, so the result does
+    /// not depend on the offset configured in its own container.
+    #[test]
+    fn test_post_fires_at_utc_instant_across_offsets() {
+        let cases = [
+            (-3, Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap()),
+            (0, Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap()),
+            (2, Utc.with_ymd_and_hms(2024, 5, 10, 7, 0, 0).unwrap()),
+            (9, Utc.with_ymd_and_hms(2024, 5, 10, 0, 0, 0).unwrap()),
+        ];
 
-        assert!(result.is_err());
+        for (hours, expected) in cases {
+            let offset = FixedOffset::east_opt(hours * 3600).unwrap();
+            let scheduled = local_to_utc("2024-05-10 09:00", "%Y-%m-%d %H:%M", offset).unwrap();
+            let stored = BsonDateTime::from_chrono(scheduled);
 
-        let updated_post = posts
-            .find_one(doc! {"_id": retrieved_post.get_object_id("_id").unwrap()})
-            .await
-            .unwrap()
-            .unwrap();
-        assert_eq!(updated_post.get_str("status").unwrap(), "pending");
+            assert_eq!(stored.to_chrono(), expected, "offset: {}", hours);
+            let stored = stored.to_chrono();
+            assert!(!is_due(stored, expected - ChronoDuration::seconds(1)), "offset: {}", hours);
+            assert!(is_due(stored, expected), "offset: {}", hours);
+            assert!(is_due(stored, expected + ChronoDuration::hours(1)), "offset: {}", hours);
+        }
+    }
 
-        mock.assert_async().await;
+    /// Tests that posts scheduled with different offsets for the same local time fire in UTC order.
+    ///
+    /// At 08:00 UTC, 09:00 in UTC+2 (07:00 UTC) has already passed, while 09:00 in UTC-3
+    /// (12:00 UTC) is still in the future.
+    #[test]
+    fn test_due_selection_with_mixed_offsets() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 8, 0, 0).unwrap();
+        let schedule = |hours: i32| {
+            let offset = FixedOffset::east_opt(hours * 3600).unwrap();
+            local_to_utc("2024-05-10 09:00", "%Y-%m-%d %H:%M", offset).unwrap()
+        };
+
+        assert!(is_due(schedule(2), now));
+        assert!(!is_due(schedule(0), now));
+        assert!(!is_due(schedule(-3), now));
+    }
+
+    /// Tests that the scheduler sleeps exactly until the next post, bounded by the poll interval.
+    #[test]
+    fn test_time_until_next_check() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 8, 0, 0).unwrap();
+        let max_wait = Duration::from_secs(20);
+
+        assert_eq!(time_until_next_check(None, now, max_wait), max_wait);
+        assert_eq!(
+            time_until_next_check(Some(now + ChronoDuration::milliseconds(7_250)), now, max_wait),
+            Duration::from_millis(7_250)
+        );
+        assert_eq!(
+            time_until_next_check(Some(now + ChronoDuration::hours(1)), now, max_wait),
+            max_wait
+        );
+        assert_eq!(time_until_next_check(Some(now), now, max_wait), Duration::ZERO);
+        assert_eq!(
+            time_until_next_check(Some(now - ChronoDuration::minutes(5)), now, max_wait),
+            Duration::ZERO
+        );
+    }
+
+    /// Tests that scheduling a post wakes up a scheduler waiting for post changes.
+    #[tokio::test]
+    async fn test_new_post_wakes_up_scheduler() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let mut changes = watch_posts(&posts).await;
+
+        posts
+            .insert(Post::new("New".to_string(), "Content".to_string(), BsonDateTime::now(), Utc::now()))
+            .await
+            .unwrap();
+
+        let woken = time::timeout(Duration::from_secs(1), changes.next()).await;
+        assert_eq!(woken, Ok(Some(())));
+    }
+
+    /// Tests that a due post is claimed, published and marked as published.
+    ///
+    /// The mock LinkedIn API accepts the publication; the post must end in the "published"
+    /// status with `published_at` set and the full transition history recorded.
+    #[tokio::test]
+    async fn test_publish_due_posts_success() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let userinfo = server
+            .mock("GET", "/v2/userinfo")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"sub": "mock-profile-id"}"#)
+            .create_async()
+            .await;
+        let ugc_posts = server
+            .mock("POST", "/v2/ugcPosts")
+            .match_body(mockito::Matcher::Regex("urn:li:person:mock-profile-id".to_string()))
+            .with_status(201)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let now = Utc::now();
+        let due = posts
+            .insert(Post::new("Due".to_string(), "Due content".to_string(), BsonDateTime::from_chrono(now), Utc::now()))
+            .await
+            .unwrap();
+        let future = posts
+            .insert(Post::new(
+                "Future".to_string(),
+                "Future content".to_string(),
+                BsonDateTime::from_chrono(now + ChronoDuration::hours(1)),
+                Utc::now(),
+            ))
+            .await
+            .unwrap();
+
+        let (settings, shutdown, claimed) = (Settings::default(), Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let published = posts.find(due).await.unwrap().unwrap();
+        assert_eq!(published.status, PostStatus::Published);
+        assert!(published.published_at.is_some());
+        let history: Vec<_> = published.status_history.iter().map(|t| (t.from, t.to)).collect();
+        assert_eq!(
+            history,
+            vec![
+                (Some(PostStatus::Scheduled), PostStatus::Publishing),
+                (Some(PostStatus::Publishing), PostStatus::Published),
+            ]
+        );
+        assert_eq!(posts.find(future).await.unwrap().unwrap().status, PostStatus::Scheduled);
+
+        userinfo.assert_async().await;
+        ugc_posts.assert_async().await;
+    }
+
+    /// Tests that a post whose publication fails is marked as failed with the error as reason.
+    #[tokio::test]
+    async fn test_publish_due_posts_failure() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let ugc_posts = server
+            .mock("POST", "/v2/ugcPosts")
+            .with_status(500)
+            .with_body("Internal Server Error")
+            .create_async()
+            .await;
+
+        let now = Utc::now();
+        let mut post = Post::new("Failing".to_string(), "Content".to_string(), BsonDateTime::from_chrono(now), now);
+        post.author = Some("mock-profile-id".to_string());
+        let id = posts.insert(post).await.unwrap();
+
+        let (settings, shutdown, claimed) = (Settings::default(), Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let failed = posts.find(id).await.unwrap().unwrap();
+        assert_eq!(failed.status, PostStatus::Failed);
+        let last = failed.status_history.last().unwrap();
+        assert_eq!(last.actor, SCHEDULER_ACTOR);
+        assert!(last.reason.is_some());
+
+        ugc_posts.assert_async().await;
+    }
+
+    /// Tests that due posts are split into per-account queues that keep their order.
+    ///
+    /// A post of the access token's owner with a target on bob's account joins both queues.
+    #[test]
+    fn test_queues_by_account() {
+        let post = |title: &str, author: Option<&str>| {
+            let mut post = Post::new(title.to_string(), "Content".to_string(), BsonDateTime::now(), Utc::now());
+            post.author = author.map(str::to_string);
+            post
+        };
+        let mut cross_post = post("owner3", None);
+        let mut bob = Target::new(DEFAULT_CHANNEL);
+        bob.author = Some("bob".to_string());
+        cross_post.targets = vec![Target::new(DEFAULT_CHANNEL), bob];
+        let due = vec![
+            post("a1", Some("alice")),
+            post("owner1", None),
+            post("b1", Some("bob")),
+            post("a2", Some("alice")),
+            post("owner2", None),
+            cross_post,
+            post("b2", Some("bob")),
+        ];
+
+        let titles: Vec<Vec<String>> = queues_by_account(due)
+            .into_iter()
+            .map(|queue| queue.into_iter().map(|post| post.title).collect())
+            .collect();
+
+        assert_eq!(titles, vec![vec!["a1", "a2"], vec!["owner1", "b1", "owner2", "owner3", "b2"]]);
+    }
+
+    /// Tests that every due post of several accounts is published by a saturated pool.
+    ///
+    /// With a single worker, the posts of both accounts must still all be published, and
+    /// the two posts of the same account in their scheduled order.
+    #[tokio::test]
+    async fn test_publish_due_posts_with_saturated_pool() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let ugc_posts = server
+            .mock("POST", "/v2/ugcPosts")
+            .with_status(201)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let now = Utc::now();
+        let one_worker = Settings {
+            scheduler: SchedulerSettings {
+                max_concurrent_publishes: 1,
+                ..SchedulerSettings::default()
+            },
+            ..Settings::default()
+        };
+        let mut ids = Vec::new();
+        for (minutes, author) in [(3, "alice"), (2, "bob"), (1, "alice")] {
+            let scheduled_time = BsonDateTime::from_chrono(now - ChronoDuration::minutes(minutes));
+            let mut post = Post::new(format!("{} {}", author, minutes), "Content".to_string(), scheduled_time, now);
+            post.author = Some(author.to_string());
+            ids.push(posts.insert(post).await.unwrap());
+        }
+
+        let (shutdown, claimed) = (Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &one_worker, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let mut published = Vec::new();
+        for id in &ids {
+            let post = posts.find(*id).await.unwrap().unwrap();
+            assert_eq!(post.status, PostStatus::Published, "{}", post.title);
+            published.push(post.published_at.unwrap());
+        }
+        assert!(published[0] <= published[2]);
+
+        ugc_posts.assert_async().await;
+    }
+
+    /// Tests that no post is claimed once a shutdown has been requested.
+    #[tokio::test]
+    async fn test_no_post_claimed_after_shutdown() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let now = Utc::now();
+        let id = posts
+            .insert(Post::new("Due".to_string(), "Content".to_string(), BsonDateTime::from_chrono(now), Utc::now()))
+            .await
+            .unwrap();
+
+        let (sender, shutdown) = Shutdown::channel();
+        sender.send_replace(true);
+        let claimed = ClaimedPosts::default();
+        let settings = Settings::default();
+        let publishers = linkedin("http://127.0.0.1:9");
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &publishers, &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        assert_eq!(posts.find(id).await.unwrap().unwrap().status, PostStatus::Scheduled);
+        assert!(claimed.0.lock().unwrap().is_empty());
+    }
+
+    /// Tests that posts left in "publishing" by an interrupted shutdown are released.
+    #[tokio::test]
+    async fn test_release_claimed_posts() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let id = posts
+            .insert(Post::new("Claimed".to_string(), "Content".to_string(), BsonDateTime::now(), Utc::now()))
+            .await
+            .unwrap();
+        posts.claim(id, SCHEDULER_ACTOR, Utc::now()).await.unwrap();
+        let claimed = ClaimedPosts::default();
+        claimed.0.lock().unwrap().insert(id);
+
+        release_claimed_posts(&posts, &claimed, Utc::now()).await;
+
+        let released = posts.find(id).await.unwrap().unwrap();
+        assert_eq!(released.status, PostStatus::Scheduled);
+        let last = released.status_history.last().unwrap();
+        assert_eq!(last.from, Some(PostStatus::Publishing));
+        assert!(last.reason.is_some());
+        assert!(claimed.0.lock().unwrap().is_empty());
+    }
+
+    /// Tests that the posts "publishing" for longer than the shutdown timeout are released,
+    /// while a post claimed more recently is left to the scheduler publishing it.
+    #[tokio::test]
+    async fn test_release_abandoned_posts() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let mut ids = Vec::new();
+        for title in ["Abandoned", "Publishing", "Scheduled"] {
+            let post = Post::new(title.to_string(), "Content".to_string(), BsonDateTime::now(), Utc::now());
+            ids.push(posts.insert(post).await.unwrap());
+        }
+        posts.claim(ids[0], SCHEDULER_ACTOR, Utc::now()).await.unwrap();
+        let now = Utc::now() + ChronoDuration::minutes(1);
+        let mut recent = posts.find(ids[1]).await.unwrap().unwrap();
+        recent.status = PostStatus::Publishing;
+        let claim = StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Publishing, SCHEDULER_ACTOR, now);
+        recent.status_history.push(claim);
+        posts.update(&recent, PostStatus::Scheduled).await.unwrap();
+
+        release_abandoned_posts(&posts, now, ChronoDuration::seconds(30)).await.unwrap();
+
+        let mut statuses = Vec::new();
+        for id in &ids {
+            statuses.push(posts.find(*id).await.unwrap().unwrap().status);
+        }
+        assert_eq!(statuses, [PostStatus::Scheduled, PostStatus::Publishing, PostStatus::Scheduled]);
+        let released = posts.find(ids[0]).await.unwrap().unwrap();
+        let last = released.status_history.last().unwrap();
+        assert_eq!(last.from, Some(PostStatus::Publishing));
+        assert!(last.reason.as_deref().unwrap().contains("stopped without releasing it"));
+    }
+
+    /// Tests that late posts are published, rescheduled or marked missed by their policies.
+    ///
+    /// After a day of downtime, alice's three posts are spread 30 minutes apart while the
+    /// company page's post, whose account is configured with `mark_missed`, is not published.
+    #[tokio::test]
+    async fn test_publish_due_posts_applies_catch_up_policies() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let ugc_posts = server
+            .mock("POST", "/v2/ugcPosts")
+            .with_status(201)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for (hours, author) in [(26, "alice"), (25, "company"), (24, "alice"), (23, "alice")] {
+            let scheduled_time = BsonDateTime::from_chrono(now - ChronoDuration::hours(hours));
+            let mut post = Post::new(format!("{} {}", author, hours), "Content".to_string(), scheduled_time, now);
+            post.author = Some(author.to_string());
+            ids.push(posts.insert(post).await.unwrap());
+        }
+
+        let mut scheduler = SchedulerSettings {
+            catch_up: CatchUpPolicy::Spread { interval_minutes: 30 },
+            ..SchedulerSettings::default()
+        };
+        scheduler
+            .catch_up_accounts
+            .insert("company".to_string(), CatchUpPolicy::MarkMissed);
+        let settings = Settings {
+            scheduler,
+            ..Settings::default()
+        };
+        let (shutdown, claimed) = (Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let mut stored = Vec::new();
+        for id in &ids {
+            stored.push(posts.find(*id).await.unwrap().unwrap());
+        }
+        let statuses: Vec<_> = stored.iter().map(|post| post.status).collect();
+        assert_eq!(
+            statuses,
+            vec![PostStatus::Published, PostStatus::Missed, PostStatus::Scheduled, PostStatus::Scheduled]
+        );
+        let actions: Vec<_> = stored.iter().map(|post| post.catch_up.as_ref().unwrap().action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                CatchUpAction::Published,
+                CatchUpAction::Missed,
+                CatchUpAction::Rescheduled,
+                CatchUpAction::Rescheduled
+            ]
+        );
+        assert_eq!(stored[2].scheduled_time, BsonDateTime::from_chrono(now + ChronoDuration::minutes(30)));
+        assert_eq!(stored[3].scheduled_time, BsonDateTime::from_chrono(now + ChronoDuration::minutes(60)));
+        assert!(stored[1].status_history.last().unwrap().reason.is_some());
+
+        ugc_posts.assert_async().await;
+    }
+
+    /// Tests that due posts of an account are deferred once its pacing rule is reached.
+    ///
+    /// With a 2-hour gap and one post a day, the first of alice's two due posts is published
+    /// and the second is moved to the next day, at least 2 hours later, while bob's post, whose
+    /// account has no limit, is published too.
+    #[tokio::test]
+    async fn test_publish_due_posts_defers_paced_posts() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let ugc_posts = server
+            .mock("POST", "/v2/ugcPosts")
+            .with_status(201)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for (minutes, author) in [(2, "alice"), (1, "alice"), (1, "bob")] {
+            let scheduled_time = BsonDateTime::from_chrono(now - ChronoDuration::minutes(minutes));
+            let mut post = Post::new(format!("{} {}", author, minutes), "Content".to_string(), scheduled_time, now);
+            post.author = Some(author.to_string());
+            ids.push(posts.insert(post).await.unwrap());
+        }
+
+        let mut settings = Settings::default();
+        settings.scheduler.pacing_accounts.insert(
+            "alice".to_string(),
+            PacingRule {
+                min_gap_minutes: Some(120),
+                max_per_day: Some(1),
+                ..PacingRule::default()
+            },
+        );
+        let (shutdown, claimed) = (Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let first = posts.find(ids[0]).await.unwrap().unwrap();
+        assert_eq!(first.status, PostStatus::Published);
+        let deferred = posts.find(ids[1]).await.unwrap().unwrap();
+        assert_eq!(deferred.status, PostStatus::Scheduled);
+        let published_at = first.published_at.unwrap().to_chrono();
+        let deferred_to = deferred.scheduled_time.to_chrono();
+        assert!(deferred_to >= published_at + ChronoDuration::minutes(120));
+        assert_ne!(deferred_to.date_naive(), published_at.date_naive());
+        let last = deferred.status_history.last().unwrap();
+        assert_eq!((last.from, last.to), (Some(PostStatus::Scheduled), PostStatus::Scheduled));
+        assert!(last.reason.as_ref().unwrap().contains("pacing"));
+        assert_eq!(posts.find(ids[2]).await.unwrap().unwrap().status, PostStatus::Published);
+
+        ugc_posts.assert_async().await;
+    }
+
+    /// Tests that due posts are deferred to the end of a blackout, or held, per policy.
+    #[tokio::test]
+    async fn test_publish_due_posts_during_blackout() {
+        initialize();
+
+        let now = Utc::now();
+        let format = |time: DateTime<Utc>| time.format("%Y-%m-%d %H:%M").to_string();
+        let until = format(now + ChronoDuration::hours(1));
+        let mut settings = Settings::default();
+        settings.scheduler.blackouts.push(BlackoutWindow::Period {
+            name: Some("Crisis freeze".to_string()),
+            from: format(now - ChronoDuration::hours(1)),
+            until: until.clone(),
+        });
+        let until = local_to_utc(&until, "%Y-%m-%d %H:%M", settings.timezone()).unwrap();
+
+        for policy in [BlackoutPolicy::Defer, BlackoutPolicy::Hold] {
+            let posts = InMemoryPostRepository::new();
+            let pauses = InMemoryPauseRepository::new();
+            let scheduled_time = BsonDateTime::from_chrono(now - ChronoDuration::minutes(1));
+            let id = posts
+                .insert(Post::new("Due".to_string(), "Content".to_string(), scheduled_time, Utc::now()))
+                .await
+                .unwrap();
+
+            settings.scheduler.blackout_policy = policy;
+            let (shutdown, claimed) = (Shutdown::channel().1, ClaimedPosts::default());
+            let clock = TestClock::new(now);
+            publish_due_posts(&posts, &pauses, &linkedin("http://127.0.0.1:9"), &clock, &settings, &shutdown, &claimed)
+                .await
+                .unwrap();
+
+            let post = posts.find(id).await.unwrap().unwrap();
+            assert_eq!(post.status, PostStatus::Scheduled, "{:?}", policy);
+            match policy {
+                BlackoutPolicy::Defer => {
+                    assert_eq!(post.scheduled_time.to_chrono(), until);
+                    assert!(post.status_history.last().unwrap().reason.as_ref().unwrap().contains("Crisis freeze"));
+                }
+                BlackoutPolicy::Hold => {
+                    assert_eq!(post.scheduled_time, scheduled_time);
+                    assert!(post.status_history.is_empty());
+                }
+            }
+        }
+    }
+
+    /// Tests that a defer blackout leaves paused posts untouched.
+    ///
+    /// During the blackout, with company's publishing paused, alice's post is deferred to the
+    /// end of the blackout while company's post keeps its time and history.
+    #[tokio::test]
+    async fn test_publish_due_posts_during_blackout_respects_pauses() {
+        initialize();
+
+        let now = Utc::now();
+        let format = |time: DateTime<Utc>| time.format("%Y-%m-%d %H:%M").to_string();
+        let mut settings = Settings::default();
+        settings.scheduler.blackout_policy = BlackoutPolicy::Defer;
+        settings.scheduler.blackouts.push(BlackoutWindow::Period {
+            name: Some("Crisis freeze".to_string()),
+            from: format(now - ChronoDuration::hours(1)),
+            until: format(now + ChronoDuration::hours(1)),
+        });
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let scheduled_time = BsonDateTime::from_chrono(now - ChronoDuration::minutes(1));
+        let mut ids = Vec::new();
+        for author in ["alice", "company"] {
+            let mut post = Post::new(author.to_string(), "Content".to_string(), scheduled_time, now);
+            post.author = Some(author.to_string());
+            ids.push(posts.insert(post).await.unwrap());
+        }
+        pauses.put(Pause::new(Some("company"), "Rebranding", "alice")).await.unwrap();
+
+        let (shutdown, claimed) = (Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &linkedin("http://127.0.0.1:9"), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let deferred = posts.find(ids[0]).await.unwrap().unwrap();
+        assert!(deferred.scheduled_time > scheduled_time);
+        assert_eq!(deferred.status_history.len(), 1);
+        let paused = posts.find(ids[1]).await.unwrap().unwrap();
+        assert_eq!(paused.scheduled_time, scheduled_time);
+        assert!(paused.status_history.is_empty());
+    }
+
+    /// Tests that paused posts are left untouched while the other accounts keep publishing.
+    ///
+    /// With company's publishing paused, only alice's post is published, and not dave's post,
+    /// which has a target on the paused brand account; once the global pause is added too,
+    /// nothing is.
+    #[tokio::test]
+    async fn test_publish_due_posts_respects_pauses() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let ugc_posts = server
+            .mock("POST", "/v2/ugcPosts")
+            .with_status(201)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for author in ["alice", "company", "bob"] {
+            let mut post = Post::new(author.to_string(), "Content".to_string(), BsonDateTime::from_chrono(now), now);
+            post.author = Some(author.to_string());
+            ids.push(posts.insert(post).await.unwrap());
+        }
+        let mut post = Post::new("dave".to_string(), "Content".to_string(), BsonDateTime::from_chrono(now), Utc::now());
+        post.author = Some("dave".to_string());
+        let mut brand = Target::new(DEFAULT_CHANNEL);
+        brand.author = Some("brand".to_string());
+        post.targets = vec![Target::new(DEFAULT_CHANNEL), brand];
+        ids.push(posts.insert(post).await.unwrap());
+        pauses.put(Pause::new(Some("company"), "Rebranding", "alice")).await.unwrap();
+        pauses.put(Pause::new(Some("bob"), "On leave", "bob")).await.unwrap();
+        pauses.put(Pause::new(Some("brand"), "Legal review", "alice")).await.unwrap();
+
+        let (settings, shutdown, claimed) = (Settings::default(), Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let mut statuses = Vec::new();
+        for id in &ids {
+            statuses.push(posts.find(*id).await.unwrap().unwrap().status);
+        }
+        let held = vec![PostStatus::Scheduled; 3];
+        assert_eq!(statuses, [vec![PostStatus::Published], held].concat());
+        let untouched = posts.find(ids[1]).await.unwrap().unwrap();
+        assert!(untouched.status_history.is_empty());
+
+        pauses.put(Pause::new(None, "Incident", "alice")).await.unwrap();
+        pauses.remove(&Pause::scope(Some("bob"))).await.unwrap();
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+        assert_eq!(posts.find(ids[2]).await.unwrap().unwrap().status, PostStatus::Scheduled);
+
+        ugc_posts.assert_async().await;
+    }
+
+    /// Tests that a dry run publishes nothing and reports each due post once.
+    ///
+    /// alice's post would be published and bob's is paused: neither is sent to LinkedIn nor
+    /// changes status, both get a `dry_run_at` marker, and a second iteration leaves them alone
+    /// until one of them is rescheduled.
+    #[tokio::test]
+    async fn test_dry_run_publishes_nothing() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let ugc_posts = server.mock("POST", "/v2/ugcPosts").expect(0).create_async().await;
+
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for author in ["alice", "bob"] {
+            let scheduled_time = BsonDateTime::from_chrono(now - ChronoDuration::minutes(1));
+            let mut post = Post::new(author.to_string(), "Content".to_string(), scheduled_time, Utc::now());
+            post.author = Some(author.to_string());
+            ids.push(posts.insert(post).await.unwrap());
+        }
+        pauses.put(Pause::new(Some("bob"), "On leave", "bob")).await.unwrap();
+
+        let mut settings = Settings::default();
+        settings.scheduler.dry_run = true;
+        let (shutdown, claimed) = (Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        for id in &ids {
+            let post = posts.find(*id).await.unwrap().unwrap();
+            assert_eq!(post.status, PostStatus::Scheduled);
+            assert!(post.status_history.is_empty());
+            assert_eq!(post.dry_run_at.map(|at| at.to_chrono().timestamp()), Some(now.timestamp()));
+            assert!(post.is_dry_run());
+        }
+
+        let mut rescheduled = posts.find(ids[0]).await.unwrap().unwrap();
+        rescheduled.scheduled_time = BsonDateTime::from_chrono(now + ChronoDuration::minutes(1));
+        posts.update(&rescheduled, PostStatus::Scheduled).await.unwrap();
+        let later = now + ChronoDuration::minutes(2);
+        let clock = TestClock::new(later);
+        publish_due_posts(&posts, &pauses, &linkedin(&server.url()), &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+        let mut reported_at = Vec::new();
+        for id in &ids {
+            reported_at.push(posts.find(*id).await.unwrap().unwrap().dry_run_at.unwrap().to_chrono().timestamp());
+        }
+        assert_eq!(reported_at, vec![later.timestamp(), now.timestamp()]);
+
+        ugc_posts.assert_async().await;
+    }
+
+    /// Tests that `--dry-run` turns the dry-run mode on whatever the settings say.
+    #[test]
+    fn test_with_dry_run_flag() {
+        let settings = Arc::new(Settings::default());
+        assert!(Arc::ptr_eq(&with_dry_run_flag(Arc::clone(&settings), false), &settings));
+        assert!(with_dry_run_flag(Arc::clone(&settings), true).scheduler.dry_run);
+
+        let mut dry_run = Settings::default();
+        dry_run.scheduler.dry_run = true;
+        assert!(with_dry_run_flag(Arc::new(dry_run), false).scheduler.dry_run);
+    }
+
+    /// Publisher recording the contents it publishes, standing for another network.
+    #[derive(Default)]
+    struct RecordingPublisher(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl Publisher for RecordingPublisher {
+        async fn publish(&self, publication: &Publication<'_>) -> Result<Option<String>, PublishError> {
+            let mut published = self.0.lock().unwrap();
+            published.push(publication.content.to_string());
+            Ok(Some(format!("recorded-{}", published.len())))
+        }
+
+        async fn dry_run(&self, publication: &Publication<'_>) -> Result<String, PublishError> {
+            Ok(format!("record {}", publication.content))
+        }
+
+        async fn delete(&self, _remote_id: &str) -> Result<(), PublishError> {
+            Ok(())
+        }
+
+        async fn fetch_metrics(&self, _remote_id: &str) -> Result<PostMetrics, PublishError> {
+            Ok(PostMetrics::default())
+        }
+    }
+
+    /// A publisher whose publications never complete, like a receiver that never answers.
+    struct HangingPublisher;
+
+    #[async_trait]
+    impl Publisher for HangingPublisher {
+        async fn publish(&self, _publication: &Publication<'_>) -> Result<Option<String>, PublishError> {
+            future::pending().await
+        }
+
+        async fn dry_run(&self, publication: &Publication<'_>) -> Result<String, PublishError> {
+            Ok(format!("hang on {}", publication.content))
+        }
+
+        async fn delete(&self, _remote_id: &str) -> Result<(), PublishError> {
+            Ok(())
+        }
+
+        async fn fetch_metrics(&self, _remote_id: &str) -> Result<PostMetrics, PublishError> {
+            Ok(PostMetrics::default())
+        }
+    }
+
+    /// Tests that the targets a post was published on before it was released by a shutdown
+    /// are not published again.
+    ///
+    /// The post targets LinkedIn, which answers, and a CRM that never does. When the shutdown
+    /// deadline releases the post, its LinkedIn target is already recorded as published, so
+    /// publishing the post again only sends it to the CRM.
+    #[tokio::test(start_paused = true)]
+    async fn test_released_post_keeps_published_targets() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let now = Utc::now();
+        let mut post = Post::new("Hiring".to_string(), "I am hiring!".to_string(), BsonDateTime::from_chrono(now), now);
+        post.targets = vec![Target::new(DEFAULT_CHANNEL), Target::new("crm")];
+        let id = posts.insert(post).await.unwrap();
+
+        let linkedin = Arc::new(RecordingPublisher::default());
+        let mut publishers = Publishers::new();
+        publishers.insert(DEFAULT_CHANNEL.to_string(), linkedin.clone());
+        publishers.insert("crm".to_string(), Arc::new(HangingPublisher));
+        let (settings, shutdown, claimed) = (Settings::default(), Shutdown::channel().1, ClaimedPosts::default());
+        let clock = TestClock::new(now);
+        let publishing = publish_due_posts(&posts, &pauses, &publishers, &clock, &settings, &shutdown, &claimed);
+        assert!(time::timeout(Duration::from_secs(30), publishing).await.is_err());
+        release_claimed_posts(&posts, &claimed, clock.now()).await;
+
+        let released = posts.find(id).await.unwrap().unwrap();
+        assert_eq!(released.status, PostStatus::Scheduled);
+        assert_eq!(released.targets[0].status, TargetStatus::Published);
+        assert_eq!(released.targets[0].remote_id.as_deref(), Some("recorded-1"));
+        assert_eq!(released.targets[1].status, TargetStatus::Pending);
+
+        let crm = Arc::new(RecordingPublisher::default());
+        publishers.insert("crm".to_string(), crm.clone());
+        publish_due_posts(&posts, &pauses, &publishers, &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        assert_eq!(posts.find(id).await.unwrap().unwrap().status, PostStatus::Published);
+        assert_eq!(linkedin.0.lock().unwrap().len(), 1);
+        assert_eq!(crm.0.lock().unwrap().len(), 1);
+    }
+
+    /// Tests that a post is published on each of its targets, with their own text and account.
+    ///
+    /// The post targets alice's and the company's LinkedIn profiles, the latter with its own
+    /// text and hashtags, and a channel without a publisher: it is partially published. Once the
+    /// channel is available and the post scheduled again, it is only published on that channel.
+    #[tokio::test]
+    async fn test_publish_post_to_several_targets() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let personal = server
+            .mock("POST", "/v2/ugcPosts")
+            .match_body(Matcher::PartialJson(serde_json::json!({ "author": "urn:li:person:alice" })))
+            .with_status(201)
+            .with_header("x-restli-id", "urn:li:share:1")
+            .expect(1)
+            .create_async()
+            .await;
+        let company_body = serde_json::json!({
+            "author": "urn:li:person:company",
+            "specificContent": {
+                "com.linkedin.ugc.ShareContent": { "shareCommentary": { "text": "We are hiring!\n\n#jobs #rust" } }
+            }
+        });
+        let company = server
+            .mock("POST", "/v2/ugcPosts")
+            .match_body(Matcher::PartialJson(company_body))
+            .with_status(201)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let now = Utc::now();
+        let mut post = Post::new("Hiring".to_string(), "I am hiring!".to_string(), BsonDateTime::from_chrono(now), now);
+        post.author = Some("alice".to_string());
+        let mut company_target = Target::new("linkedin");
+        company_target.author = Some("company".to_string());
+        company_target.content = Some("We are hiring!".to_string());
+        company_target.hashtags = vec!["jobs".to_string(), "rust".to_string()];
+        post.targets = vec![Target::new("linkedin"), company_target, Target::new("mastodon")];
+        let id = posts.insert(post).await.unwrap();
+
+        let (settings, shutdown, claimed) = (Settings::default(), Shutdown::channel().1, ClaimedPosts::default());
+        let mut publishers = linkedin(&server.url());
+        let clock = TestClock::new(now);
+        publish_due_posts(&posts, &pauses, &publishers, &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let partial = posts.find(id).await.unwrap().unwrap();
+        assert_eq!(partial.status, PostStatus::PartiallyPublished);
+        assert!(partial.published_at.is_some());
+        let reason = partial.status_history.last().unwrap().reason.as_deref();
+        assert_eq!(reason, Some("mastodon: Unknown channel mastodon"));
+        let statuses: Vec<_> = partial.targets.iter().map(|target| target.status).collect();
+        assert_eq!(statuses, vec![TargetStatus::Published, TargetStatus::Published, TargetStatus::Failed]);
+        assert_eq!(partial.targets[0].remote_id.as_deref(), Some("urn:li:share:1"));
+
+        let mastodon = Arc::new(RecordingPublisher::default());
+        publishers.insert("mastodon".to_string(), mastodon.clone());
+        let retry = StatusTransition::new(Some(PostStatus::PartiallyPublished), PostStatus::Scheduled, "alice", now);
+        posts.transition(id, retry).await.unwrap();
+        publish_due_posts(&posts, &pauses, &publishers, &clock, &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let published = posts.find(id).await.unwrap().unwrap();
+        assert_eq!(published.status, PostStatus::Published);
+        assert!(published.targets.iter().all(|target| target.status == TargetStatus::Published));
+        assert_eq!(published.targets[2].remote_id.as_deref(), Some("recorded-1"));
+        assert_eq!(*mastodon.0.lock().unwrap(), vec!["I am hiring!".to_string()]);
+
+        personal.assert_async().await;
+        company.assert_async().await;
+    }
+
+    /// Tests that a post targeting a webhook channel goes through the scheduling loop like
+    /// a LinkedIn post, signed with the channel's secret at the time of the scheduler's clock.
+    #[tokio::test]
+    async fn test_publish_due_posts_to_webhook() {
+        initialize();
+
+        let posts = InMemoryPostRepository::new();
+        let pauses = InMemoryPauseRepository::new();
+        let mut server = Server::new_async().await;
+        let hook = server
+            .mock("POST", "/hooks")
+            .match_header(SIGNATURE_HEADER, Matcher::Regex("^sha256=[0-9a-f]{64}$".to_string()))
+            .match_header(TIMESTAMP_HEADER, Matcher::Regex(format!("^{}[0-9]$", nine().timestamp() / 10)))
+            .match_body(Matcher::PartialJson(serde_json::json!({ "event": "post.published", "title": "Webhook" })))
+            .with_status(202)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let now = nine();
+        let mut post = Post::new("Webhook".to_string(), "Content".to_string(), BsonDateTime::from_chrono(now), now);
+        post.targets = vec![Target::new("crm")];
+        let id = posts.insert(post).await.unwrap();
+
+        let mut settings = Settings::default();
+        let url = format!("{}/hooks", server.url());
+        let secret = Secret::new("shared-secret");
+        let channel = ChannelSettings::Webhook { url, secret, timeout_secs: DEFAULT_TIMEOUT_SECS };
+        settings.channels.insert("crm".to_string(), channel);
+        let clock = Arc::new(TestClock::new(now));
+        let publishers = publishers_for(&settings, &Secret::new("mock-token"), clock.clone());
+        let (shutdown, claimed) = (Shutdown::channel().1, ClaimedPosts::default());
+        publish_due_posts(&posts, &pauses, &publishers, clock.as_ref(), &settings, &shutdown, &claimed)
+            .await
+            .unwrap();
+
+        let published = posts.find(id).await.unwrap().unwrap();
+        assert_eq!(published.status, PostStatus::Published);
+        assert_eq!(published.targets[0].status, TargetStatus::Published);
+        hook.assert_async().await;
+    }
+
+    /// Returns a scheduler of `posts` on `clock`, with fixed publishers and settings.
+    async fn scheduler(
+        posts: &Arc<InMemoryPostRepository>,
+        pauses: &Arc<InMemoryPauseRepository>,
+        publishers: Publishers,
+        clock: &Arc<TestClock>,
+        settings: Settings,
+    ) -> Scheduler {
+        let posts: Arc<dyn PostRepository> = posts.clone();
+        let pauses: Arc<dyn PauseRepository> = pauses.clone();
+        let clock: Arc<dyn Clock> = clock.clone();
+        Scheduler::new(posts, pauses, Box::new(publishers), clock, fixed(settings), false).await
+    }
+
+    /// Returns publishers sending every LinkedIn publication to `publisher`.
+    fn recording(publisher: &Arc<RecordingPublisher>) -> Publishers {
+        let mut publishers = Publishers::new();
+        publishers.insert(DEFAULT_CHANNEL.to_string(), publisher.clone() as Arc<dyn Publisher>);
+        publishers
+    }
+
+    /// A day at 09:00 UTC, when the tests driving a `Scheduler` start.
+    fn nine() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap()
+    }
+
+    /// Tests that the scheduler publishes a post exactly once, at its scheduled time.
+    ///
+    /// Two scheduler loops run on a test clock starting at 08:59 UTC, with a post due at 09:00
+    /// and one added at 09:10 for 09:30. Time only moves when the test advances it: nothing is
+    /// published a second early, each post is published when its time comes, and by only one
+    /// of the loops, however long they run afterwards. The times recorded on the posts are those
+    /// of the test clock.
+    #[tokio::test(start_paused = true)]
+    async fn test_publishes_exactly_once_on_time() {
+        initialize();
+
+        let at = |hour, minute, second| Utc.with_ymd_and_hms(2024, 5, 10, hour, minute, second).unwrap();
+        let clock = Arc::new(TestClock::new(at(8, 59, 0)));
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let pauses = Arc::new(InMemoryPauseRepository::new());
+        let recorder = Arc::new(RecordingPublisher::default());
+
+        let post = |title: &str, content: &str, scheduled_time| {
+            Post::new(title.to_string(), content.to_string(), BsonDateTime::from_chrono(scheduled_time), clock.now())
+        };
+        let nine = posts.insert(post("Nine", "At nine", at(9, 0, 0))).await.unwrap();
+        let (stop, shutdown) = Shutdown::channel();
+        let mut schedulers = Vec::new();
+        for _ in 0..2 {
+            let scheduler = scheduler(&posts, &pauses, recording(&recorder), &clock, Settings::default()).await;
+            schedulers.push(tokio::spawn(scheduler.run_until(shutdown.clone())));
+        }
+
+        clock.advance(Duration::from_secs(59)).await;
+        assert!(recorder.0.lock().unwrap().is_empty());
+        assert_eq!(posts.find(nine).await.unwrap().unwrap().status, PostStatus::Scheduled);
+
+        clock.advance(Duration::from_secs(1)).await;
+        assert_eq!(*recorder.0.lock().unwrap(), vec!["At nine"]);
+        let published = posts.find(nine).await.unwrap().unwrap();
+        assert_eq!(published.status, PostStatus::Published);
+        assert_eq!(published.created_at, Some(BsonDateTime::from_chrono(at(8, 59, 0))));
+        assert_eq!(published.published_at, Some(BsonDateTime::from_chrono(at(9, 0, 0))));
+        assert_eq!(published.targets[0].published_at, Some(BsonDateTime::from_chrono(at(9, 0, 0))));
+        let history: Vec<_> =
+            published.status_history.iter().map(|transition| (transition.to, transition.at)).collect();
+        let nine_o_clock = BsonDateTime::from_chrono(at(9, 0, 0));
+        assert_eq!(history, [(PostStatus::Publishing, nine_o_clock), (PostStatus::Published, nine_o_clock)]);
+
+        clock.advance(Duration::from_secs(600)).await;
+        let half_past = posts.insert(post("Half past", "At 09:30", at(9, 30, 0))).await.unwrap();
+        clock.advance(Duration::from_secs(1199)).await;
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+
+        clock.advance(Duration::from_secs(1)).await;
+        assert_eq!(clock.now(), at(9, 30, 0));
+        assert_eq!(*recorder.0.lock().unwrap(), vec!["At nine", "At 09:30"]);
+        let published = posts.find(half_past).await.unwrap().unwrap();
+        assert_eq!(published.status, PostStatus::Published);
+        assert_eq!(published.created_at, Some(BsonDateTime::from_chrono(at(9, 10, 0))));
+        assert_eq!(published.targets[0].published_at, Some(BsonDateTime::from_chrono(at(9, 30, 0))));
+
+        clock.advance(Duration::from_secs(24 * 3600)).await;
+        assert_eq!(recorder.0.lock().unwrap().len(), 2);
+
+        stop.send_replace(true);
+        for scheduler in schedulers {
+            scheduler.await.unwrap().unwrap();
+        }
+    }
+
+    /// Tests that `run_once` publishes the due scheduled posts only, and waits for the next one.
+    ///
+    /// At 09:00, a post due at 08:59 is published, while a draft due at 08:00 and a post due at
+    /// 09:10 are not; the scheduler then waits exactly 10 minutes, and publishes the latter.
+    #[tokio::test(start_paused = true)]
+    async fn test_run_once_publishes_due_posts() {
+        initialize();
+
+        let clock = Arc::new(TestClock::new(nine()));
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let pauses = Arc::new(InMemoryPauseRepository::new());
+        let recorder = Arc::new(RecordingPublisher::default());
+        let at = |minutes| BsonDateTime::from_chrono(nine() + ChronoDuration::minutes(minutes));
+        let late = posts.insert(Post::new("Late".to_string(), "Late".to_string(), at(-1), Utc::now())).await.unwrap();
+        let mut draft = Post::new("Draft".to_string(), "Draft".to_string(), at(-60), Utc::now());
+        draft.status = PostStatus::Draft;
+        let draft = posts.insert(draft).await.unwrap();
+        let later = posts.insert(Post::new("Later".to_string(), "Later".to_string(), at(10), nine())).await.unwrap();
+
+        let mut settings = Settings::default();
+        settings.scheduler.poll_interval_secs = 3600;
+        let scheduler = scheduler(&posts, &pauses, recording(&recorder), &clock, settings).await;
+        let shutdown = Shutdown::channel().1;
+
+        let wait = scheduler.run_once(&shutdown).await.unwrap();
+        assert_eq!(wait, Duration::from_secs(600));
+        assert_eq!(*recorder.0.lock().unwrap(), vec!["Late"]);
+        assert_eq!(posts.find(late).await.unwrap().unwrap().status, PostStatus::Published);
+        assert_eq!(posts.find(draft).await.unwrap().unwrap().status, PostStatus::Draft);
+        assert_eq!(posts.find(later).await.unwrap().unwrap().status, PostStatus::Scheduled);
+
+        clock.advance(wait).await;
+        let wait = scheduler.run_once(&shutdown).await.unwrap();
+        assert_eq!(wait, Duration::from_secs(3600));
+        assert_eq!(*recorder.0.lock().unwrap(), vec!["Late", "Later"]);
+    }
+
+    /// Tests how long `run_once` waits before the next run.
+    ///
+    /// It waits until the next post, at most the poll interval, and not at all when a post is
+    /// due, except for due posts that cannot be published yet: paused posts are checked again
+    /// after the poll interval, and posts held by a blackout when it ends.
+    #[tokio::test(start_paused = true)]
+    async fn test_run_once_delays() {
+        initialize();
+
+        let clock = Arc::new(TestClock::new(nine()));
+        let recorder = Arc::new(RecordingPublisher::default());
+        let shutdown = Shutdown::channel().1;
+        let poll_interval = Duration::from_secs(20);
+        let at = |minutes| BsonDateTime::from_chrono(nine() + ChronoDuration::minutes(minutes));
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let pauses = Arc::new(InMemoryPauseRepository::new());
+        let idle = scheduler(&posts, &pauses, recording(&recorder), &clock, Settings::default()).await;
+        assert_eq!(idle.run_once(&shutdown).await.unwrap(), poll_interval);
+        posts.insert(Post::new("Soon".to_string(), "Soon".to_string(), at(0), Utc::now())).await.unwrap();
+        let in_five_seconds = BsonDateTime::from_chrono(nine() + ChronoDuration::seconds(5));
+        posts.insert(Post::new("Later".to_string(), "Later".to_string(), in_five_seconds, Utc::now())).await.unwrap();
+        assert_eq!(idle.run_once(&shutdown).await.unwrap(), Duration::from_secs(5));
+
+        pauses.put(Pause::new(None, "Incident", "alice")).await.unwrap();
+        posts.insert(Post::new("Paused".to_string(), "Paused".to_string(), at(-1), Utc::now())).await.unwrap();
+        assert_eq!(idle.run_once(&shutdown).await.unwrap(), poll_interval);
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let pauses = Arc::new(InMemoryPauseRepository::new());
+        posts.insert(Post::new("Held".to_string(), "Held".to_string(), at(-1), Utc::now())).await.unwrap();
+        let mut settings = Settings::default();
+        settings.scheduler.poll_interval_secs = 3600;
+        settings.scheduler.blackout_policy = BlackoutPolicy::Hold;
+        settings.scheduler.blackouts.push(BlackoutWindow::Period {
+            name: None,
+            from: "2024-05-10 08:00".to_string(),
+            until: "2024-05-10 09:30".to_string(),
+        });
+        let held = scheduler(&posts, &pauses, recording(&recorder), &clock, settings).await;
+        assert_eq!(held.run_once(&shutdown).await.unwrap(), Duration::from_secs(1800));
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+    }
+
+    /// Publisher failing every publication, standing for an unavailable network.
+    struct FailingPublisher;
+
+    #[async_trait]
+    impl Publisher for FailingPublisher {
+        async fn publish(&self, _publication: &Publication<'_>) -> Result<Option<String>, PublishError> {
+            Err("Service unavailable".into())
+        }
+
+        async fn dry_run(&self, _publication: &Publication<'_>) -> Result<String, PublishError> {
+            Err("Service unavailable".into())
+        }
+
+        async fn delete(&self, _remote_id: &str) -> Result<(), PublishError> {
+            Err("Service unavailable".into())
+        }
+
+        async fn fetch_metrics(&self, _remote_id: &str) -> Result<PostMetrics, PublishError> {
+            Err("Service unavailable".into())
+        }
+    }
+
+    /// Repository failing every operation, standing for an unreachable database.
+    struct UnavailableRepository;
+
+    #[async_trait]
+    impl PostRepository for UnavailableRepository {
+        async fn insert(&self, _post: Post) -> Result<ObjectId, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+
+        async fn find(&self, _id: ObjectId) -> Result<Option<Post>, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+
+        async fn find_due(&self, _now: DateTime<Utc>) -> Result<Vec<Post>, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+
+        async fn next_scheduled_time(&self) -> Result<Option<DateTime<Utc>>, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+
+        async fn find_in_range(
+            &self,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+            _status: PostStatus,
+        ) -> Result<Vec<Post>, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+
+        async fn find_published_in_range(
+            &self,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+            _status: PostStatus,
+        ) -> Result<Vec<Post>, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+
+        async fn update(&self, _post: &Post, _expected_status: PostStatus) -> Result<bool, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+
+        async fn transition(&self, _id: ObjectId, _transition: StatusTransition) -> Result<bool, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+
+        async fn delete(&self, _id: ObjectId) -> Result<bool, RepositoryError> {
+            Err("Database unavailable".into())
+        }
+    }
+
+    /// Tests the error paths of the scheduler.
+    ///
+    /// A publication error marks the post as failed and the scheduler goes on, while a
+    /// repository error is returned by `run_once`, and stops `run_until`.
+    #[tokio::test(start_paused = true)]
+    async fn test_run_once_errors() {
+        initialize();
+
+        let clock = Arc::new(TestClock::new(nine()));
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let pauses = Arc::new(InMemoryPauseRepository::new());
+        let scheduled_time = BsonDateTime::from_chrono(nine());
+        let id = posts.insert(Post::new("Due".to_string(), "Due".to_string(), scheduled_time, nine())).await.unwrap();
+        let mut publishers = Publishers::new();
+        publishers.insert(DEFAULT_CHANNEL.to_string(), Arc::new(FailingPublisher) as Arc<dyn Publisher>);
+        let failing = scheduler(&posts, &pauses, publishers, &clock, Settings::default()).await;
+        let shutdown = Shutdown::channel().1;
+
+        assert_eq!(failing.run_once(&shutdown).await.unwrap(), Duration::from_secs(20));
+        let post = posts.find(id).await.unwrap().unwrap();
+        assert_eq!(post.status, PostStatus::Failed);
+        assert!(post.status_history.last().unwrap().reason.as_ref().unwrap().contains("Service unavailable"));
+
+        let unavailable = Scheduler::new(
+            Arc::new(UnavailableRepository),
+            pauses,
+            Box::new(Publishers::new()),
+            clock,
+            fixed(Settings::default()),
+            false,
+        )
+        .await;
+        let error = unavailable.run_once(&shutdown).await.unwrap_err();
+        assert!(error.to_string().contains("Database unavailable"));
+        assert!(unavailable.run_until(shutdown).await.is_err());
     }
 }
@@ -0,0 +1,336 @@
+use crate::config::settings::local_to_utc;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Format of the `start` and `end` times of quiet hours.
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Format of the `from` and `until` date/times of blackout periods, the same as the
+/// `scheduled_time` of `POST /schedule`.
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Upper bound on the windows chained by `next_allowed`, e.g. quiet hours running into a
+/// holiday period running into a weekend.
+const MAX_CHAINED_WINDOWS: usize = 100;
+
+/// A period during which nothing may be published.
+///
+/// Times are local times in the configured `timezone_offset`. Blackouts are written as an
+/// array of tables tagged by `kind`:
+///
+/// ```toml
+/// # Every night
+/// [[scheduler.blackouts]]
+/// kind = "quiet_hours"
+/// start = "22:00"
+/// end = "07:00"
+///
+/// # Weekends: a window starting and ending at the same time lasts 24 hours
+/// [[scheduler.blackouts]]
+/// kind = "quiet_hours"
+/// start = "00:00"
+/// end = "00:00"
+/// days = ["sat", "sun"]
+///
+/// # A holiday or an ad-hoc freeze
+/// [[scheduler.blackouts]]
+/// kind = "period"
+/// name = "Year-end freeze"
+/// from = "2024-12-24 00:00"
+/// until = "2025-01-02 00:00"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BlackoutWindow {
+    /// A daily window from `start` to `end` ("HH:MM"), ending the next day if `end` is not
+    /// after `start`.
+    QuietHours {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        start: String,
+        end: String,
+        /// Days the window starts on ("mon" to "sun"); every day when empty.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        days: Vec<String>,
+    },
+    /// A one-off window from `from` to `until` ("YYYY-MM-DD HH:MM").
+    Period {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        from: String,
+        until: String,
+    },
+}
+
+/// What the scheduler does with posts that are due during a blackout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlackoutPolicy {
+    /// Reschedule the posts to the end of the blackout, recording it in their status history.
+    #[default]
+    Defer,
+    /// Leave the posts untouched and publish nothing until the blackout ends. The posts are
+    /// then late and handled by their catch-up policy.
+    Hold,
+}
+
+/// The blackout a given instant falls in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blackout {
+    /// Description of the window, e.g. for logs and warnings.
+    pub window: String,
+    /// When publishing is allowed again, after any window chained to this one.
+    pub until: DateTime<Utc>,
+}
+
+impl fmt::Display for BlackoutWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlackoutWindow::QuietHours { name: Some(name), .. } | BlackoutWindow::Period { name: Some(name), .. } => {
+                write!(f, "{}", name)
+            }
+            BlackoutWindow::QuietHours { start, end, days, .. } if days.is_empty() => {
+                write!(f, "quiet hours {}-{}", start, end)
+            }
+            BlackoutWindow::QuietHours { start, end, days, .. } => {
+                write!(f, "quiet hours {}-{} on {}", start, end, days.join(", "))
+            }
+            BlackoutWindow::Period { from, until, .. } => write!(f, "blackout {} to {}", from, until),
+        }
+    }
+}
+
+impl BlackoutWindow {
+    /// Checks the times and days of the window, reporting errors under the settings path `key`.
+    pub fn validate(&self, key: &str, errors: &mut Vec<String>) {
+        match self {
+            BlackoutWindow::QuietHours { start, end, days, .. } => {
+                for (name, value) in [("start", start), ("end", end)] {
+                    if NaiveTime::parse_from_str(value, TIME_FORMAT).is_err() {
+                        errors.push(format!("{}.{}: {:?} is not a HH:MM time", key, name, value));
+                    }
+                }
+                for day in days {
+                    if day.parse::<Weekday>().is_err() {
+                        errors.push(format!("{}.days: {:?} is not a day of the week", key, day));
+                    }
+                }
+            }
+            BlackoutWindow::Period { from, until, .. } => {
+                let offset = FixedOffset::east_opt(0).unwrap();
+                match (
+                    local_to_utc(from, DATE_TIME_FORMAT, offset),
+                    local_to_utc(until, DATE_TIME_FORMAT, offset),
+                ) {
+                    (Ok(from), Ok(until)) if from >= until => {
+                        errors.push(format!("{}.until: must be after from", key));
+                    }
+                    (Ok(_), Ok(_)) => {}
+                    _ => errors.push(format!("{}: from and until must be YYYY-MM-DD HH:MM date/times", key)),
+                }
+            }
+        }
+    }
+
+    /// Returns the end of the window if `at` falls in it, counting local times in `offset`.
+    ///
+    /// Invalid windows, rejected by `validate` when the settings are loaded, never match.
+    pub fn end_if_contains(&self, at: DateTime<Utc>, offset: FixedOffset) -> Option<DateTime<Utc>> {
+        match self {
+            BlackoutWindow::QuietHours { start, end, days, .. } => {
+                let start = NaiveTime::parse_from_str(start, TIME_FORMAT).ok()?;
+                let end = NaiveTime::parse_from_str(end, TIME_FORMAT).ok()?;
+                let starts_on = |date: NaiveDate| {
+                    days.is_empty() || days.iter().any(|day| day.parse::<Weekday>() == Ok(date.weekday()))
+                };
+
+                let local = at.with_timezone(&offset);
+                let (date, time) = (local.date_naive(), local.time());
+                let end_on = |date: NaiveDate| offset.from_local_datetime(&date.and_time(end)).single();
+                if start < end {
+                    (starts_on(date) && start <= time && time < end).then(|| end_on(date)).flatten()
+                } else if time >= start && starts_on(date) {
+                    end_on(date.succ_opt()?)
+                } else if time < end && starts_on(date.pred_opt()?) {
+                    end_on(date)
+                } else {
+                    None
+                }
+                .map(|end| end.with_timezone(&Utc))
+            }
+            BlackoutWindow::Period { from, until, .. } => {
+                let from = local_to_utc(from, DATE_TIME_FORMAT, offset).ok()?;
+                let until = local_to_utc(until, DATE_TIME_FORMAT, offset).ok()?;
+                (from <= at && at < until).then_some(until)
+            }
+        }
+    }
+}
+
+/// Returns the blackout `at` falls in, if any.
+///
+/// # Functionality
+///
+/// The first window containing `at` gives the description. Its end is then followed through
+/// the windows it runs into, so `until` is the first instant no window contains.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// if let Some(blackout) = blackout_at(&settings.scheduler.blackouts, Utc::now(), settings.timezone()) {
+///     info!("Not publishing during {} until {}", blackout.window, blackout.until);
+/// }
+/// ```
+pub fn blackout_at(windows: &[BlackoutWindow], at: DateTime<Utc>, offset: FixedOffset) -> Option<Blackout> {
+    let (window, mut until) = windows
+        .iter()
+        .find_map(|window| window.end_if_contains(at, offset).map(|end| (window, end)))?;
+
+    for _ in 0..MAX_CHAINED_WINDOWS {
+        match windows.iter().find_map(|window| window.end_if_contains(until, offset)) {
+            Some(end) if end > until => until = end,
+            _ => break,
+        }
+    }
+
+    Some(Blackout {
+        window: window.to_string(),
+        until,
+    })
+}
+
+/// Returns the earliest time at or after `at` outside every blackout window.
+pub fn next_allowed(windows: &[BlackoutWindow], at: DateTime<Utc>, offset: FixedOffset) -> DateTime<Utc> {
+    blackout_at(windows, at, offset).map_or(at, |blackout| blackout.until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn brasilia() -> FixedOffset {
+        FixedOffset::west_opt(3 * 3600).unwrap()
+    }
+
+    /// Friday 10 May 2024 at the given time in Brasília (UTC-3).
+    fn friday(hour: u32, minute: u32) -> DateTime<Utc> {
+        brasilia()
+            .with_ymd_and_hms(2024, 5, 10, hour, minute, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn nights() -> BlackoutWindow {
+        BlackoutWindow::QuietHours {
+            name: None,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            days: Vec::new(),
+        }
+    }
+
+    fn weekends() -> BlackoutWindow {
+        BlackoutWindow::QuietHours {
+            name: Some("Weekend".to_string()),
+            start: "00:00".to_string(),
+            end: "00:00".to_string(),
+            days: vec!["sat".to_string(), "sun".to_string()],
+        }
+    }
+
+    /// Tests overnight quiet hours on both sides of midnight, in local time.
+    #[test]
+    fn test_overnight_quiet_hours() {
+        let windows = [nights()];
+
+        assert_eq!(blackout_at(&windows, friday(21, 59), brasilia()), None);
+        assert_eq!(
+            blackout_at(&windows, friday(23, 0), brasilia()),
+            Some(Blackout {
+                window: "quiet hours 22:00-07:00".to_string(),
+                until: friday(7, 0) + Duration::days(1),
+            })
+        );
+        assert_eq!(next_allowed(&windows, friday(6, 30), brasilia()), friday(7, 0));
+        assert_eq!(next_allowed(&windows, friday(7, 0), brasilia()), friday(7, 0));
+    }
+
+    /// Tests that a window ending in another one is followed to the end of both.
+    ///
+    /// Friday night runs into the weekend, which runs into Sunday night: publishing resumes
+    /// on Monday at 07:00.
+    #[test]
+    fn test_chained_windows() {
+        let windows = [nights(), weekends()];
+
+        let blackout = blackout_at(&windows, friday(23, 0), brasilia()).unwrap();
+        assert_eq!(blackout.until, friday(7, 0) + Duration::days(3));
+        assert_eq!(
+            blackout_at(&windows, friday(12, 0) + Duration::days(1), brasilia()).unwrap().window,
+            "Weekend"
+        );
+    }
+
+    /// Tests one-off periods, including their bounds.
+    #[test]
+    fn test_period() {
+        let windows = [BlackoutWindow::Period {
+            name: None,
+            from: "2024-05-10 12:00".to_string(),
+            until: "2024-05-10 18:00".to_string(),
+        }];
+
+        assert_eq!(next_allowed(&windows, friday(11, 59), brasilia()), friday(11, 59));
+        assert_eq!(next_allowed(&windows, friday(12, 0), brasilia()), friday(18, 0));
+        assert_eq!(next_allowed(&windows, friday(18, 0), brasilia()), friday(18, 0));
+    }
+
+    /// Tests that invalid times, days and periods are reported.
+    #[test]
+    fn test_validate() {
+        let mut errors = Vec::new();
+        BlackoutWindow::QuietHours {
+            name: None,
+            start: "25:00".to_string(),
+            end: "07:00".to_string(),
+            days: vec!["someday".to_string()],
+        }
+        .validate("scheduler.blackouts[0]", &mut errors);
+        BlackoutWindow::Period {
+            name: None,
+            from: "2024-05-10 18:00".to_string(),
+            until: "2024-05-10 12:00".to_string(),
+        }
+        .validate("scheduler.blackouts[1]", &mut errors);
+
+        assert_eq!(
+            errors,
+            vec![
+                "scheduler.blackouts[0].start: \"25:00\" is not a HH:MM time",
+                "scheduler.blackouts[0].days: \"someday\" is not a day of the week",
+                "scheduler.blackouts[1].until: must be after from",
+            ]
+        );
+    }
+
+    /// Tests the TOML representation of the windows, tagged by `kind`.
+    #[test]
+    fn test_window_toml() {
+        let window: BlackoutWindow =
+            toml::from_str("kind = \"quiet_hours\"\nstart = \"00:00\"\nend = \"00:00\"\ndays = [\"sat\", \"sun\"]")
+                .unwrap();
+        assert_eq!(
+            window,
+            BlackoutWindow::QuietHours {
+                name: None,
+                start: "00:00".to_string(),
+                end: "00:00".to_string(),
+                days: vec!["sat".to_string(), "sun".to_string()],
+            }
+        );
+
+        assert!(toml::from_str::<BlackoutWindow>("kind = \"period\"\nfrom = \"2024-05-10 12:00\"").is_err());
+    }
+}
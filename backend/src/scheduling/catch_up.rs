@@ -0,0 +1,318 @@
+use crate::config::settings::SchedulerSettings;
+use crate::models::post::Post;
+use chrono::{DateTime, Duration, Utc};
+use mongodb::bson::DateTime as BsonDateTime;
+use serde::{Deserialize, Serialize};
+
+/// What the scheduler does with a post found late, e.g. after it was down for a while.
+///
+/// A post is late when it is found due more than `scheduler.late_after_secs` after its
+/// `scheduled_time`. The policy comes from the post (`Post::catch_up_policy`), else from
+/// `scheduler.catch_up_accounts` for the post's `author`, else from `scheduler.catch_up`.
+///
+/// It is written as a table tagged by `policy`:
+///
+/// ```toml
+/// [scheduler.catch_up]
+/// policy = "spread"
+/// interval_minutes = 30
+///
+/// [scheduler.catch_up_accounts."company-page-id"]
+/// policy = "mark_missed"
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CatchUpPolicy {
+    /// Publish every late post right away.
+    #[default]
+    PublishAll,
+    /// Publish late posts that are at most `max_delay_minutes` late; mark the others missed.
+    PublishIfWithin { max_delay_minutes: u32 },
+    /// Publish the first late post of an account right away and reschedule the next ones
+    /// `interval_minutes` apart.
+    Spread { interval_minutes: u32 },
+    /// Publish no late post: mark them `missed` for someone to reschedule or cancel.
+    MarkMissed,
+}
+
+impl CatchUpPolicy {
+    /// Returns the snake_case name of the policy, as written in the `policy` key.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CatchUpPolicy::PublishAll => "publish_all",
+            CatchUpPolicy::PublishIfWithin { .. } => "publish_if_within",
+            CatchUpPolicy::Spread { .. } => "spread",
+            CatchUpPolicy::MarkMissed => "mark_missed",
+        }
+    }
+
+    /// Checks the parameters of the policy, reporting errors under the settings path `key`.
+    pub fn validate(&self, key: &str, errors: &mut Vec<String>) {
+        match self {
+            CatchUpPolicy::PublishIfWithin { max_delay_minutes: 0 } => {
+                errors.push(format!("{}.max_delay_minutes: must be greater than 0", key));
+            }
+            CatchUpPolicy::Spread { interval_minutes: 0 } => {
+                errors.push(format!("{}.interval_minutes: must be greater than 0", key));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// What the catch-up policy did with a late post.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUpAction {
+    /// The post was published late.
+    Published,
+    /// The post was moved to `rescheduled_to`.
+    Rescheduled,
+    /// The post was marked `missed` and was not published.
+    Missed,
+}
+
+/// The outcome of the catch-up policy for a late post, recorded on the post.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatchUpOutcome {
+    pub action: CatchUpAction,
+    /// The policy that was applied.
+    pub policy: CatchUpPolicy,
+    /// The `scheduled_time` the post was late for.
+    pub scheduled_time: BsonDateTime,
+    /// How late the post was when the policy was applied, in seconds.
+    pub late_by_secs: i64,
+    /// The new `scheduled_time` of a rescheduled post.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rescheduled_to: Option<BsonDateTime>,
+    pub decided_at: BsonDateTime,
+}
+
+impl CatchUpOutcome {
+    /// Describes the outcome, e.g. as the reason of a status transition.
+    pub fn describe(&self) -> String {
+        let late_by_minutes = self.late_by_secs / 60;
+        match (self.action, self.rescheduled_to) {
+            (CatchUpAction::Rescheduled, Some(to)) => format!(
+                "{} minutes late, rescheduled to {} by the {} catch-up policy",
+                late_by_minutes,
+                to,
+                self.policy.name()
+            ),
+            (CatchUpAction::Missed, _) => format!(
+                "{} minutes late, marked missed by the {} catch-up policy",
+                late_by_minutes,
+                self.policy.name()
+            ),
+            _ => format!(
+                "{} minutes late, published by the {} catch-up policy",
+                late_by_minutes,
+                self.policy.name()
+            ),
+        }
+    }
+}
+
+/// Returns the catch-up policy applying to `post`.
+pub fn policy_for(post: &Post, scheduler: &SchedulerSettings) -> CatchUpPolicy {
+    post.catch_up_policy
+        .or_else(|| {
+            post.author
+                .as_ref()
+                .and_then(|author| scheduler.catch_up_accounts.get(author).copied())
+        })
+        .unwrap_or(scheduler.catch_up)
+}
+
+/// Applies the catch-up policies to the due posts of one account queue.
+///
+/// # Functionality
+///
+/// `queue` holds the due posts of an account queue in chronological order, as published by
+/// the scheduler. Posts that are not late get `None` and are published normally. For each
+/// late post, the outcome of its policy is returned:
+///
+/// - `publish_all`: published.
+/// - `publish_if_within`: published if at most `max_delay_minutes` late, missed otherwise.
+/// - `spread`: the first late `spread` post of the queue is published, the following ones are
+///   rescheduled `interval_minutes` apart from `now`.
+/// - `mark_missed`: missed.
+///
+/// # Parameters
+///
+/// - `queue`: The due posts of one account queue, in chronological order.
+/// - `now`: The current UTC instant.
+/// - `scheduler`: The `[scheduler]` settings holding the policies and `late_after_secs`.
+///
+/// # Returns
+///
+/// One entry per post of `queue`, in the same order.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// for (post, outcome) in queue.iter().zip(plan_catch_up(&queue, Utc::now(), &settings.scheduler)) {
+///     match outcome.map(|outcome| outcome.action) {
+///         None | Some(CatchUpAction::Published) => publish(post).await,
+///         Some(CatchUpAction::Rescheduled) | Some(CatchUpAction::Missed) => record(post, outcome).await,
+///     }
+/// }
+/// ```
+pub fn plan_catch_up(queue: &[Post], now: DateTime<Utc>, scheduler: &SchedulerSettings) -> Vec<Option<CatchUpOutcome>> {
+    let late_after = Duration::seconds(scheduler.late_after_secs as i64);
+    let mut spread = 0;
+
+    queue
+        .iter()
+        .map(|post| {
+            let late_by = now - post.scheduled_time.to_chrono();
+            if late_by <= late_after {
+                return None;
+            }
+
+            let policy = policy_for(post, scheduler);
+            let mut rescheduled_to = None;
+            let action = match policy {
+                CatchUpPolicy::PublishAll => CatchUpAction::Published,
+                CatchUpPolicy::PublishIfWithin { max_delay_minutes } => {
+                    if late_by <= Duration::minutes(max_delay_minutes as i64) {
+                        CatchUpAction::Published
+                    } else {
+                        CatchUpAction::Missed
+                    }
+                }
+                CatchUpPolicy::Spread { interval_minutes } => {
+                    let delay = Duration::minutes(interval_minutes as i64 * spread);
+                    spread += 1;
+                    if delay.is_zero() {
+                        CatchUpAction::Published
+                    } else {
+                        rescheduled_to = Some(BsonDateTime::from_chrono(now + delay));
+                        CatchUpAction::Rescheduled
+                    }
+                }
+                CatchUpPolicy::MarkMissed => CatchUpAction::Missed,
+            };
+
+            Some(CatchUpOutcome {
+                action,
+                policy,
+                scheduled_time: post.scheduled_time,
+                late_by_secs: late_by.num_seconds(),
+                rescheduled_to,
+                decided_at: BsonDateTime::from_chrono(now),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap()
+    }
+
+    fn post_late_by(minutes: i64) -> Post {
+        let scheduled_time = BsonDateTime::from_chrono(now() - Duration::minutes(minutes));
+        Post::new(format!("{} minutes late", minutes), "Content".to_string(), scheduled_time, Utc::now())
+    }
+
+    fn actions(queue: &[Post], scheduler: &SchedulerSettings) -> Vec<Option<CatchUpAction>> {
+        plan_catch_up(queue, now(), scheduler)
+            .into_iter()
+            .map(|outcome| outcome.map(|outcome| outcome.action))
+            .collect()
+    }
+
+    fn scheduler(policy: CatchUpPolicy) -> SchedulerSettings {
+        SchedulerSettings {
+            catch_up: policy,
+            ..SchedulerSettings::default()
+        }
+    }
+
+    /// Tests that posts within `late_after_secs` are not late, whatever the policy.
+    #[test]
+    fn test_on_time_posts_are_not_caught_up() {
+        let queue = [post_late_by(0), post_late_by(4)];
+        assert_eq!(actions(&queue, &scheduler(CatchUpPolicy::MarkMissed)), vec![None, None]);
+    }
+
+    /// Tests the outcome of each policy on the same late posts.
+    #[test]
+    fn test_policies() {
+        let queue = [post_late_by(24 * 60), post_late_by(30), post_late_by(2)];
+        use CatchUpAction::*;
+
+        assert_eq!(
+            actions(&queue, &scheduler(CatchUpPolicy::PublishAll)),
+            vec![Some(Published), Some(Published), None]
+        );
+        assert_eq!(
+            actions(&queue, &scheduler(CatchUpPolicy::PublishIfWithin { max_delay_minutes: 60 })),
+            vec![Some(Missed), Some(Published), None]
+        );
+        assert_eq!(
+            actions(&queue, &scheduler(CatchUpPolicy::MarkMissed)),
+            vec![Some(Missed), Some(Missed), None]
+        );
+    }
+
+    /// Tests that `spread` publishes the first late post and spaces out the others.
+    #[test]
+    fn test_spread_reschedules_following_posts() {
+        let queue = [post_late_by(180), post_late_by(120), post_late_by(60)];
+        let outcomes = plan_catch_up(&queue, now(), &scheduler(CatchUpPolicy::Spread { interval_minutes: 30 }));
+
+        let rescheduled: Vec<_> = outcomes
+            .iter()
+            .map(|outcome| {
+                let outcome = outcome.as_ref().unwrap();
+                (outcome.action, outcome.rescheduled_to.map(|to| to.to_chrono()))
+            })
+            .collect();
+        assert_eq!(
+            rescheduled,
+            vec![
+                (CatchUpAction::Published, None),
+                (CatchUpAction::Rescheduled, Some(now() + Duration::minutes(30))),
+                (CatchUpAction::Rescheduled, Some(now() + Duration::minutes(60))),
+            ]
+        );
+        assert_eq!(outcomes[0].as_ref().unwrap().late_by_secs, 180 * 60);
+    }
+
+    /// Tests that a post's own policy wins over its account's, which wins over the default.
+    #[test]
+    fn test_policy_precedence() {
+        let mut settings = scheduler(CatchUpPolicy::PublishAll);
+        settings
+            .catch_up_accounts
+            .insert("company".to_string(), CatchUpPolicy::MarkMissed);
+
+        let mut post = post_late_by(60);
+        assert_eq!(policy_for(&post, &settings), CatchUpPolicy::PublishAll);
+
+        post.author = Some("company".to_string());
+        assert_eq!(policy_for(&post, &settings), CatchUpPolicy::MarkMissed);
+
+        post.catch_up_policy = Some(CatchUpPolicy::Spread { interval_minutes: 15 });
+        assert_eq!(policy_for(&post, &settings), CatchUpPolicy::Spread { interval_minutes: 15 });
+    }
+
+    /// Tests the TOML representation of the policies, tagged by `policy`.
+    #[test]
+    fn test_policy_toml() {
+        let policy: CatchUpPolicy = toml::from_str("policy = \"spread\"\ninterval_minutes = 30").unwrap();
+        assert_eq!(policy, CatchUpPolicy::Spread { interval_minutes: 30 });
+
+        let policy: CatchUpPolicy = toml::from_str("policy = \"mark_missed\"").unwrap();
+        assert_eq!(policy, CatchUpPolicy::MarkMissed);
+
+        assert!(toml::from_str::<CatchUpPolicy>("policy = \"spread\"\ninterval = 30").is_err());
+        assert!(toml::from_str::<CatchUpPolicy>("policy = \"retry\"").is_err());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod blackout;
+pub mod catch_up;
+pub mod pacing;
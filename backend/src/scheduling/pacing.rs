@@ -0,0 +1,602 @@
+use crate::config::settings::SchedulerSettings;
+use crate::models::post::{Post, PostStatus};
+use crate::repository::posts::{PostRepository, RepositoryError};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Utc};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Upper bound on the candidate times tried by `PacingRule::next_allowed`.
+const MAX_CANDIDATES: usize = 1000;
+
+/// How far ahead of the requested time `account_post_times` looks for posts, so that the
+/// alternative times suggested by `PostPacing::suggest` take later posts into account.
+const LOOKAHEAD_DAYS: i64 = 35;
+
+/// Statuses of the posts that count against the pacing rule of their account when a new
+/// post is scheduled: the ones that are or will be published.
+pub const SCHEDULING_STATUSES: &[PostStatus] = &[
+    PostStatus::Scheduled,
+    PostStatus::Publishing,
+    PostStatus::Published,
+    PostStatus::PartiallyPublished,
+];
+
+/// Statuses of the posts that count against the pacing rule of their account when a due post
+/// is published: the ones that are already out. Posts still waiting are checked when they
+/// are due themselves.
+pub const PUBLISHING_STATUSES: &[PostStatus] =
+    &[PostStatus::Publishing, PostStatus::Published, PostStatus::PartiallyPublished];
+
+/// Limits on how often an account publishes.
+///
+/// Every limit is optional. Days and weeks (starting on Monday) are calendar days and weeks
+/// in the configured `timezone_offset`.
+///
+/// ```toml
+/// [scheduler.pacing]
+/// min_gap_minutes = 120
+/// max_per_day = 2
+/// max_per_week = 5
+///
+/// [scheduler.pacing_accounts."company-page-id"]
+/// max_per_day = 1
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PacingRule {
+    /// Minimum number of minutes between two posts of the account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_gap_minutes: Option<u32>,
+    /// Maximum number of posts of the account per day.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_per_day: Option<u32>,
+    /// Maximum number of posts of the account per week.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_per_week: Option<u32>,
+}
+
+/// Why a post cannot be published at a given time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacingViolation {
+    /// Another post of the account is less than `min_gap_minutes` away.
+    TooClose { other: DateTime<Utc>, min_gap_minutes: u32 },
+    /// The account already has `max_per_day` posts that day.
+    DailyCap { max_per_day: u32 },
+    /// The account already has `max_per_week` posts that week.
+    WeeklyCap { max_per_week: u32 },
+}
+
+impl fmt::Display for PacingViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PacingViolation::TooClose { other, min_gap_minutes } => write!(
+                f,
+                "Another post of this account goes out at {}, less than {} minutes away",
+                other, min_gap_minutes
+            ),
+            PacingViolation::DailyCap { max_per_day } => {
+                write!(f, "This account already has {} posts that day", max_per_day)
+            }
+            PacingViolation::WeeklyCap { max_per_week } => {
+                write!(f, "This account already has {} posts that week", max_per_week)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PacingViolation {}
+
+impl PacingRule {
+    /// Returns whether the rule sets no limit at all.
+    pub fn is_unrestricted(&self) -> bool {
+        *self == PacingRule::default()
+    }
+
+    /// Checks the limits, reporting errors under the settings path `key`.
+    pub fn validate(&self, key: &str, errors: &mut Vec<String>) {
+        let limits = [
+            ("min_gap_minutes", self.min_gap_minutes),
+            ("max_per_day", self.max_per_day),
+            ("max_per_week", self.max_per_week),
+        ];
+        for (name, limit) in limits {
+            if limit == Some(0) {
+                errors.push(format!("{}.{}: must be greater than 0", key, name));
+            }
+        }
+    }
+
+    /// Checks whether the account may publish at `at`.
+    ///
+    /// # Parameters
+    ///
+    /// - `at`: The time the post would be published.
+    /// - `others`: The times of the account's other posts that count against the rule.
+    /// - `offset`: The timezone in which days and weeks are counted.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first limit that would be exceeded.
+    pub fn check(
+        &self,
+        at: DateTime<Utc>,
+        others: &[DateTime<Utc>],
+        offset: FixedOffset,
+    ) -> Result<(), PacingViolation> {
+        if let Some(min_gap_minutes) = self.min_gap_minutes {
+            let min_gap = Duration::minutes(min_gap_minutes as i64);
+            if let Some(other) = others.iter().find(|other| (at - **other).abs() < min_gap) {
+                return Err(PacingViolation::TooClose {
+                    other: *other,
+                    min_gap_minutes,
+                });
+            }
+        }
+
+        let day = at.with_timezone(&offset).date_naive();
+        if let Some(max_per_day) = self.max_per_day {
+            let count = others
+                .iter()
+                .filter(|other| other.with_timezone(&offset).date_naive() == day)
+                .count();
+            if count >= max_per_day as usize {
+                return Err(PacingViolation::DailyCap { max_per_day });
+            }
+        }
+
+        if let Some(max_per_week) = self.max_per_week {
+            let week = day.iso_week();
+            let count = others
+                .iter()
+                .filter(|other| other.with_timezone(&offset).date_naive().iso_week() == week)
+                .count();
+            if count >= max_per_week as usize {
+                return Err(PacingViolation::WeeklyCap { max_per_week });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the earliest time at or after `at` when the account may publish.
+    ///
+    /// # Functionality
+    ///
+    /// Starting from `at`, the candidate time moves past each violated limit until none is:
+    ///
+    /// - Too close to another post: `min_gap_minutes` after that post.
+    /// - Daily cap reached: the same time of day on the next day.
+    /// - Weekly cap reached: the same time of day on the next Monday.
+    ///
+    /// Keeping the time of day means a post scheduled in the morning is suggested or deferred
+    /// to another morning rather than to midnight.
+    pub fn next_allowed(&self, at: DateTime<Utc>, others: &[DateTime<Utc>], offset: FixedOffset) -> DateTime<Utc> {
+        let mut candidate = at;
+        for _ in 0..MAX_CANDIDATES {
+            candidate = match self.check(candidate, others, offset) {
+                Ok(()) => return candidate,
+                Err(PacingViolation::TooClose { other, min_gap_minutes }) => {
+                    other + Duration::minutes(min_gap_minutes as i64)
+                }
+                Err(PacingViolation::DailyCap { .. }) => candidate + Duration::days(1),
+                Err(PacingViolation::WeeklyCap { .. }) => {
+                    let weekday = candidate.with_timezone(&offset).weekday();
+                    candidate + Duration::days(7 - weekday.num_days_from_monday() as i64)
+                }
+            };
+        }
+        candidate
+    }
+
+    /// How far before a post the account's other posts can affect it.
+    fn lookback(&self) -> Duration {
+        let min_gap = Duration::minutes(self.min_gap_minutes.unwrap_or(0) as i64);
+        min_gap.max(Duration::days(8))
+    }
+}
+
+/// Returns the pacing rule of the account `author`.
+///
+/// A rule in `scheduler.pacing_accounts` replaces `scheduler.pacing` for its account; it is
+/// not merged with it. Posts without an author are published on behalf of the owner of the
+/// access token and follow `scheduler.pacing`.
+pub fn rule_for<'a>(author: Option<&str>, scheduler: &'a SchedulerSettings) -> &'a PacingRule {
+    author
+        .and_then(|author| scheduler.pacing_accounts.get(author))
+        .unwrap_or(&scheduler.pacing)
+}
+
+/// Returns the times of the posts of `author` that count against its pacing rule around `at`.
+///
+/// # Functionality
+///
+/// Queries the posts in the given statuses from `rule`'s lookback before `at` to
+/// `LOOKAHEAD_DAYS` after it, and keeps those published on behalf of the account on any of
+/// their targets (see `Post::accounts`), except `exclude`. Posts that went out are queried by
+/// when they did (see `PostRepository::find_published_in_range`), so a post published late
+/// counts in the window it was published in, and the others by their `scheduled_time`. Each
+/// post counts once, at the time it was published on the account (see `account_time`), if
+/// that is within the window.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be queried.
+pub async fn account_post_times<R: PostRepository + ?Sized>(
+    posts: &R,
+    rule: &PacingRule,
+    author: Option<&str>,
+    exclude: Option<ObjectId>,
+    statuses: &[PostStatus],
+    at: DateTime<Utc>,
+) -> Result<Vec<DateTime<Utc>>, RepositoryError> {
+    let start = at - rule.lookback();
+    let end = at + Duration::days(LOOKAHEAD_DAYS);
+
+    let mut times = Vec::new();
+    for status in statuses {
+        let found = if *status == PostStatus::Publishing || status.is_published() {
+            posts.find_published_in_range(start, end, *status).await?
+        } else {
+            posts.find_in_range(start, end, *status).await?
+        };
+        for post in found {
+            if exclude.is_some() && post.id == exclude {
+                continue;
+            }
+            times.extend(account_time(&post, author).filter(|time| *time >= start && *time <= end));
+        }
+    }
+    times.sort();
+    Ok(times)
+}
+
+/// Returns the time `post` counts at against the pacing rule of `account`, or `None` if none
+/// of its targets is published on behalf of that account.
+///
+/// That is when its first target on the account was published, or, if none was, when the
+/// post went out (see `Post::publication_time`), and its `scheduled_time` if it has not.
+fn account_time(post: &Post, account: Option<&str>) -> Option<DateTime<Utc>> {
+    if !post.accounts().contains(&account) {
+        return None;
+    }
+    let published_at = post
+        .targets
+        .iter()
+        .filter(|target| post.account_of(target) == account)
+        .filter_map(|target| target.published_at)
+        .min();
+    Some(match published_at {
+        Some(published_at) => published_at.to_chrono(),
+        None => post.publication_time().unwrap_or(post.scheduled_time.to_chrono()),
+    })
+}
+
+/// The pacing rule of one account, with the times of the account's other posts.
+#[derive(Debug, Clone)]
+struct AccountPacing {
+    account: Option<String>,
+    rule: PacingRule,
+    others: Vec<DateTime<Utc>>,
+}
+
+/// The pacing rules a post must respect: those of every account it is still to be published
+/// on behalf of (see `Post::pending_accounts`).
+///
+/// # Functionality
+///
+/// A post published on a personal profile and a company page must respect the rules of both
+/// accounts, each with respect to that account's other posts: it is allowed at a time every
+/// rule allows, and deferred or suggested to such times. Accounts without any limit are left
+/// out, so a post of such accounts only is unrestricted.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// let pacing = PostPacing::load(posts, &post, &settings.scheduler, SCHEDULING_STATUSES, at).await?;
+/// if let Err(violation) = pacing.check(at, settings.timezone()) {
+///     let suggested_times = pacing.suggest(at, settings.timezone(), 3);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PostPacing {
+    accounts: Vec<AccountPacing>,
+}
+
+impl PostPacing {
+    /// Loads the rules of the pending accounts of `post`, with the times of their other posts
+    /// in `statuses` around `at` (see `account_post_times`); `post` itself is left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository cannot be queried.
+    pub async fn load<R: PostRepository + ?Sized>(
+        posts: &R,
+        post: &Post,
+        scheduler: &SchedulerSettings,
+        statuses: &[PostStatus],
+        at: DateTime<Utc>,
+    ) -> Result<Self, RepositoryError> {
+        let mut accounts = Vec::new();
+        for account in post.pending_accounts() {
+            let rule = rule_for(account, scheduler);
+            if rule.is_unrestricted() {
+                continue;
+            }
+            let others = account_post_times(posts, rule, account, post.id, statuses, at).await?;
+            accounts.push(AccountPacing {
+                account: account.map(str::to_string),
+                rule: *rule,
+                others,
+            });
+        }
+        Ok(PostPacing { accounts })
+    }
+
+    /// Returns whether no account of the post has any limit.
+    pub fn is_unrestricted(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Counts another post of `account` at `at`, such as one a dry run would have published.
+    pub fn count(&mut self, account: Option<&str>, at: DateTime<Utc>) {
+        for pacing in self.accounts.iter_mut().filter(|pacing| pacing.account.as_deref() == account) {
+            pacing.others.push(at);
+        }
+    }
+
+    /// Checks whether the post may be published at `at` (see `PacingRule::check`).
+    ///
+    /// # Errors
+    ///
+    /// Returns the first limit that would be exceeded, for the first account exceeding one.
+    pub fn check(&self, at: DateTime<Utc>, offset: FixedOffset) -> Result<(), PacingViolation> {
+        self.accounts
+            .iter()
+            .try_for_each(|pacing| pacing.rule.check(at, &pacing.others, offset))
+    }
+
+    /// Returns the earliest time at or after `at` when every account may publish.
+    ///
+    /// The candidate time moves to the latest time allowed by each rule (see
+    /// `PacingRule::next_allowed`) until every rule allows it.
+    pub fn next_allowed(&self, at: DateTime<Utc>, offset: FixedOffset) -> DateTime<Utc> {
+        let mut candidate = at;
+        for _ in 0..MAX_CANDIDATES {
+            let next = self
+                .accounts
+                .iter()
+                .map(|pacing| pacing.rule.next_allowed(candidate, &pacing.others, offset))
+                .max()
+                .unwrap_or(candidate);
+            if next == candidate {
+                break;
+            }
+            candidate = next;
+        }
+        candidate
+    }
+
+    /// Suggests `count` increasing times at or after `at` when the post may be published, each
+    /// one respecting the rules with respect to the previous suggestions too.
+    ///
+    /// Each suggestion is the earliest allowed time counting the previous ones as posts, so
+    /// they keep the time of day of `at` when possible. A time already suggested is followed by
+    /// one the largest `min_gap_minutes` (or an hour) later.
+    pub fn suggest(&self, at: DateTime<Utc>, offset: FixedOffset, count: usize) -> Vec<DateTime<Utc>> {
+        let step = self.accounts.iter().filter_map(|pacing| pacing.rule.min_gap_minutes).max();
+        let step = Duration::minutes(step.unwrap_or(60) as i64);
+        let mut pacing = self.clone();
+        let mut suggestions: Vec<DateTime<Utc>> = Vec::with_capacity(count);
+        while suggestions.len() < count {
+            let mut suggestion = pacing.next_allowed(at, offset);
+            if let Some(last) = suggestions.last() {
+                if suggestion <= *last {
+                    suggestion = pacing.next_allowed(*last + step, offset);
+                }
+            }
+            suggestions.push(suggestion);
+            for account in &mut pacing.accounts {
+                account.others.push(suggestion);
+            }
+        }
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::post::{StatusTransition, Target, TargetStatus};
+    use crate::repository::memory::InMemoryPostRepository;
+    use chrono::TimeZone;
+    use mongodb::bson::DateTime as BsonDateTime;
+
+    fn utc() -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    /// Friday 10 May 2024 at the given UTC time.
+    fn friday(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 10, hour, minute, 0).unwrap()
+    }
+
+    /// Tests that a post too close to another one is rejected, on either side.
+    #[test]
+    fn test_min_gap() {
+        let rule = PacingRule {
+            min_gap_minutes: Some(120),
+            ..PacingRule::default()
+        };
+        let others = [friday(10, 0)];
+
+        assert_eq!(
+            rule.check(friday(11, 0), &others, utc()),
+            Err(PacingViolation::TooClose {
+                other: friday(10, 0),
+                min_gap_minutes: 120
+            })
+        );
+        assert!(rule.check(friday(9, 0), &others, utc()).is_err());
+        assert_eq!(rule.check(friday(12, 0), &others, utc()), Ok(()));
+        assert_eq!(rule.check(friday(8, 0), &others, utc()), Ok(()));
+        assert_eq!(rule.next_allowed(friday(9, 30), &others, utc()), friday(12, 0));
+    }
+
+    /// Tests that daily caps count calendar days in the configured timezone.
+    #[test]
+    fn test_daily_cap_uses_timezone() {
+        let rule = PacingRule {
+            max_per_day: Some(1),
+            ..PacingRule::default()
+        };
+        // 01:00 UTC on Friday is still Thursday in UTC-3.
+        let others = [friday(1, 0)];
+        let brasilia = FixedOffset::west_opt(3 * 3600).unwrap();
+
+        assert_eq!(
+            rule.check(friday(15, 0), &others, utc()),
+            Err(PacingViolation::DailyCap { max_per_day: 1 })
+        );
+        assert_eq!(rule.check(friday(15, 0), &others, brasilia), Ok(()));
+        assert_eq!(rule.next_allowed(friday(15, 0), &others, utc()), friday(15, 0) + Duration::days(1));
+    }
+
+    /// Tests that a full week defers to the next Monday at the same time of day.
+    #[test]
+    fn test_weekly_cap() {
+        let rule = PacingRule {
+            max_per_week: Some(2),
+            ..PacingRule::default()
+        };
+        // Monday and Wednesday of the same week.
+        let others = [friday(9, 0) - Duration::days(4), friday(9, 0) - Duration::days(2)];
+
+        assert_eq!(
+            rule.check(friday(9, 0), &others, utc()),
+            Err(PacingViolation::WeeklyCap { max_per_week: 2 })
+        );
+        assert_eq!(rule.next_allowed(friday(9, 0), &others, utc()), friday(9, 0) + Duration::days(3));
+    }
+
+    /// Tests that suggestions respect the rule with respect to each other.
+    #[test]
+    fn test_suggestions_are_spaced() {
+        let rule = PacingRule {
+            min_gap_minutes: Some(60),
+            max_per_day: Some(3),
+            ..PacingRule::default()
+        };
+        let pacing = PostPacing {
+            accounts: vec![AccountPacing {
+                account: None,
+                rule,
+                others: vec![friday(9, 0), friday(10, 0)],
+            }],
+        };
+
+        assert_eq!(
+            pacing.suggest(friday(9, 30), utc(), 3),
+            vec![friday(11, 0), friday(12, 0) + Duration::days(1), friday(13, 0) + Duration::days(1)]
+        );
+    }
+
+    /// Tests that only posts of the same account in the given statuses are counted.
+    #[tokio::test]
+    async fn test_account_post_times() {
+        let posts = InMemoryPostRepository::new();
+        let insert = |title: &str, author: Option<&str>, at: DateTime<Utc>, status: PostStatus| {
+            let mut post = Post::new(title.to_string(), "Content".to_string(), BsonDateTime::from_chrono(at), at);
+            post.author = author.map(str::to_string);
+            post.status = status;
+            posts.insert(post)
+        };
+        insert("Mine", Some("alice"), friday(9, 0), PostStatus::Scheduled).await.unwrap();
+        insert("Other account", Some("bob"), friday(10, 0), PostStatus::Scheduled).await.unwrap();
+        insert("Draft", Some("alice"), friday(11, 0), PostStatus::Draft).await.unwrap();
+        let excluded = insert("Excluded", Some("alice"), friday(12, 0), PostStatus::Scheduled).await.unwrap();
+
+        let rule = PacingRule::default();
+        let times = account_post_times(&posts, &rule, Some("alice"), Some(excluded), SCHEDULING_STATUSES, friday(10, 0))
+            .await
+            .unwrap();
+
+        assert_eq!(times, vec![friday(9, 0)]);
+    }
+
+    /// Tests that posts that went out count when they were published, or claimed, whenever
+    /// they were scheduled.
+    #[tokio::test]
+    async fn test_account_post_times_of_late_posts() {
+        let posts = InMemoryPostRepository::new();
+        let late = |scheduled_time: DateTime<Utc>, transitions: &[(PostStatus, DateTime<Utc>)]| {
+            let mut post = Post::new(
+                "Late".to_string(),
+                "Content".to_string(),
+                BsonDateTime::from_chrono(scheduled_time),
+                scheduled_time,
+            );
+            for (to, at) in transitions {
+                post.record_transition(StatusTransition::new(Some(post.status), *to, "scheduler", *at));
+            }
+            posts.insert(post)
+        };
+        let published = [(PostStatus::Publishing, friday(7, 55)), (PostStatus::Published, friday(8, 0))];
+        late(friday(8, 0) - Duration::days(10), &published).await.unwrap();
+        late(friday(8, 30) - Duration::days(10), &[(PostStatus::Publishing, friday(8, 30))]).await.unwrap();
+        let long_ago = friday(8, 0) - Duration::days(9);
+        let published_long_ago = [(PostStatus::Publishing, long_ago), (PostStatus::Published, long_ago)];
+        late(friday(8, 0) - Duration::days(20), &published_long_ago).await.unwrap();
+
+        let rule = PacingRule::default();
+        let times = account_post_times(&posts, &rule, None, None, PUBLISHING_STATUSES, friday(9, 0)).await.unwrap();
+
+        assert_eq!(times, vec![friday(8, 0), friday(8, 30)]);
+    }
+
+    /// Tests that a post is paced by the rules of the accounts of its targets, not only of its
+    /// author, and that posts count for the accounts of their targets.
+    #[tokio::test]
+    async fn test_post_pacing_covers_every_account() {
+        let posts = InMemoryPostRepository::new();
+        let on_company = |author: &str, at: DateTime<Utc>| {
+            let mut post = Post::new("Hiring".to_string(), "Content".to_string(), BsonDateTime::from_chrono(at), at);
+            post.author = Some(author.to_string());
+            let mut company = Target::new("linkedin");
+            company.author = Some("company".to_string());
+            post.targets = vec![Target::new("linkedin"), company];
+            post
+        };
+        let mut published = on_company("bob", friday(8, 0));
+        published.status = PostStatus::Published;
+        published.targets[1].status = TargetStatus::Published;
+        published.targets[1].published_at = Some(BsonDateTime::from_chrono(friday(8, 30)));
+        posts.insert(published).await.unwrap();
+
+        let rule = PacingRule::default();
+        let times = account_post_times(&posts, &rule, Some("company"), None, PUBLISHING_STATUSES, friday(9, 0))
+            .await
+            .unwrap();
+        assert_eq!(times, vec![friday(8, 30)]);
+
+        let mut scheduler = SchedulerSettings::default();
+        scheduler.pacing_accounts.insert(
+            "company".to_string(),
+            PacingRule {
+                max_per_day: Some(1),
+                ..PacingRule::default()
+            },
+        );
+        let post = on_company("alice", friday(9, 0));
+        let pacing = PostPacing::load(&posts, &post, &scheduler, PUBLISHING_STATUSES, friday(9, 0)).await.unwrap();
+        assert_eq!(pacing.check(friday(9, 0), utc()), Err(PacingViolation::DailyCap { max_per_day: 1 }));
+        assert_eq!(pacing.next_allowed(friday(9, 0), utc()), friday(9, 0) + Duration::days(1));
+
+        let personal = Post {
+            targets: vec![Target::new("linkedin")],
+            ..post
+        };
+        let pacing = PostPacing::load(&posts, &personal, &scheduler, PUBLISHING_STATUSES, friday(9, 0)).await.unwrap();
+        assert!(pacing.is_unrestricted());
+    }
+}
@@ -0,0 +1,60 @@
+use linkedin_automation::config::secret::{Secret, SecretCipher, ACCESS_TOKEN_SECRET, SECRET_KEY_ENV};
+use linkedin_automation::config::settings::Settings;
+use linkedin_automation::repository::secrets::connect_secrets;
+use log::{error, info};
+use std::io::Read;
+
+/// Entry point of the secret store management tool.
+///
+/// # Functionality
+///
+/// Manages the secrets kept encrypted at rest in the configured database:
+///
+/// - `generate-key`: Prints a new random key for `LINKEDIN_AUTOMATION_SECRET_KEY`.
+/// - `set-access-token`: Reads the LinkedIn access token from standard input, encrypts it
+///   with the key in `LINKEDIN_AUTOMATION_SECRET_KEY` and stores it in the secret store,
+///   replacing the previous one. The scheduler picks it up when no `access_token_file` or
+///   `access_token` setting is set.
+///
+/// The token is read from standard input rather than from an argument so that it does not
+/// end up in the shell history or the process list.
+///
+/// # Example Usage
+///
+/// ```bash
+/// export LINKEDIN_AUTOMATION_SECRET_KEY=$(cargo run -q --bin secrets -- generate-key)
+/// cargo run --bin secrets -- set-access-token < token.txt
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the command is unknown, the settings are invalid, the key is
+/// missing or invalid, the token is empty, or the database cannot be reached.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    match std::env::args().nth(1).as_deref() {
+        Some("generate-key") => {
+            println!("{}", SecretCipher::generate_key()?);
+            Ok(())
+        }
+        Some("set-access-token") => {
+            let settings = Settings::load().inspect_err(|e| error!("{}", e))?;
+            let cipher = SecretCipher::from_env()?.ok_or_else(|| format!("{} must be set", SECRET_KEY_ENV))?;
+
+            let mut token = String::new();
+            std::io::stdin().read_to_string(&mut token)?;
+            let token = Secret::new(token.trim());
+            if token.is_empty() {
+                return Err("The access token read from standard input is empty".into());
+            }
+
+            let secrets = connect_secrets(&settings.database).await?;
+            secrets.put(cipher.encrypt(ACCESS_TOKEN_SECRET, &token)?).await?;
+            info!("Stored the encrypted access token as {}", ACCESS_TOKEN_SECRET);
+            Ok(())
+        }
+        _ => Err("Usage: secrets generate-key | secrets set-access-token < token.txt".into()),
+    }
+}
@@ -0,0 +1,163 @@
+use log::{info, warn};
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::error::Elapsed;
+
+/// Handle telling the tasks of a binary that it is shutting down.
+///
+/// Clones share the same state. A shutdown is requested once, by `SIGTERM` or `SIGINT` for
+/// handles created with `Shutdown::listen`, and stays requested.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    requested: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Returns a handle requested when the process receives `SIGTERM` or `SIGINT` (Ctrl+C).
+    ///
+    /// # Functionality
+    ///
+    /// Spawns a task waiting for the signals. On the first one, the shutdown is logged and
+    /// requested; the binary is then expected to stop accepting new work, finish what is in
+    /// flight with `finish_within`, and exit.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust,ignore
+    /// let mut shutdown = Shutdown::listen();
+    /// tokio::select! {
+    ///     _ = do_work() => {}
+    ///     _ = shutdown.requested() => info!("Stopping"),
+    /// }
+    /// ```
+    pub fn listen() -> Self {
+        let (sender, shutdown) = Shutdown::channel();
+        tokio::spawn(async move {
+            let signal = termination_signal().await;
+            info!("Received {}, shutting down", signal);
+            sender.send_replace(true);
+            // Keep the sender alive so that handles keep reporting the shutdown as requested.
+            sender.closed().await;
+        });
+        shutdown
+    }
+
+    /// Returns a handle whose shutdown is requested by sending `true` on the returned sender,
+    /// e.g. in tests.
+    pub fn channel() -> (watch::Sender<bool>, Self) {
+        let (sender, requested) = watch::channel(false);
+        (sender, Shutdown { requested })
+    }
+
+    /// Returns whether a shutdown has been requested.
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// Waits until a shutdown is requested. Never returns if the sender is dropped first.
+    pub async fn requested(&mut self) {
+        if self.requested.wait_for(|requested| *requested).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Runs `work` to completion, but gives up `deadline` after a shutdown is requested.
+    ///
+    /// # Functionality
+    ///
+    /// Before a shutdown is requested, `work` runs without a time limit. Once it is
+    /// requested, `work` has `deadline` left to finish, e.g. for in-flight HTTP requests or
+    /// publications to complete; after that it is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Elapsed` if `work` did not finish within `deadline` of the shutdown request.
+    pub async fn finish_within<F: Future>(&self, work: F, deadline: Duration) -> Result<F::Output, Elapsed> {
+        let mut shutdown = self.clone();
+        tokio::pin!(work);
+        tokio::select! {
+            output = &mut work => return Ok(output),
+            _ = shutdown.requested() => {}
+        }
+
+        info!("Waiting up to {:?} for in-flight work to finish", deadline);
+        let result = tokio::time::timeout(deadline, work).await;
+        if result.is_err() {
+            warn!("In-flight work did not finish within {:?}", deadline);
+        }
+        result
+    }
+}
+
+/// Waits for `SIGTERM` or `SIGINT` and returns its name.
+#[cfg(unix)]
+async fn termination_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => tokio::select! {
+            _ = terminate.recv() => "SIGTERM",
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+        },
+        Err(e) => {
+            warn!("Cannot listen for SIGTERM: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+            "SIGINT"
+        }
+    }
+}
+
+/// Waits for Ctrl+C and returns its name.
+#[cfg(not(unix))]
+async fn termination_signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "Ctrl+C"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that work finishing before the shutdown deadline returns its output.
+    #[tokio::test(start_paused = true)]
+    async fn test_work_finishes_within_deadline() {
+        let (sender, shutdown) = Shutdown::channel();
+        sender.send_replace(true);
+
+        let work = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            "done"
+        };
+
+        assert_eq!(shutdown.finish_within(work, Duration::from_secs(10)).await, Ok("done"));
+        assert!(shutdown.is_requested());
+    }
+
+    /// Tests that work still running `deadline` after the shutdown request is dropped.
+    #[tokio::test(start_paused = true)]
+    async fn test_work_is_dropped_after_deadline() {
+        let (sender, shutdown) = Shutdown::channel();
+        let work = tokio::time::sleep(Duration::from_secs(3600));
+
+        let result = tokio::join!(shutdown.finish_within(work, Duration::from_secs(10)), async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            sender.send_replace(true);
+        })
+        .0;
+
+        assert!(result.is_err());
+    }
+
+    /// Tests that work runs without a deadline as long as no shutdown is requested.
+    #[tokio::test(start_paused = true)]
+    async fn test_no_deadline_without_shutdown() {
+        let (_sender, shutdown) = Shutdown::channel();
+        let work = async {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            "done"
+        };
+
+        assert_eq!(shutdown.finish_within(work, Duration::from_secs(1)).await, Ok("done"));
+    }
+}
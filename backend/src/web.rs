@@ -1,35 +1,130 @@
-use bson::Bson;
-use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
-use config::settings::{get_local_time, get_timezone_offset};
-use futures_util::TryStreamExt;
-use log::{error, info};
-use mongodb::{
-    bson::{doc, DateTime as BsonDateTime},
-    Client,
-};
+use chrono::{DateTime, DurationRound, FixedOffset, TimeDelta, Utc};
+use linkedin_automation::clock::{Clock, SystemClock};
+use linkedin_automation::config::reload::{watch_settings, SharedSettings};
+use linkedin_automation::config::settings::{local_to_utc, print_config_requested, Settings};
+use linkedin_automation::models::account::parse_account;
+use linkedin_automation::models::pause::Pause;
+use linkedin_automation::models::post::{Post, PostStatus, StatusTransition, Target, DEFAULT_CHANNEL};
+use linkedin_automation::problem::{recover, ApiError, FieldError};
+use linkedin_automation::publishing::publisher::channel_names;
+use linkedin_automation::repository::pauses::{connect_pauses, PauseRepository};
+use linkedin_automation::repository::posts::{connect, PostRepository};
+use linkedin_automation::scheduling::blackout::{blackout_at, BlackoutPolicy};
+use linkedin_automation::scheduling::catch_up::CatchUpPolicy;
+use linkedin_automation::scheduling::pacing::{PostPacing, SCHEDULING_STATUSES};
+use linkedin_automation::shutdown::Shutdown;
+use log::{error, info, warn};
+use mongodb::bson::DateTime as BsonDateTime;
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
+use warp::http::header::{HeaderValue, WARNING};
 use warp::http::Method;
-use warp::reject::Reject;
 use warp::Filter;
-use warp::{Rejection};
+use warp::Rejection;
+use warp::Reply;
 
-mod config;
-
-/// Structure representing a scheduled post.
+/// Structure representing the JSON payload used to schedule or update a post.
+///
+/// The payload includes a title, content, and a scheduled time in local string format (`YYYY-MM-DD HH:MM`).
+/// The status of the post is set to "scheduled" by default. It is converted into the shared
+/// `Post` model before being stored.
+///
+/// The optional `catch_up_policy` (e.g. `{"policy": "mark_missed"}`) overrides the configured
+/// catch-up policy for this post; when an update omits it, the post keeps its current one.
 ///
-/// The post includes a title, content, and a scheduled time in string format (`YYYY-MM-DD HH:MM`).
-/// The status of the post is set to "pending" by default.
+/// The optional `author` is the account the post is published on behalf of: a LinkedIn
+/// profile ID, or an organization URN for a company page; by default, the owner of the access
+/// token. It is stored as its account key (see `parse_account`), so that pauses and pacing rules
+/// find it however it was written. It is only set when the post is created.
+///
+/// The optional `channels` lists the channels the post is published to (by default
+/// `["linkedin"]`). For a different account or text per destination, `targets` lists them
+/// instead (see `TargetRequest`). Both are only set when the post is created.
 #[derive(Debug, Deserialize, Serialize)]
-struct Post {
+struct PostRequest {
     title: String,
     content: String,
     scheduled_time: String,
-    #[serde(default = "default_status")]
-    status: String,
+    #[serde(default)]
+    status: PostStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    catch_up_policy: Option<CatchUpPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    channels: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    targets: Option<Vec<TargetRequest>>,
 }
 
+/// A destination of a post in `PostRequest::targets`: a channel, with an optional `author`,
+/// `content` and `hashtags` replacing or extending those of the post on that channel.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct TargetRequest {
+    channel: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hashtags: Vec<String>,
+}
+
+/// Returns the targets of a new post, from its `targets`, its `channels` or `DEFAULT_CHANNEL`.
+///
+/// # Errors
+///
+/// Returns the error of the field given (`targets` or `channels`) if both are given, there
+/// is no target, a target's author is not an account (see `parse_account`), a channel is not
+/// among `available`, or a target (channel and account) is listed twice.
+fn post_targets(post: &PostRequest, available: &[String]) -> Result<Vec<Target>, FieldError> {
+    let field = if post.targets.is_some() { "targets" } else { "channels" };
+    let targets: Vec<Target> = match (&post.targets, &post.channels) {
+        (Some(_), Some(_)) => return Err(FieldError::new(field, "Give either channels or targets, not both")),
+        (Some(targets), None) => targets
+            .iter()
+            .map(|request| {
+                Ok(Target {
+                    author: request.author.as_deref().map(parse_account).transpose().map_err(|e| {
+                        FieldError::new(field, format!("Invalid author of a {} target: {}", request.channel, e))
+                    })?,
+                    content: request.content.clone(),
+                    hashtags: request
+                        .hashtags
+                        .iter()
+                        .map(|hashtag| hashtag.trim().trim_start_matches('#').to_string())
+                        .filter(|hashtag| !hashtag.is_empty())
+                        .collect(),
+                    ..Target::new(request.channel.as_str())
+                })
+            })
+            .collect::<Result<_, FieldError>>()?,
+        (None, Some(channels)) => channels.iter().map(|channel| Target::new(channel.as_str())).collect(),
+        (None, None) => vec![Target::new(DEFAULT_CHANNEL)],
+    };
+    if targets.is_empty() {
+        return Err(FieldError::new(field, "A post needs at least one target"));
+    }
+
+    let mut listed = HashSet::new();
+    for target in &targets {
+        if !available.contains(&target.channel) {
+            return Err(FieldError::new(field, format!("Unknown channel {}", target.channel)));
+        }
+        if !listed.insert(target.label()) {
+            return Err(FieldError::new(field, format!("Target {} is listed twice", target.label())));
+        }
+    }
+    Ok(targets)
+}
+
+/// Number of alternative times suggested when a post breaks the pacing rule of its account.
+const SUGGESTED_TIMES: usize = 3;
+
 /// Structure to handle query parameters in the post query route.
 ///
 /// The parameters include a start date and an end date to filter the scheduled posts.
@@ -39,21 +134,127 @@ struct PostQueryParams {
     end_date: String,
 }
 
-/// Custom error structure for handling date parsing errors.
-#[derive(Debug)]
-struct ParseDateError;
+/// JSON payload of `POST /admin/pause`.
+///
+/// Without `account`, publishing is paused for every account.
+#[derive(Debug, Deserialize, Serialize)]
+struct PauseRequest {
+    reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+}
+
+/// JSON payload of `POST /admin/resume`: the account to resume, or none for the global pause.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ResumeRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+}
+
+/// Actor recorded in the status history when a request has no `X-Actor` header.
+const DEFAULT_ACTOR: &str = "web";
+
+/// Extracts the actor recorded in the status history from the optional `X-Actor` header.
+fn actor() -> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("x-actor")
+        .map(|actor: Option<String>| actor.unwrap_or_else(|| DEFAULT_ACTOR.to_string()))
+}
+
+/// Identifies the operator calling an `/admin` route by the bearer token of the
+/// `Authorization` header, among the `http.admin_tokens` of the current settings.
+///
+/// Extracts the name the token is configured under, recorded as the actor of the change.
+/// Requests without a token, or with a token that is not configured, are rejected with a
+/// 401 Unauthorized problem (see `recover`).
+fn admin(settings: SharedSettings) -> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization").and_then(move |authorization: Option<String>| {
+        let current = Arc::clone(&settings.borrow());
+        async move {
+            let token = authorization.as_deref().and_then(|value| value.strip_prefix("Bearer "));
+            let operator = token.and_then(|token| {
+                let mut tokens = current.http.admin_tokens.iter();
+                tokens.find(|(_, secret)| secret.matches(token)).map(|(name, _)| name.clone())
+            });
+            operator.ok_or_else(|| {
+                warn!("Rejected an /admin request without a valid admin token");
+                warp::reject::custom(ApiError::Unauthorized("A valid admin token is required".to_string()))
+            })
+        }
+    })
+}
+
+/// Returns a warning if a post scheduled at `scheduled_time` falls in a blackout window,
+/// saying what the scheduler will do with it.
+fn blackout_warning(settings: &Settings, scheduled_time: DateTime<Utc>) -> Option<String> {
+    let offset = settings.timezone();
+    let blackout = blackout_at(&settings.scheduler.blackouts, scheduled_time, offset)?;
+    let local = |time: DateTime<Utc>| time.with_timezone(&offset).format("%Y-%m-%d %H:%M").to_string();
+    let outcome = match settings.scheduler.blackout_policy {
+        BlackoutPolicy::Defer => "deferred to",
+        BlackoutPolicy::Hold => "held until",
+    };
+    Some(format!(
+        "{} falls in {}; the post will be {} {}",
+        local(scheduled_time),
+        blackout.window,
+        outcome,
+        local(blackout.until)
+    ))
+}
+
+/// Returns an error if a post scheduled at `scheduled_time` is already late at `now`.
+///
+/// Times are typed to the minute, so the current minute is still accepted: the scheduler
+/// publishes such a post right away.
+fn past_time_error(settings: &Settings, scheduled_time: DateTime<Utc>, now: DateTime<Utc>) -> Option<String> {
+    let this_minute = now.duration_trunc(TimeDelta::minutes(1)).unwrap_or(now);
+    (scheduled_time < this_minute).then(|| {
+        let offset = settings.timezone();
+        format!(
+            "{} is in the past, it is {}",
+            scheduled_time.with_timezone(&offset).format("%Y-%m-%d %H:%M"),
+            now.with_timezone(&offset).format("%Y-%m-%d %H:%M")
+        )
+    })
+}
 
-impl fmt::Display for ParseDateError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Failed to parse date")
+/// Adds `warning`, if any, to `response` as a `Warning: 199` header.
+fn with_warning(mut response: warp::reply::Response, warning: Option<String>) -> warp::reply::Response {
+    if let Some(warning) = warning {
+        info!("Warning: {}", warning);
+        // Debug formatting quotes the text and escapes non-ASCII characters, as header values require.
+        if let Ok(value) = HeaderValue::from_str(&format!("199 - {:?}", warning)) {
+            response.headers_mut().append(WARNING, value);
+        }
     }
+    response
 }
 
-impl Reject for ParseDateError {}
+/// Returns the errors of the fields every post needs: a title and some content.
+fn required_fields(post: &PostRequest) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if post.title.trim().is_empty() {
+        errors.push(FieldError::new("title", "A title is required"));
+    }
+    if post.content.trim().is_empty() {
+        errors.push(FieldError::new("content", "Content is required"));
+    }
+    errors
+}
 
-/// Function to provide the default status for a post, which is "pending".
-fn default_status() -> String {
-    "pending".to_string()
+/// Parses the `scheduled_time` of a post, typed in the configured timezone, as a UTC instant.
+///
+/// # Errors
+///
+/// Returns the error of the `scheduled_time` field if it is not a "YYYY-MM-DD HH:MM" time.
+fn parse_scheduled_time(post: &PostRequest, offset: FixedOffset) -> Result<DateTime<Utc>, FieldError> {
+    local_to_utc(&post.scheduled_time, "%Y-%m-%d %H:%M", offset).map_err(|e| {
+        error!("Error parsing date {:?}: {}", post.scheduled_time, e);
+        FieldError::new(
+            "scheduled_time",
+            format!("Expected a time like 2024-05-10 09:00, not {:?}", post.scheduled_time),
+        )
+    })
 }
 
 /// The main entry point for the LinkedIn post scheduler API server.
@@ -65,37 +266,54 @@ fn default_status() -> String {
 /// 1. **Logging Initialization**: Initializes the logging environment using `env_logger`
 ///    to provide detailed runtime information for monitoring and debugging.
 ///
-/// 2. **MongoDB Connection**: Establishes a connection to a MongoDB database,
-///    specifically targeting the `lkdin-posts` database and the `posts` collection,
-///    where scheduled posts are stored.
+/// 2. **Settings**: Loads the layered `Settings` (defaults, `config.toml` and
+///    `LINKEDIN_AUTOMATION__*` environment variables). With `--print-config`, the settings
+///    are printed with secrets redacted and the server does not start.
+///
+/// 3. **Database Connection**: Opens the storage backend described by the `[database]`
+///    settings (see `repository::posts::connect`). By default, posts are stored in the
+///    `posts` collection of the `lkdin-posts` database at `mongodb://mongo:27017`.
 ///
-/// 3. **CORS Configuration**: Configures Cross-Origin Resource Sharing (CORS) to allow
-///    the frontend (running on a different origin) to communicate with this backend server,
-///    enabling HTTP methods such as GET, POST, PUT, and DELETE.
+/// 4. **CORS Configuration**: Configures Cross-Origin Resource Sharing (CORS) to allow
+///    the frontend origins listed in `cors.allowed_origins` to communicate with this backend
+///    server, enabling HTTP methods such as GET, POST, PUT, and DELETE.
 ///
-/// 4. **Routing Setup**: Sets up the various HTTP routes using the Warp framework:
+/// 5. **Routing Setup**: Sets up the various HTTP routes using the Warp framework:
 ///    - `POST /schedule`: Schedule a new post by inserting it into the MongoDB collection.
 ///    - `GET /posts`: Query scheduled posts within a specified date range.
 ///    - `PUT /posts/{id}`: Update an existing post by its ID.
 ///    - `DELETE /posts/{id}`: Delete a post by its ID.
+///    - `GET /admin/pause`, `POST /admin/pause` and `POST /admin/resume`: List, set and lift the
+///      publishing pauses, stored in the database and respected by the scheduler. These routes
+///      require one of the `http.admin_tokens` as a bearer token.
 ///
-/// 5. **Server Execution**: The server is started and listens on `http://localhost:8080/`,
-///    serving the defined routes with the CORS configuration.
+/// 6. **Hot Reload**: The settings are watched (file changes and `SIGHUP`, see
+///    `watch_settings`). The routes read the timezone offset on every request; the bind
+///    address, CORS and database settings take effect after a restart.
+///
+/// 7. **Server Execution**: The server is started and listens on `http.bind_address` and
+///    `http.port` (`0.0.0.0:8080` by default), serving the defined routes with the CORS configuration.
+///
+/// 8. **Graceful Shutdown**: On `SIGTERM` or `SIGINT`, the server stops accepting connections and
+///    in-flight requests get `http.shutdown_timeout_secs` (30 by default) to complete before the
+///    server exits.
 ///
 /// # Errors
 ///
 /// This function will terminate with an error in the following situations:
 ///
-/// - If the connection to the MongoDB database fails.
+/// - If the settings are invalid; every invalid key is reported.
+/// - If the connection to the database fails.
 /// - If the server fails to start due to issues such as port conflicts or other I/O errors.
 ///
 /// # Example
 ///
 /// To run the server, simply execute the binary. Ensure that MongoDB is running
-/// and accessible at the configured URI (`mongodb://localhost:27017` by default).
+/// and accessible at the configured URI (`mongodb://mongo:27017` by default).
 ///
 /// ```bash
 /// cargo run --bin web_server
+/// cargo run --bin web_server -- --print-config
 /// ```
 ///
 /// Once the server is running, you can interact with it using tools like `curl` or Postman,
@@ -106,41 +324,70 @@ fn default_status() -> String {
 /// This function is asynchronous and returns a `Result<(), Box<dyn std::error::Error>>`.
 /// It will return `Ok(())` if the server runs successfully, or an `Err` if an error occurs during setup or execution.
 #[tokio::main]
-async fn main() {
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize the logger with a default level of "info".
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
-    // Create a MongoDB client and connect to the "lkdin-posts" database.
-    let mongo_client = Client::with_uri_str("mongodb://mongo:27017")
-        .await
-        .unwrap();
-    let db = mongo_client.database("lkdin-posts");
-    let posts = Arc::new(db.collection("posts"));
+    let settings = Settings::load().inspect_err(|e| error!("{}", e))?;
+    if print_config_requested() {
+        print!("{}", settings.redacted().to_toml());
+        return Ok(());
+    }
+
+    // Open the storage backend described by the `[database]` settings, MongoDB by default.
+    let posts = connect(&settings.database).await?;
+    let pauses = connect_pauses(&settings.database).await?;
 
-    info!("Server running on http://localhost:8080/");
+    let bind_address: IpAddr = settings.http.bind_address.parse()?;
+    let address = SocketAddr::new(bind_address, settings.http.port);
+    info!("Server running on http://{}/", address);
 
-    // Configure CORS to allow any origin, and specific HTTP methods and headers.
-    let cors = warp::cors()
-        .allow_any_origin()
+    // Configure CORS for the configured origins, and specific HTTP methods and headers.
+    let mut cors = warp::cors()
         .allow_methods(&[Method::GET, Method::POST, Method::PUT, Method::DELETE])
-        .allow_headers(vec!["Content-Type"]);
+        .allow_headers(vec!["Content-Type", "X-Actor", "Authorization"]);
+    if settings.cors.allowed_origins.iter().any(|origin| origin == "*") {
+        cors = cors.allow_any_origin();
+    } else {
+        cors = cors.allow_origins(settings.cors.allowed_origins.iter().map(String::as_str));
+    }
 
-    info!("CORS configured.");
+    info!("CORS configured for {:?}.", settings.cors.allowed_origins);
 
-    let update_post = update_post_route(Arc::clone(&posts));
-    let schedule_post = schedule_post_route(Arc::clone(&posts));
-    let query_posts = query_posts_route(Arc::clone(&posts));
+    // The timezone offset is read on every request, so a reload applies it immediately.
+    let settings = watch_settings(settings);
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let update_post = update_post_route(Arc::clone(&posts), settings.clone(), Arc::clone(&clock));
+    let schedule_post = schedule_post_route(Arc::clone(&posts), settings.clone(), Arc::clone(&clock));
+    let query_posts = query_posts_route(Arc::clone(&posts), settings.clone());
     let delete_post = delete_post_route(Arc::clone(&posts));
+    let list_pauses = list_pauses_route(Arc::clone(&pauses), settings.clone());
+    let pause = pause_route(Arc::clone(&pauses), settings.clone());
+    let resume = resume_route(Arc::clone(&pauses), settings.clone());
 
     let routes = schedule_post
         .or(query_posts)
         .or(delete_post)
         .or(update_post)
+        .or(list_pauses)
+        .or(pause)
+        .or(resume)
+        .recover(recover)
         .with(cors);
 
-    warp::serve(routes)
-        .run(([0, 0, 0, 0], 8080))
-        .await;
+    // On SIGTERM or SIGINT, stop accepting connections and let in-flight requests finish.
+    let shutdown = Shutdown::listen();
+    let mut stop_accepting = shutdown.clone();
+    let (_, server) = warp::serve(routes)
+        .try_bind_with_graceful_shutdown(address, async move { stop_accepting.requested().await })?;
+
+    let deadline = Duration::from_secs(settings.borrow().http.shutdown_timeout_secs);
+    if shutdown.finish_within(server, deadline).await.is_err() {
+        error!("Closing the connections of requests still in flight");
+    }
+    info!("Server stopped");
+
+    Ok(())
 }
 
 /// Configures and manages the route for scheduling new LinkedIn posts.
@@ -152,17 +399,21 @@ async fn main() {
 ///
 /// 1. **Post Reception**: Accepts a JSON payload containing post details.
 ///
-/// 2. **Date Parsing**: Converts the provided date/time string to a `NaiveDateTime` object.
+/// 2. **Date Parsing**: Parses the provided date/time string as local wall-clock time.
 ///
-/// 3. **Timezone Adjustment**: Applies the Brazil timezone offset to the provided date.
+/// 3. **UTC Conversion**: Applies the configured timezone offset to convert the local
+///    date/time to a UTC instant. This is the only place the offset is applied; the
+///    scheduler compares UTC instants only.
 ///
-/// 4. **UTC Conversion**: Converts the Brazil timezone date to UTC.
-///
-/// 5. **Storage**: Inserts the post into the MongoDB database.
+/// 4. **Storage**: Inserts the post into the MongoDB database, with `scheduled_time`
+///    stored as a native BSON `DateTime`.
 ///
 /// # Parameters
 ///
-/// - `posts`: A shared `Arc<Collection<Document>>` for concurrent access to the MongoDB collection.
+/// - `posts`: A shared `Arc` of the `PostRepository` storing the posts (MongoDB in production,
+///   in memory in tests).
+/// - `settings`: The shared settings; the date/time is expressed in their current timezone offset.
+/// - `clock`: The source of the current time, to reject posts scheduled in the past.
 ///
 /// # Returns
 ///
@@ -173,7 +424,6 @@ async fn main() {
 /// The function may return a `Rejection` in the following situations:
 ///
 /// - Failure to parse the provided date/time string.
-/// - Error in obtaining the timezone offset.
 /// - Failure to insert the document into MongoDB.
 ///
 /// # Payload Format
@@ -182,67 +432,145 @@ async fn main() {
 /// - `title`: Title of the post (string)
 /// - `content`: Content of the post (string)
 /// - `scheduled_time`: Scheduled date and time in "YYYY-MM-DD HH:MM" format (string)
-/// - `status`: Status of the post (string, typically "pending")
+/// - `status`: Initial status of the post: "draft", "pending_approval" or "scheduled" (optional, defaults to "scheduled")
+///
+/// The optional `X-Actor` header names who created the post; it is recorded with the initial
+/// status in the post's `status_history` (defaults to "web").
+///
+/// A post created with any other status is rejected with 422 Unprocessable Entity, and so is a
+/// "scheduled" post whose time is before the current minute.
+///
+/// A "scheduled" post must respect the pacing rule (`scheduler.pacing` or
+/// `scheduler.pacing_accounts`) of every account it is published on behalf of, its `author`
+/// and those of its targets, with respect to each account's other scheduled and published
+/// posts. Otherwise it is rejected with 409 Conflict and a problem-details body giving the
+/// reason and the next times it could be scheduled at:
+///
+/// ```json
+/// {
+///   "type": "about:blank",
+///   "title": "Conflict",
+///   "status": 409,
+///   "detail": "This account already has 2 posts that day",
+///   "suggested_times": ["2024-05-11 09:00"]
+/// }
+/// ```
+///
+/// A post scheduled during a blackout window (`scheduler.blackouts`) is still accepted, but
+/// the response carries a `Warning` header saying when the scheduler will publish it instead.
 ///
 /// # Logging
 ///
 /// The function uses logging macros to record important information:
 /// - Reception of a new post for scheduling.
-/// - Date converted to Brazil timezone.
-/// - Timestamp in milliseconds being stored.
+/// - Local date/time and the UTC instant being stored.
 ///
 /// # Example Usage
 ///
 /// ```rust
-/// let schedule_route = schedule_post(posts.clone());
+/// let schedule_route = schedule_post_route(posts.clone(), settings.clone(), Arc::new(SystemClock));
 /// let routes = schedule_route.or(other_routes);
 /// warp::serve(routes).run(([127, 0, 0, 1], 8080)).await;
 /// ```
 ///
-/// Where `posts` is a shared `Arc<Collection<Document>>` and `other_routes`
+/// Where `posts` is a shared `Arc` of a `PostRepository` and `other_routes`
 /// are other routes defined in your web server.
-pub fn schedule_post_route(
-    posts: Arc<mongodb::Collection<mongodb::bson::Document>>,
+pub fn schedule_post_route<R: PostRepository + ?Sized + 'static>(
+    posts: Arc<R>,
+    settings: SharedSettings,
+    clock: Arc<dyn Clock>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path("schedule"))
         .and(warp::body::json())
-        .and_then(move |post: Post| {
+        .and(actor())
+        .and_then(move |post: PostRequest, actor: String| {
             let posts = Arc::clone(&posts);
+            let current = Arc::clone(&settings.borrow());
+            let offset = current.timezone();
+            let now = clock.now();
             async move {
                 info!("Receiving a new post for scheduling: {:?}", post);
 
-                let naive_date = NaiveDateTime::parse_from_str(&post.scheduled_time, "%Y-%m-%d %H:%M")
-                    .map_err(|e| {
-                        error!("Error parsing date: {}", e);
-                        warp::reject::custom(ParseDateError)
-                    })?;
+                // Every invalid field is reported at once, so that the form can flag them all.
+                let mut errors = required_fields(&post);
+                if !post.status.is_initial() {
+                    errors.push(FieldError::new("status", format!("A new post cannot have status {}", post.status)));
+                }
+                let author = post.author.as_deref().map(parse_account).transpose().unwrap_or_else(|e| {
+                    errors.push(FieldError::new("author", e));
+                    None
+                });
+                let targets = post_targets(&post, &channel_names(&current)).unwrap_or_else(|error| {
+                    errors.push(error);
+                    Vec::new()
+                });
+                let utc_date = parse_scheduled_time(&post, offset).map_err(|error| errors.push(error)).ok();
+                if let Some(utc_date) = utc_date.filter(|_| post.status == PostStatus::Scheduled) {
+                    if let Some(message) = past_time_error(&current, utc_date, now) {
+                        errors.push(FieldError::new("scheduled_time", message));
+                    }
+                }
+                let utc_date = match utc_date {
+                    Some(utc_date) if errors.is_empty() => utc_date,
+                    _ => {
+                        let error = ApiError::invalid(errors);
+                        error!("Cannot create the post: {}", error);
+                        return Ok::<_, Rejection>(error.into_response());
+                    }
+                };
 
-                let brazil_offset = get_timezone_offset().map_err(|e| {
-                    error!("Error getting timezone offset: {}", e);
-                    warp::reject::custom(ParseDateError)
-                })?;
-                let brazil_date: DateTime<_> = brazil_offset.from_local_datetime(&naive_date).unwrap();
+                info!("Local time {} ({}) stored as UTC {}", post.scheduled_time, offset, utc_date);
 
-                info!("Date in Brazil time: {}", brazil_date);
+                let scheduled_time = BsonDateTime::from_chrono(utc_date);
 
-                let milliseconds = brazil_date.timestamp_millis();
+                let mut new_post = Post::new(post.title, post.content, scheduled_time, now);
+                new_post.status = post.status;
+                new_post.author = author;
+                new_post.targets = targets;
+                new_post.catch_up_policy = post.catch_up_policy;
+                new_post.status_history.push(StatusTransition::new(None, post.status, &actor, now));
 
-                info!("Date stored as milliseconds: {}", milliseconds);
+                // The rules of every account the post goes out on apply, not only its author's.
+                if post.status == PostStatus::Scheduled {
+                    let pacing =
+                        PostPacing::load(posts.as_ref(), &new_post, &current.scheduler, SCHEDULING_STATUSES, utc_date)
+                            .await;
+                    let pacing = match pacing {
+                        Ok(pacing) => pacing,
+                        Err(e) => {
+                            error!("Error querying posts: {}", e);
+                            let error = ApiError::Internal("Failed to check the pacing rule".to_string());
+                            return Ok::<_, Rejection>(error.into_response());
+                        }
+                    };
 
-                let doc = doc! {
-                    "title": post.title,
-                    "content": post.content,
-                    "scheduled_time": Bson::Int64(milliseconds),
-                    "status": post.status,
-                };
-                posts.insert_one(doc).await.map_err(|e| {
+                    if let Err(violation) = pacing.check(utc_date, offset) {
+                        info!("Rejected post at {}: {}", utc_date, violation);
+                        let suggested_times = pacing
+                            .suggest(utc_date, offset, SUGGESTED_TIMES)
+                            .into_iter()
+                            .map(|time| time.with_timezone(&offset).format("%Y-%m-%d %H:%M").to_string())
+                            .collect();
+                        let conflict = ApiError::Conflict {
+                            detail: violation.to_string(),
+                            suggested_times,
+                        };
+                        return Ok::<_, Rejection>(conflict.into_response());
+                    }
+                }
+
+                if let Err(e) = posts.insert(new_post).await {
                     error!("Error inserting post: {}", e);
-                    warp::reject::custom(ParseDateError)
-                })?;
-                Ok::<_, Rejection>(warp::reply::with_status(
-                    "Post scheduled",
-                    warp::http::StatusCode::OK,
+                    let error = ApiError::Internal("Failed to schedule the post".to_string());
+                    return Ok::<_, Rejection>(error.into_response());
+                }
+                let warning = (post.status == PostStatus::Scheduled)
+                    .then(|| blackout_warning(&current, utc_date))
+                    .flatten();
+                Ok::<_, Rejection>(with_warning(
+                    warp::reply::with_status("Post scheduled".to_string(), warp::http::StatusCode::OK).into_response(),
+                    warning,
                 ))
             }
         })
@@ -255,13 +583,16 @@ pub fn schedule_post_route(
 /// This function sets up an HTTP GET route for `/posts` that retrieves scheduled posts
 /// within a specified date range. The process includes:
 ///
-/// 1. **Date Range Parsing**: Converts start and end date strings to millisecond timestamps.
-/// 2. **Timezone Conversion**: Adjusts timestamps to the local timezone.
-/// 3. **Database Query**: Retrieves posts from MongoDB based on the date range and status.
+/// 1. **Date Range Parsing**: Converts start and end date strings to BSON datetimes.
+/// 2. **Timezone Conversion**: Interprets the day boundaries in the configured timezone and
+///    converts them to UTC instants.
+/// 3. **Database Query**: Retrieves the posts in the `scheduled` status within the date range from MongoDB.
 ///
 /// # Parameters
 ///
-/// - `posts`: A shared `Arc<Collection<Document>>` for concurrent access to the MongoDB collection.
+/// - `posts`: A shared `Arc` of the `PostRepository` storing the posts (MongoDB in production,
+///   in memory in tests).
+/// - `settings`: The shared settings; the day boundaries are expressed in their current timezone offset.
 ///
 /// # Returns
 ///
@@ -283,71 +614,63 @@ pub fn schedule_post_route(
 /// # Logging
 ///
 /// Logs the following information:
-/// - Query date range in milliseconds.
+/// - Query date range.
 /// - Any errors encountered during the process.
 ///
 /// # Example Usage
 ///
 /// ```rust
-/// let query_route = query_posts(posts.clone());
+/// let query_route = query_posts(posts.clone(), settings.clone());
 /// let routes = query_route.or(other_routes);
 /// warp::serve(routes).run(([127, 0, 0, 1], 8080)).await;
 /// ```
-pub fn query_posts_route(
-    posts: Arc<mongodb::Collection<mongodb::bson::Document>>,
+pub fn query_posts_route<R: PostRepository + ?Sized + 'static>(
+    posts: Arc<R>,
+    settings: SharedSettings,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::get()
         .and(warp::path("posts"))
         .and(warp::query::<PostQueryParams>())
         .and_then(move |params: PostQueryParams| {
             let posts = Arc::clone(&posts);
+            let offset = settings.borrow().timezone();
             async move {
-                let to_millis = |date_str: &str, is_end_of_day: bool| -> Result<i64, Rejection> {
+                let to_datetime = |field: &str, date_str: &str, is_end_of_day: bool| {
                     let time_str = if is_end_of_day { "23:59:59" } else { "00:00:00" };
                     let datetime_str = format!("{} {}", date_str, time_str);
-                    let naive_dt = NaiveDateTime::parse_from_str(&datetime_str, "%Y-%m-%d %H:%M:%S")
-                        .map_err(|_| warp::reject::custom(ParseDateError))?;
-
-                    get_timezone_offset()
-                        .map_err(|e| {
-                            error!("Error getting timezone offset: {}", e);
-                            warp::reject::custom(ParseDateError)
-                        })
-                        .and_then(|offset| {
-                            offset.from_local_datetime(&naive_dt)
-                                .single()
-                                .ok_or_else(|| warp::reject::custom(ParseDateError))
-                        })
-                        .map(|local_time| local_time.timestamp_millis())
+                    local_to_utc(&datetime_str, "%Y-%m-%d %H:%M:%S", offset).map_err(|e| {
+                        error!("Error parsing date: {}", e);
+                        FieldError::new(field, format!("Expected a date like 2024-05-10, not {:?}", date_str))
+                    })
                 };
 
-                let start_millis = to_millis(&params.start_date, false)?;
-                let end_millis = to_millis(&params.end_date, true)?;
+                let (start, end) = match (
+                    to_datetime("start_date", &params.start_date, false),
+                    to_datetime("end_date", &params.end_date, true),
+                ) {
+                    (Ok(start), Ok(end)) => (start, end),
+                    (start, end) => {
+                        let errors = [start.err(), end.err()].into_iter().flatten().collect();
+                        return Ok::<_, Rejection>(ApiError::invalid(errors).into_response());
+                    }
+                };
 
-                info!("Querying posts from {} to {}", start_millis, end_millis);
+                info!("Querying posts from {} to {}", start, end);
 
-                let filter = doc! {
-                    "scheduled_time": {
-                        "$gte": Bson::Int64(start_millis),
-                        "$lte": Bson::Int64(end_millis),
-                    },
-                    "status": "pending"
-                };
                 info!("Start date: {}, End date: {}", params.start_date, params.end_date);
-                info!("Start millis: {}, End millis: {}", start_millis, end_millis);
-
-                let mut cursor = posts.find(filter).await.map_err(|e| {
-                    error!("Error querying posts: {}", e);
-                    warp::reject::custom(ParseDateError)
-                })?;
-                let mut results = Vec::new();
-                while let Ok(Some(post)) = cursor.try_next().await {
-                    if let Some(scheduled_time) = post.get("scheduled_time") {
-                        info!("Found post with scheduled_time: {:?}", scheduled_time);
+
+                let results = match posts.find_in_range(start, end, PostStatus::Scheduled).await {
+                    Ok(results) => results,
+                    Err(e) => {
+                        error!("Error querying posts: {}", e);
+                        let error = ApiError::Internal("Failed to query the posts".to_string());
+                        return Ok::<_, Rejection>(error.into_response());
                     }
-                    results.push(post);
+                };
+                for post in &results {
+                    info!("Found post with scheduled_time: {}", post.scheduled_time);
                 }
-                Ok::<_, Rejection>(warp::reply::json(&results))
+                Ok::<_, Rejection>(warp::reply::json(&results).into_response())
             }
         })
 }
@@ -364,7 +687,8 @@ pub fn query_posts_route(
 ///
 /// # Parameters
 ///
-/// - `posts`: A shared `Arc<Collection<Document>>` for concurrent access to the MongoDB collection.
+/// - `posts`: A shared `Arc` of the `PostRepository` storing the posts (MongoDB in production,
+///   in memory in tests).
 ///
 /// # Returns
 ///
@@ -391,20 +715,28 @@ pub fn query_posts_route(
 /// let routes = delete_route.or(other_routes);
 /// warp::serve(routes).run(([127, 0, 0, 1], 8080)).await;
 /// ```
-pub fn delete_post_route(
-    posts: Arc<mongodb::Collection<mongodb::bson::Document>>,
+pub fn delete_post_route<R: PostRepository + ?Sized + 'static>(
+    posts: Arc<R>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::delete()
         .and(warp::path!("posts" / String))
         .and_then(move |id: String| {
             let posts = Arc::clone(&posts);
             async move {
-                let object_id = bson::oid::ObjectId::parse_str(&id).unwrap();
-                posts.delete_one(doc! { "_id": object_id }).await.unwrap();
-                Ok::<_, warp::Rejection>(warp::reply::with_status(
-                    "Post deleted",
-                    warp::http::StatusCode::OK,
-                ))
+                let Ok(object_id) = bson::oid::ObjectId::parse_str(&id) else {
+                    error!("Failed to parse ObjectId from {}", id);
+                    return Ok::<_, Rejection>(ApiError::NotFound("Post not found".to_string()).into_response());
+                };
+                match posts.delete(object_id).await {
+                    Ok(true) => Ok::<_, Rejection>(
+                        warp::reply::with_status("Post deleted", warp::http::StatusCode::OK).into_response(),
+                    ),
+                    Ok(false) => Ok(ApiError::NotFound("Post not found".to_string()).into_response()),
+                    Err(e) => {
+                        error!("Error deleting post {}: {}", id, e);
+                        Ok(ApiError::Internal("Failed to delete the post".to_string()).into_response())
+                    }
+                }
             }
         })
 }
@@ -417,13 +749,19 @@ pub fn delete_post_route(
 /// in the database. The process includes:
 ///
 /// 1. **ID Parsing**: Converts the provided string ID to a MongoDB ObjectId.
-/// 2. **Date Parsing**: Converts the updated scheduled time to a timestamp.
-/// 3. **Timezone Adjustment**: Applies the Brazil timezone offset to the scheduled time.
-/// 4. **Database Update**: Updates the specified post in the MongoDB collection.
+/// 2. **Date Parsing**: Parses the updated scheduled time as local wall-clock time.
+/// 3. **UTC Conversion**: Applies the configured timezone offset and stores the UTC instant.
+/// 4. **Transition Check**: Rejects the update if the requested status is not reachable from
+///    the current status according to the `PostStatus` transition table, or is one only the
+///    scheduler sets.
+/// 5. **Database Update**: Updates the specified post in the MongoDB collection and, when the
+///    status changes, appends the transition (with timestamp and actor) to `status_history`.
 ///
 /// # Parameters
 ///
-/// - `posts`: A shared `Arc<Collection<Document>>` for concurrent access to the MongoDB collection.
+/// - `posts`: A shared `Arc` of the `PostRepository` storing the posts (MongoDB in production,
+///   in memory in tests).
+/// - `settings`: The shared settings; the updated date/time is expressed in their current timezone offset.
 ///
 /// # Returns
 ///
@@ -441,100 +779,1021 @@ pub fn delete_post_route(
 /// - `scheduled_time`: Updated scheduled time (format: "YYYY-MM-DD HH:MM").
 /// - `status`: Updated status of the post.
 ///
+/// The optional `X-Actor` header names who made the change (defaults to "web").
+///
 /// # Errors
 ///
 /// May return a `Rejection` in the following situations:
 /// - Failure to parse the post ID.
 /// - Failure to parse the updated date/time.
-/// - Error in obtaining the timezone offset.
 /// - Failure to update the document in MongoDB.
 ///
 /// # Logging
 ///
 /// Logs the following information:
 /// - Attempt to update a post with a specific ID.
-/// - Converted date in Brazil time.
+/// - Local date/time and the UTC instant being stored.
 /// - Success or failure of the update operation.
 ///
 /// # Responses
 ///
-/// - Returns a 200 OK status with "Post updated" message upon successful update.
+/// - Returns a 200 OK status with "Post updated" message upon successful update. If the post
+///   is scheduled during a blackout window, a `Warning` header says so and gives the time the
+///   scheduler will defer or hold it to.
 /// - Returns a 404 Not Found status if no post matches the given ID.
+/// - Returns a 422 Unprocessable Entity status if the post is moved to a time before the current
+///   minute, or scheduled with such a time. Editing a late post without moving it is allowed.
+/// - Returns a 409 Conflict status if the status transition is not allowed (e.g. `published` to
+///   `scheduled`) or if the post changed status while it was being updated.
+/// - Returns a 409 Conflict status if the post is moved to `publishing`, `published`,
+///   `partially_published` or `missed`: only the scheduler sets these statuses.
+/// - Returns a 409 Conflict status if the post is `publishing`, unless it has been for longer than
+///   `scheduler.shutdown_timeout_secs`: the scheduler that claimed it stopped without releasing it,
+///   and an operator may move it to `scheduled` (at its current time, even if past) or `failed`.
 /// - Returns a 500 Internal Server Error status if the update operation fails.
 ///
 /// # Example Usage
 ///
 /// ```rust
-/// let update_route = update_post(posts.clone());
+/// let update_route = update_post_route(posts.clone(), settings.clone(), Arc::new(SystemClock));
 /// let routes = update_route.or(other_routes);
 /// warp::serve(routes).run(([127, 0, 0, 1], 8080)).await;
 /// ```
-pub fn update_post_route(
-    posts: Arc<mongodb::Collection<mongodb::bson::Document>>,
+pub fn update_post_route<R: PostRepository + ?Sized + 'static>(
+    posts: Arc<R>,
+    settings: SharedSettings,
+    clock: Arc<dyn Clock>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::put()
         .and(warp::path!("posts" / String))
         .and(warp::body::json())
-        .and_then(move |id: String, updated_post: Post| {
+        .and(actor())
+        .and_then(move |id: String, updated_post: PostRequest, actor: String| {
             let posts = Arc::clone(&posts);
+            let current_settings = Arc::clone(&settings.borrow());
+            let offset = current_settings.timezone();
+            let now = clock.now();
             async move {
                 info!("Updating post with ID: {}", id);
 
-                let object_id = bson::oid::ObjectId::parse_str(&id).map_err(|e| {
-                    error!("Failed to parse ObjectId from {}: {:?}", id, e);
-                    warp::reject::custom(ParseDateError)
-                })?;
+                let Ok(object_id) = bson::oid::ObjectId::parse_str(&id) else {
+                    error!("Failed to parse ObjectId from {}", id);
+                    return Ok::<_, Rejection>(ApiError::NotFound("Post not found".to_string()).into_response());
+                };
 
-                let naive_date = NaiveDateTime::parse_from_str(&updated_post.scheduled_time, "%Y-%m-%d %H:%M")
-                    .map_err(|e| {
-                        error!("Error parsing date: {}", e);
-                        warp::reject::custom(ParseDateError)
-                    })?;
+                let mut errors = required_fields(&updated_post);
+                let utc_date = parse_scheduled_time(&updated_post, offset).map_err(|error| errors.push(error)).ok();
+                let utc_date = match utc_date {
+                    Some(utc_date) if errors.is_empty() => utc_date,
+                    _ => {
+                        let error = ApiError::invalid(errors);
+                        error!("Rejected update of post {}: {}", id, error);
+                        return Ok::<_, Rejection>(error.into_response());
+                    }
+                };
 
-                let brazil_offset = get_timezone_offset().map_err(|e| {
-                    error!("Error getting timezone offset: {}", e);
-                    warp::reject::custom(ParseDateError)
-                })?;
+                info!("Local time {} ({}) stored as UTC {}", updated_post.scheduled_time, offset, utc_date);
 
-                let brazil_date: DateTime<_> = brazil_offset.from_local_datetime(&naive_date).unwrap();
+                let current = match posts.find(object_id).await {
+                    Ok(Some(current)) => current,
+                    Ok(None) => {
+                        error!("No post found with ID {}", id);
+                        return Ok::<_, Rejection>(ApiError::NotFound("Post not found".to_string()).into_response());
+                    }
+                    Err(e) => {
+                        error!("Failed to read post with ID {}: {:?}", id, e);
+                        let error = ApiError::Internal("Failed to update post".to_string());
+                        return Ok::<_, Rejection>(error.into_response());
+                    }
+                };
 
-                info!("Date in Brazil time: {}", brazil_date);
+                if let Err(e) = current.status.transition_to(updated_post.status) {
+                    error!("Rejected update of post {}: {}", id, e);
+                    return Ok::<_, Rejection>(ApiError::conflict(e.to_string()).into_response());
+                }
 
-                let milliseconds = brazil_date.timestamp_millis();
+                // Otherwise a client could claim a post so that it is never sent, or record an
+                // outcome the channels never gave. This also leaves an abandoned post only
+                // "scheduled" and "failed" to go to.
+                if current.status != updated_post.status && updated_post.status.is_set_by_scheduler() {
+                    let message = format!("Only the scheduler may move a post to {}", updated_post.status.as_str());
+                    error!("Rejected update of post {}: {}", id, message);
+                    return Ok::<_, Rejection>(ApiError::conflict(message).into_response());
+                }
+
+                let timeout = TimeDelta::seconds(current_settings.scheduler.shutdown_timeout_secs as i64);
+                let abandoned = current.is_abandoned(now, timeout);
+                if current.status == PostStatus::Publishing && !abandoned {
+                    error!("Rejected update of post {}: it is being published", id);
+                    let error = ApiError::conflict("Post is being published, please retry once it is done");
+                    return Ok::<_, Rejection>(error.into_response());
+                }
 
-                let update_doc = doc! {
-                    "$set": {
-                        "title": updated_post.title,
-                        "content": updated_post.content,
-                        "scheduled_time": Bson::Int64(milliseconds),
-                        "status": updated_post.status,
+                // Typed times have no seconds, so an unchanged time may differ from a stored one
+                // set by the scheduler (e.g. a catch-up) by less than a minute. An abandoned post
+                // keeps its time when released: its catch-up policy decides whether it is too late.
+                let stored_minute = current.scheduled_time.to_chrono().duration_trunc(TimeDelta::minutes(1)).ok();
+                let moved = stored_minute != Some(utc_date) || (current.status != updated_post.status && !abandoned);
+                if updated_post.status == PostStatus::Scheduled && moved {
+                    if let Some(message) = past_time_error(&current_settings, utc_date, now) {
+                        error!("Rejected update of post {}: {}", id, message);
+                        let error = ApiError::invalid_field("scheduled_time", message);
+                        return Ok::<_, Rejection>(error.into_response());
                     }
-                };
+                }
+
+                let previous_status = current.status;
+                let mut post = current;
+                post.title = updated_post.title;
+                post.content = updated_post.content;
+                post.scheduled_time = BsonDateTime::from_chrono(utc_date);
+                post.updated_at = Some(BsonDateTime::from_chrono(now));
+                if updated_post.catch_up_policy.is_some() {
+                    post.catch_up_policy = updated_post.catch_up_policy;
+                }
+                if previous_status != updated_post.status {
+                    post.status = updated_post.status;
+                    post.status_history.push(StatusTransition::new(
+                        Some(previous_status),
+                        updated_post.status,
+                        &actor,
+                        now,
+                    ));
+                }
 
-                match posts.update_one(doc! { "_id": object_id }, update_doc).await {
-                    Ok(update_result) => {
-                        if update_result.matched_count > 0 {
+                // Requiring the previous status makes a concurrent status change (for
+                // example the scheduler claiming the post) fail the update instead of
+                // being overwritten.
+                match posts.update(&post, previous_status).await {
+                    Ok(updated) => {
+                        if updated {
                             info!("Post with ID {} updated successfully", id);
-                            Ok::<_, Rejection>(warp::reply::with_status(
-                                "Post updated",
-                                warp::http::StatusCode::OK,
+                            let warning = (post.status == PostStatus::Scheduled)
+                                .then(|| blackout_warning(&current_settings, utc_date))
+                                .flatten();
+                            Ok::<_, Rejection>(with_warning(
+                                warp::reply::with_status("Post updated".to_string(), warp::http::StatusCode::OK)
+                                    .into_response(),
+                                warning,
                             ))
                         } else {
-                            error!("No post found with ID {}", id);
-                            Ok::<_, Rejection>(warp::reply::with_status(
-                                "Post not found",
-                                warp::http::StatusCode::NOT_FOUND,
-                            ))
+                            error!("Post with ID {} changed status during the update", id);
+                            let error = ApiError::conflict("Post was modified concurrently, please retry");
+                            Ok::<_, Rejection>(error.into_response())
                         }
                     }
                     Err(e) => {
                         error!("Failed to update post with ID {}: {:?}", id, e);
-                        Ok::<_, Rejection>(warp::reply::with_status(
-                            "Failed to update post",
-                            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                        ))
+                        Ok::<_, Rejection>(ApiError::Internal("Failed to update post".to_string()).into_response())
+                    }
+                }
+            }
+        })
+}
+
+/// Configures the route listing the publishing pauses.
+///
+/// # Functionality
+///
+/// This function sets up an HTTP GET route for `/admin/pause` returning the stored pauses as a
+/// JSON array, empty when publishing is not paused. Each pause has its `_id` scope (`"global"`
+/// or `"account:<author>"`), `reason`, `actor` and `paused_at`.
+///
+/// The request must carry one of the `http.admin_tokens` of `settings` in an
+/// `Authorization: Bearer <token>` header.
+///
+/// # Errors
+///
+/// Returns a 401 Unauthorized status without a valid admin token, and a 500 Internal Server
+/// Error status if the pauses cannot be read.
+pub fn list_pauses_route<P: PauseRepository + ?Sized + 'static>(
+    pauses: Arc<P>,
+    settings: SharedSettings,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("admin" / "pause"))
+        .and(admin(settings))
+        .and_then(move |_operator: String| {
+            let pauses = Arc::clone(&pauses);
+            async move {
+                match pauses.list().await {
+                    Ok(pauses) => Ok::<_, Rejection>(warp::reply::json(&pauses).into_response()),
+                    Err(e) => {
+                        error!("Failed to read the pauses: {}", e);
+                        Ok::<_, Rejection>(ApiError::Internal("Failed to read the pauses".to_string()).into_response())
+                    }
+                }
+            }
+        })
+}
+
+/// Configures the emergency route pausing publishing.
+///
+/// # Functionality
+///
+/// This function sets up an HTTP POST route for `/admin/pause` that stores a pause, globally
+/// or for the posts of one `account` (a post's `author`). The scheduler reads the pauses before
+/// claiming each post, so no post in the scope of the pause is published from then on; posts
+/// already being published are not interrupted. Pending posts are left untouched and are
+/// published when publishing resumes; if they are late by then, their catch-up policy applies.
+///
+/// Pauses are stored in the database, so restarting the scheduler or the web server does not
+/// resume publishing. Pausing again replaces the reason and actor of the existing pause.
+///
+/// # Request Body
+///
+/// - `reason`: Why publishing is paused (string, required).
+/// - `account`: The account to pause (string, optional; every account when omitted), written
+///   like a post's `author` (see `parse_account`).
+///
+/// The request must carry one of the `http.admin_tokens` of `settings` in an
+/// `Authorization: Bearer <token>` header; the name of the token is recorded as the actor of
+/// the pause.
+///
+/// # Responses
+///
+/// - Returns a 200 OK status with the stored pause as JSON.
+/// - Returns a 401 Unauthorized status without a valid admin token.
+/// - Returns a 422 Unprocessable Entity status if the reason is empty or the account invalid.
+/// - Returns a 500 Internal Server Error status if the pause cannot be stored.
+///
+/// # Example Usage
+///
+/// ```bash
+/// curl -X POST http://localhost:8080/admin/pause -H "Authorization: Bearer $ADMIN_TOKEN" \
+///      -H "Content-Type: application/json" -d '{"reason": "Incident in progress"}'
+/// ```
+pub fn pause_route<P: PauseRepository + ?Sized + 'static>(
+    pauses: Arc<P>,
+    settings: SharedSettings,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("admin" / "pause"))
+        .and(admin(settings))
+        .and(warp::body::json())
+        .and_then(move |actor: String, request: PauseRequest| {
+            let pauses = Arc::clone(&pauses);
+            async move {
+                if request.reason.trim().is_empty() {
+                    let error = ApiError::invalid_field("reason", "A reason is required to pause publishing");
+                    return Ok::<_, Rejection>(error.into_response());
+                }
+                let account = match request.account.as_deref().map(parse_account).transpose() {
+                    Ok(account) => account,
+                    Err(e) => return Ok::<_, Rejection>(ApiError::invalid_field("account", e).into_response()),
+                };
+
+                let pause = Pause::new(account.as_deref(), &request.reason, &actor);
+                match pauses.put(pause.clone()).await {
+                    Ok(()) => {
+                        warn!("Publishing paused ({}) by {}: {}", pause.scope, pause.actor, pause.reason);
+                        Ok::<_, Rejection>(warp::reply::json(&pause).into_response())
+                    }
+                    Err(e) => {
+                        error!("Failed to store the pause: {}", e);
+                        Ok::<_, Rejection>(ApiError::Internal("Failed to pause publishing".to_string()).into_response())
                     }
                 }
             }
         })
-}
\ No newline at end of file
+}
+
+/// Configures the route resuming publishing after a pause.
+///
+/// # Functionality
+///
+/// This function sets up an HTTP POST route for `/admin/resume` that removes the global pause,
+/// or the pause of one `account`. Lifting the global pause does not lift account pauses. The
+/// scheduler notices the resume within `scheduler.poll_interval_secs`.
+///
+/// # Request Body
+///
+/// - `account`: The account to resume (string, optional; the global pause when omitted), written
+///   like a post's `author` (see `parse_account`).
+///
+/// The request must carry one of the `http.admin_tokens` of `settings` in an
+/// `Authorization: Bearer <token>` header; the name of the token is logged as who resumed
+/// publishing.
+///
+/// # Responses
+///
+/// - Returns a 200 OK status with the message "Publishing resumed".
+/// - Returns a 401 Unauthorized status without a valid admin token.
+/// - Returns a 404 Not Found status if there is no such pause.
+/// - Returns a 422 Unprocessable Entity status if the account is invalid.
+/// - Returns a 500 Internal Server Error status if the pause cannot be removed.
+pub fn resume_route<P: PauseRepository + ?Sized + 'static>(
+    pauses: Arc<P>,
+    settings: SharedSettings,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("admin" / "resume"))
+        .and(admin(settings))
+        .and(warp::body::json())
+        .and_then(move |actor: String, request: ResumeRequest| {
+            let pauses = Arc::clone(&pauses);
+            async move {
+                let account = match request.account.as_deref().map(parse_account).transpose() {
+                    Ok(account) => account,
+                    Err(e) => return Ok::<_, Rejection>(ApiError::invalid_field("account", e).into_response()),
+                };
+                let scope = Pause::scope(account.as_deref());
+                match pauses.remove(&scope).await {
+                    Ok(true) => {
+                        warn!("Publishing resumed ({}) by {}", scope, actor);
+                        let reply = warp::reply::with_status("Publishing resumed", warp::http::StatusCode::OK);
+                        Ok::<_, Rejection>(reply.into_response())
+                    }
+                    Ok(false) => Ok(ApiError::NotFound("Publishing is not paused".to_string()).into_response()),
+                    Err(e) => {
+                        error!("Failed to remove the pause {}: {}", scope, e);
+                        Ok(ApiError::Internal("Failed to resume publishing".to_string()).into_response())
+                    }
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use linkedin_automation::clock::TestClock;
+    use linkedin_automation::config::reload::fixed;
+    use linkedin_automation::config::secret::Secret;
+    use linkedin_automation::problem::{Problem, PROBLEM_CONTENT_TYPE};
+    use linkedin_automation::repository::memory::{InMemoryPauseRepository, InMemoryPostRepository};
+    use linkedin_automation::scheduling::blackout::BlackoutWindow;
+    use linkedin_automation::scheduling::pacing::PacingRule;
+    use std::sync::Once;
+    use warp::http::header::CONTENT_TYPE;
+
+    static INIT: Once = Once::new();
+
+    /// Initializes the test environment by configuring the logger.
+    fn initialize() {
+        INIT.call_once(|| {
+            let _ = env_logger::builder().is_test(true).try_init();
+        });
+    }
+
+    /// A clock at 2024-05-01 00:00 UTC, before the dates the tests schedule posts at.
+    fn clock() -> Arc<dyn Clock> {
+        Arc::new(TestClock::new(Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()))
+    }
+
+    /// Returns the problem-details body of an error response.
+    fn problem(response: &warp::http::Response<warp::hyper::body::Bytes>) -> Problem {
+        serde_json::from_slice(response.body()).unwrap()
+    }
+
+    /// Settings interpreting local dates in Brasília time (UTC-3).
+    fn brasilia() -> SharedSettings {
+        fixed(Settings {
+            timezone_offset: -3,
+            ..Settings::default()
+        })
+    }
+
+    /// Tests that a scheduled post is stored in UTC with its initial status recorded.
+    ///
+    /// 09:00 in Brasília time must be stored as 12:00 UTC, and the `X-Actor` header must be
+    /// recorded in the status history.
+    #[tokio::test]
+    async fn test_schedule_post() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let route = schedule_post_route(Arc::clone(&posts), brasilia(), clock());
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/schedule")
+            .header("x-actor", "alice")
+            .json(&serde_json::json!({
+                "title": "Title",
+                "content": "Content",
+                "scheduled_time": "2024-05-10 09:00",
+            }))
+            .reply(&route)
+            .await;
+
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let stored = posts.all();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(
+            stored[0].scheduled_time.to_chrono(),
+            Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap()
+        );
+        assert_eq!(stored[0].status, PostStatus::Scheduled);
+        assert_eq!(stored[0].status_history.len(), 1);
+        assert_eq!(stored[0].status_history[0].actor, "alice");
+        assert_eq!(stored[0].targets, vec![Target::new(DEFAULT_CHANNEL)]);
+    }
+
+    /// Tests that a post can only target available channels.
+    #[tokio::test]
+    async fn test_schedule_post_channels() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let route = schedule_post_route(Arc::clone(&posts), brasilia(), clock());
+        let schedule = |channels: serde_json::Value| {
+            warp::test::request().method("POST").path("/schedule").json(&serde_json::json!({
+                "title": "Title",
+                "content": "Content",
+                "scheduled_time": "2024-05-10 09:00",
+                "channels": channels,
+            }))
+        };
+
+        let response = schedule(serde_json::json!(["myspace"])).reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(problem(&response).errors, vec![FieldError::new("channels", "Unknown channel myspace")]);
+        let response = schedule(serde_json::json!([])).reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(posts.all().is_empty());
+
+        let response = schedule(serde_json::json!(["linkedin"])).reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert_eq!(posts.all()[0].targets, vec![Target::new("linkedin")]);
+    }
+
+    /// Tests that a post can be cross-posted with a different account, text and hashtags per
+    /// target, and that a target cannot be listed twice.
+    ///
+    /// Accounts are stored as their account keys: a member URN as the bare profile ID, an
+    /// organization URN as is, so that the same member written both ways is listed twice.
+    #[tokio::test]
+    async fn test_schedule_post_targets() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let route = schedule_post_route(Arc::clone(&posts), brasilia(), clock());
+        let schedule = |targets: serde_json::Value| {
+            warp::test::request().method("POST").path("/schedule").json(&serde_json::json!({
+                "title": "Hiring",
+                "content": "I am hiring!",
+                "scheduled_time": "2024-05-10 09:00",
+                "targets": targets,
+            }))
+        };
+
+        let twice = serde_json::json!([{ "channel": "linkedin" }, { "channel": "linkedin" }]);
+        let response = schedule(twice).reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(problem(&response).errors, vec![FieldError::new("targets", "Target linkedin is listed twice")]);
+
+        let aliases = serde_json::json!([
+            { "channel": "linkedin", "author": "alice" },
+            { "channel": "linkedin", "author": "urn:li:person:alice" },
+        ]);
+        let response = schedule(aliases).reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        let response = schedule(serde_json::json!([{ "channel": "linkedin", "author": "urn:li:group:1" }]))
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(problem(&response).errors[0].field, "targets");
+
+        let targets = serde_json::json!([
+            { "channel": "linkedin" },
+            { "channel": "linkedin", "author": "company", "content": "We are hiring!", "hashtags": ["#jobs", "rust"] },
+            { "channel": "linkedin", "author": "urn:li:person:bob" },
+            { "channel": "linkedin", "author": "urn:li:organization:2414183" },
+        ]);
+        let response = schedule(targets).reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let stored = &posts.all()[0];
+        assert_eq!(stored.targets[0], Target::new("linkedin"));
+        assert_eq!(stored.targets[1].author.as_deref(), Some("company"));
+        assert_eq!(stored.targets[1].text(&stored.content), "We are hiring!\n\n#jobs #rust");
+        assert_eq!(stored.targets[2].author.as_deref(), Some("bob"));
+        assert_eq!(stored.targets[3].author.as_deref(), Some("urn:li:organization:2414183"));
+    }
+
+    /// Tests that a post can be scheduled with its own catch-up policy.
+    #[tokio::test]
+    async fn test_schedule_post_with_catch_up_policy() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let route = schedule_post_route(Arc::clone(&posts), brasilia(), clock());
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/schedule")
+            .json(&serde_json::json!({
+                "title": "Title",
+                "content": "Content",
+                "scheduled_time": "2024-05-10 09:00",
+                "catch_up_policy": { "policy": "publish_if_within", "max_delay_minutes": 30 },
+            }))
+            .reply(&route)
+            .await;
+
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert_eq!(
+            posts.all()[0].catch_up_policy,
+            Some(CatchUpPolicy::PublishIfWithin { max_delay_minutes: 30 })
+        );
+    }
+
+    /// Tests that a post breaking the pacing rule of its account is rejected with suggestions.
+    ///
+    /// With at most one post a day for alice, a second post on 10 May is rejected with 409 and
+    /// the same local time on the following days suggested, while bob can still post that day,
+    /// but not with a target on alice's account.
+    #[tokio::test]
+    async fn test_schedule_post_enforces_pacing() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let mut settings = Settings {
+            timezone_offset: -3,
+            ..Settings::default()
+        };
+        settings.scheduler.pacing_accounts.insert(
+            "alice".to_string(),
+            PacingRule {
+                max_per_day: Some(1),
+                ..PacingRule::default()
+            },
+        );
+        let route = schedule_post_route(Arc::clone(&posts), fixed(settings), clock());
+
+        let schedule = |author: &str, scheduled_time: &str| {
+            warp::test::request()
+                .method("POST")
+                .path("/schedule")
+                .json(&serde_json::json!({
+                    "title": "Title",
+                    "content": "Content",
+                    "scheduled_time": scheduled_time,
+                    "author": author,
+                }))
+                .reply(&route)
+        };
+
+        assert_eq!(schedule("alice", "2024-05-10 09:00").await.status(), warp::http::StatusCode::OK);
+        assert_eq!(schedule("bob", "2024-05-10 10:00").await.status(), warp::http::StatusCode::OK);
+
+        let response = schedule("alice", "2024-05-10 18:00").await;
+        assert_eq!(response.status(), warp::http::StatusCode::CONFLICT);
+        assert_eq!(
+            problem(&response).suggested_times,
+            vec!["2024-05-11 18:00", "2024-05-12 18:00", "2024-05-13 18:00"]
+        );
+        assert_eq!(posts.all().len(), 2);
+        assert_eq!(posts.all()[0].author.as_deref(), Some("alice"));
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/schedule")
+            .json(&serde_json::json!({
+                "title": "Title",
+                "content": "Content",
+                "scheduled_time": "2024-05-10 19:00",
+                "author": "bob",
+                "targets": [{ "channel": "linkedin" }, { "channel": "linkedin", "author": "alice" }],
+            }))
+            .reply(&route)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::CONFLICT);
+        assert_eq!(posts.all().len(), 2);
+    }
+
+    /// Tests that scheduling or moving a post into a blackout window succeeds with a warning.
+    #[tokio::test]
+    async fn test_blackout_warning() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let mut settings = Settings {
+            timezone_offset: -3,
+            ..Settings::default()
+        };
+        settings.scheduler.blackouts.push(BlackoutWindow::QuietHours {
+            name: None,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            days: Vec::new(),
+        });
+        let settings = fixed(settings);
+        let schedule = schedule_post_route(Arc::clone(&posts), settings.clone(), clock());
+        let update = update_post_route(Arc::clone(&posts), settings, clock());
+
+        let body = |scheduled_time: &str| {
+            serde_json::json!({
+                "title": "Title",
+                "content": "Content",
+                "scheduled_time": scheduled_time,
+            })
+        };
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/schedule")
+            .json(&body("2024-05-10 12:00"))
+            .reply(&schedule)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert!(response.headers().get(WARNING).is_none());
+
+        let id = posts.all()[0].id.unwrap();
+        let response = warp::test::request()
+            .method("PUT")
+            .path(&format!("/posts/{}", id))
+            .json(&body("2024-05-10 23:30"))
+            .reply(&update)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert_eq!(
+            response.headers().get(WARNING).unwrap(),
+            "199 - \"2024-05-10 23:30 falls in quiet hours 22:00-07:00; the post will be deferred to 2024-05-11 07:00\""
+        );
+    }
+
+    /// Settings accepting the admin token of alice.
+    fn admin_settings() -> SharedSettings {
+        let mut settings = Settings::default();
+        settings.http.admin_tokens.insert("alice".to_string(), Secret::new("alice-token"));
+        fixed(settings)
+    }
+
+    /// Tests pausing and resuming publishing, globally and for one account.
+    ///
+    /// The pauses are recorded with the operator of the admin token as actor, whatever the
+    /// `X-Actor` header says.
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        initialize();
+
+        let pauses = Arc::new(InMemoryPauseRepository::new());
+        let settings = admin_settings();
+        let routes = list_pauses_route(Arc::clone(&pauses), settings.clone())
+            .or(pause_route(Arc::clone(&pauses), settings.clone()))
+            .or(resume_route(Arc::clone(&pauses), settings));
+        let post = |path: &str, body: serde_json::Value| {
+            warp::test::request()
+                .method("POST")
+                .path(path)
+                .header("authorization", "Bearer alice-token")
+                .header("x-actor", "mallory")
+                .json(&body)
+                .reply(&routes)
+        };
+
+        let response = post("/admin/pause", serde_json::json!({ "reason": "Incident" })).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let response = post("/admin/pause", serde_json::json!({ "reason": "Rebranding", "account": "company" })).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let response = post("/admin/pause", serde_json::json!({ "reason": " " })).await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response = warp::test::request()
+            .method("GET")
+            .path("/admin/pause")
+            .header("authorization", "Bearer alice-token")
+            .reply(&routes)
+            .await;
+        let listed: Vec<Pause> = serde_json::from_slice(response.body()).unwrap();
+        let listed: Vec<_> = listed
+            .iter()
+            .map(|pause| (pause.scope.as_str(), pause.reason.as_str(), pause.actor.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![("account:company", "Rebranding", "alice"), ("global", "Incident", "alice")]
+        );
+
+        assert_eq!(post("/admin/resume", serde_json::json!({})).await.status(), warp::http::StatusCode::OK);
+        assert_eq!(post("/admin/resume", serde_json::json!({})).await.status(), warp::http::StatusCode::NOT_FOUND);
+        assert_eq!(pauses.list().await.unwrap().len(), 1);
+
+        let invalid = serde_json::json!({ "reason": "Legal", "account": "urn:li:group:1" });
+        let response = post("/admin/pause", invalid).await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        let response = post("/admin/resume", serde_json::json!({ "account": "urn:li:person:company" })).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert!(pauses.list().await.unwrap().is_empty());
+    }
+
+    /// Tests that the `/admin` routes answer 401 Unauthorized without a valid admin token, and
+    /// leave the pauses untouched.
+    #[tokio::test]
+    async fn test_admin_routes_require_a_token() {
+        initialize();
+
+        let pauses = Arc::new(InMemoryPauseRepository::new());
+        let settings = admin_settings();
+        let routes = list_pauses_route(Arc::clone(&pauses), settings.clone())
+            .or(pause_route(Arc::clone(&pauses), settings.clone()))
+            .or(resume_route(Arc::clone(&pauses), settings))
+            .recover(recover);
+
+        for authorization in [None, Some("Bearer bob-token"), Some("alice-token"), Some("Bearer ")] {
+            for (method, path) in [("GET", "/admin/pause"), ("POST", "/admin/pause"), ("POST", "/admin/resume")] {
+                let mut request = warp::test::request()
+                    .method(method)
+                    .path(path)
+                    .header("x-actor", "alice")
+                    .json(&serde_json::json!({ "reason": "Incident" }));
+                if let Some(authorization) = authorization {
+                    request = request.header("authorization", authorization);
+                }
+                let response = request.reply(&routes).await;
+                assert_eq!(response.status(), warp::http::StatusCode::UNAUTHORIZED, "{} {}", method, path);
+                assert_eq!(problem(&response).detail, "A valid admin token is required");
+            }
+        }
+        assert!(pauses.list().await.unwrap().is_empty());
+    }
+
+    /// Tests that a post cannot be created directly in a status reserved for the scheduler.
+    #[tokio::test]
+    async fn test_schedule_post_rejects_published_status() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let route = schedule_post_route(Arc::clone(&posts), brasilia(), clock());
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/schedule")
+            .json(&serde_json::json!({
+                "title": "Title",
+                "content": "Content",
+                "scheduled_time": "2024-05-10 09:00",
+                "status": "published",
+            }))
+            .reply(&route)
+            .await;
+
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(posts.all().is_empty());
+    }
+
+    /// Tests that `PUT /posts/{id}` enforces the status transition table.
+    ///
+    /// A published post cannot go back to "scheduled", while a scheduled post can be
+    /// moved to "draft", which is recorded in its status history.
+    #[tokio::test]
+    async fn test_update_post_enforces_transitions() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let route = update_post_route(Arc::clone(&posts), brasilia(), clock());
+
+        let now = Utc::now();
+        let mut published = Post::new("Published".to_string(), "Content".to_string(), BsonDateTime::now(), now);
+        published.status = PostStatus::Published;
+        let published_id = posts.insert(published).await.unwrap();
+        let scheduled_id = posts
+            .insert(Post::new("Scheduled".to_string(), "Content".to_string(), BsonDateTime::now(), now))
+            .await
+            .unwrap();
+
+        let update = |id: bson::oid::ObjectId, status: &str| {
+            warp::test::request()
+                .method("PUT")
+                .path(&format!("/posts/{}", id.to_hex()))
+                .json(&serde_json::json!({
+                    "title": "Edited",
+                    "content": "Edited content",
+                    "scheduled_time": "2024-05-10 09:00",
+                    "status": status,
+                }))
+        };
+
+        let response = update(published_id, "scheduled").reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::CONFLICT);
+        assert_eq!(posts.find(published_id).await.unwrap().unwrap().title, "Published");
+
+        let response = update(scheduled_id, "draft").reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        let edited = posts.find(scheduled_id).await.unwrap().unwrap();
+        assert_eq!(edited.title, "Edited");
+        assert_eq!(edited.status, PostStatus::Draft);
+        assert_eq!(edited.status_history.last().unwrap().actor, DEFAULT_ACTOR);
+    }
+
+    /// Tests that `PUT /posts/{id}` cannot set the statuses only the scheduler sets.
+    ///
+    /// A scheduled post can be neither claimed nor marked as missed, and a post abandoned
+    /// while being published cannot be marked as published: it stays where it was.
+    #[tokio::test]
+    async fn test_update_post_rejects_scheduler_statuses() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 30).unwrap();
+        let route = update_post_route(Arc::clone(&posts), brasilia(), Arc::new(TestClock::new(now)));
+        let scheduled_time = BsonDateTime::from_chrono(Utc.with_ymd_and_hms(2024, 5, 10, 13, 0, 0).unwrap());
+        let scheduled_id = posts
+            .insert(Post::new("Scheduled".to_string(), "Content".to_string(), scheduled_time, now))
+            .await
+            .unwrap();
+        let claimed_at = now - TimeDelta::hours(1);
+        let mut abandoned = Post::new("Abandoned".to_string(), "Content".to_string(), scheduled_time, claimed_at);
+        abandoned.status = PostStatus::Publishing;
+        abandoned.status_history.push(StatusTransition::new(
+            Some(PostStatus::Scheduled),
+            PostStatus::Publishing,
+            "scheduler",
+            claimed_at,
+        ));
+        let abandoned_id = posts.insert(abandoned).await.unwrap();
+
+        let update = |id: bson::oid::ObjectId, status: &str| {
+            warp::test::request()
+                .method("PUT")
+                .path(&format!("/posts/{}", id.to_hex()))
+                .json(&serde_json::json!({
+                    "title": "Edited",
+                    "content": "Content",
+                    "scheduled_time": "2024-05-10 10:00",
+                    "status": status,
+                }))
+        };
+
+        for status in ["publishing", "missed"] {
+            let response = update(scheduled_id, status).reply(&route).await;
+            assert_eq!(response.status(), warp::http::StatusCode::CONFLICT, "{}", status);
+            assert!(problem(&response).detail.contains(status));
+        }
+        let scheduled = posts.find(scheduled_id).await.unwrap().unwrap();
+        assert_eq!((scheduled.status, scheduled.title.as_str()), (PostStatus::Scheduled, "Scheduled"));
+
+        for status in ["published", "partially_published"] {
+            let response = update(abandoned_id, status).reply(&route).await;
+            assert_eq!(response.status(), warp::http::StatusCode::CONFLICT, "{}", status);
+        }
+        assert_eq!(posts.find(abandoned_id).await.unwrap().unwrap().status, PostStatus::Publishing);
+
+        let response = update(abandoned_id, "failed").reply(&route).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert_eq!(posts.find(abandoned_id).await.unwrap().unwrap().status, PostStatus::Failed);
+    }
+
+    /// Tests that a post left "publishing" by a stopped scheduler can be released by an operator.
+    ///
+    /// At 09:00:30 in Brasília time, a post claimed at 08:00 is moved back to "scheduled" at its
+    /// past time, while a post claimed ten seconds ago is still being published and cannot be.
+    #[tokio::test]
+    async fn test_update_abandoned_post() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 30).unwrap();
+        let route = update_post_route(Arc::clone(&posts), brasilia(), Arc::new(TestClock::new(now)));
+        let scheduled_time = BsonDateTime::from_chrono(Utc.with_ymd_and_hms(2024, 5, 10, 11, 0, 0).unwrap());
+        let mut ids = Vec::new();
+        for claimed_at in [scheduled_time.to_chrono(), now - TimeDelta::seconds(10)] {
+            let mut post = Post::new("Publishing".to_string(), "Content".to_string(), scheduled_time, claimed_at);
+            post.status = PostStatus::Publishing;
+            let claim =
+                StatusTransition::new(Some(PostStatus::Scheduled), PostStatus::Publishing, "scheduler", claimed_at);
+            post.status_history.push(claim);
+            ids.push(posts.insert(post).await.unwrap());
+        }
+
+        let mut statuses = Vec::new();
+        for id in &ids {
+            let response = warp::test::request()
+                .method("PUT")
+                .path(&format!("/posts/{}", id.to_hex()))
+                .json(&serde_json::json!({
+                    "title": "Publishing",
+                    "content": "Content",
+                    "scheduled_time": "2024-05-10 08:00",
+                    "status": "scheduled",
+                }))
+                .reply(&route)
+                .await;
+            statuses.push((response.status(), posts.find(*id).await.unwrap().unwrap().status));
+        }
+        assert_eq!(
+            statuses,
+            [
+                (warp::http::StatusCode::OK, PostStatus::Scheduled),
+                (warp::http::StatusCode::CONFLICT, PostStatus::Publishing),
+            ]
+        );
+    }
+
+    /// Tests that posts cannot be scheduled, or moved, before the current minute of the clock.
+    ///
+    /// At 09:00:30 in Brasília time, 09:00 is still accepted but 08:59 is not, unless the post
+    /// is a draft. A late post can be edited as long as it is not moved.
+    #[tokio::test]
+    async fn test_schedule_post_in_the_past() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let clock: Arc<dyn Clock> = Arc::new(TestClock::new(Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 30).unwrap()));
+        let schedule = schedule_post_route(Arc::clone(&posts), brasilia(), Arc::clone(&clock));
+        let update = update_post_route(Arc::clone(&posts), brasilia(), clock);
+        let body = |scheduled_time: &str, status: &str| {
+            serde_json::json!({
+                "title": "Title",
+                "content": "Content",
+                "scheduled_time": scheduled_time,
+                "status": status,
+            })
+        };
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/schedule")
+            .json(&body("2024-05-10 08:59", "scheduled"))
+            .reply(&schedule)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            problem(&response).errors,
+            vec![FieldError::new("scheduled_time", "2024-05-10 08:59 is in the past, it is 2024-05-10 09:00")]
+        );
+        assert!(posts.all().is_empty());
+
+        for (scheduled_time, status) in [("2024-05-10 09:00", "scheduled"), ("2024-05-10 08:59", "draft")] {
+            let response = warp::test::request()
+                .method("POST")
+                .path("/schedule")
+                .json(&body(scheduled_time, status))
+                .reply(&schedule)
+                .await;
+            assert_eq!(response.status(), warp::http::StatusCode::OK);
+        }
+
+        let mut late = Post::new(
+            "Late".to_string(),
+            "Content".to_string(),
+            BsonDateTime::from_chrono(Utc.with_ymd_and_hms(2024, 5, 10, 11, 0, 15).unwrap()),
+            Utc::now(),
+        );
+        late.status = PostStatus::Scheduled;
+        let late_id = posts.insert(late).await.unwrap();
+        let put = |time: &str, status: &str| {
+            warp::test::request()
+                .method("PUT")
+                .path(&format!("/posts/{}", late_id.to_hex()))
+                .json(&body(time, status))
+        };
+
+        assert_eq!(put("2024-05-10 08:00", "scheduled").reply(&update).await.status(), warp::http::StatusCode::OK);
+        let response = put("2024-05-10 07:00", "scheduled").reply(&update).await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(put("2024-05-10 07:00", "draft").reply(&update).await.status(), warp::http::StatusCode::OK);
+        let response = put("2024-05-10 07:00", "scheduled").reply(&update).await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(posts.find(late_id).await.unwrap().unwrap().status, PostStatus::Draft);
+    }
+
+    /// Tests that invalid requests are answered with problem details naming every field at
+    /// fault, and unknown posts with a 404 problem.
+    #[tokio::test]
+    async fn test_error_problem_details() {
+        initialize();
+
+        let posts = Arc::new(InMemoryPostRepository::new());
+        let schedule = schedule_post_route(Arc::clone(&posts), brasilia(), clock());
+        let query = query_posts_route(Arc::clone(&posts), brasilia());
+        let delete = delete_post_route(Arc::clone(&posts));
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/schedule")
+            .json(&serde_json::json!({
+                "title": " ",
+                "content": "",
+                "scheduled_time": "tomorrow",
+            }))
+            .reply(&schedule)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_CONTENT_TYPE);
+        let fields: Vec<_> = problem(&response).errors.into_iter().map(|error| error.field).collect();
+        assert_eq!(fields, vec!["title", "content", "scheduled_time"]);
+        assert!(posts.all().is_empty());
+
+        let response = warp::test::request()
+            .path("/posts?start_date=2024-05-10&end_date=May")
+            .reply(&query)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            problem(&response).errors,
+            vec![FieldError::new("end_date", "Expected a date like 2024-05-10, not \"May\"")]
+        );
+
+        let id = bson::oid::ObjectId::new();
+        let response = warp::test::request()
+            .method("DELETE")
+            .path(&format!("/posts/{}", id.to_hex()))
+            .reply(&delete)
+            .await;
+        assert_eq!(response.status(), warp::http::StatusCode::NOT_FOUND);
+        assert_eq!(problem(&response).detail, "Post not found");
+    }
+}
@@ -4,6 +4,7 @@ import Picker from '@emoji-mart/react';
 import { Form, Input, TextArea, Button, Header, Icon, Segment, Grid, Popup, Modal } from 'semantic-ui-react';
 import 'semantic-ui-css/semantic.min.css';
 import './styles.css';
+import { readProblem } from './problem';
 
 /**
  * PostSchedulerForm Component - A form component that allows users to schedule a post.
@@ -24,6 +25,7 @@ const PostSchedulerForm = () => {
     const [modalOpen, setModalOpen] = useState(false);
     const [modalMessage, setModalMessage] = useState('');
     const [modalSuccess, setModalSuccess] = useState(true);
+    const [fieldErrors, setFieldErrors] = useState({});
 
     const showModal = (message, success = true) => {
         setModalMessage(message);
@@ -38,13 +40,14 @@ const PostSchedulerForm = () => {
     const handleSubmit = (event) => {
         event.preventDefault();
         setLoading(true);
+        setFieldErrors({});
         const formattedDate = scheduledTime.replace('T', ' ');
 
         const post = {
             title,
             content,
             scheduled_time: formattedDate,
-            status: 'pending',
+            status: 'scheduled',
         };
 
         fetch('http://localhost:8080/schedule', {
@@ -56,7 +59,9 @@ const PostSchedulerForm = () => {
         })
             .then(response => {
                 if (!response.ok) {
-                    throw new Error('Network response was not ok');
+                    return readProblem(response).then(error => {
+                        throw error;
+                    });
                 }
                 return response.text();
             })
@@ -68,7 +73,8 @@ const PostSchedulerForm = () => {
             .catch((error) => {
                 setLoading(false);
                 console.error('Error:', error);
-                showModal(`Error scheduling post: ${error.message}`);
+                setFieldErrors(error.fieldErrors || {});
+                showModal(`Error scheduling post: ${error.message}`, false);
             });
     };
 
@@ -80,6 +86,7 @@ const PostSchedulerForm = () => {
         setContent('');
         setScheduledTime('');
         setShowEmojiPicker(false);
+        setFieldErrors({});
     };
 
     /**
@@ -105,6 +112,7 @@ const PostSchedulerForm = () => {
                     placeholder="Enter post title"
                     value={title}
                     onChange={(e) => setTitle(e.target.value)}
+                    error={fieldErrors.title}
                     required
                 />
                 <Form.Field
@@ -113,6 +121,7 @@ const PostSchedulerForm = () => {
                     placeholder="Enter post content"
                     value={content}
                     onChange={(e) => setContent(e.target.value)}
+                    error={fieldErrors.content}
                     required
                 />
                 <Popup
@@ -134,6 +143,7 @@ const PostSchedulerForm = () => {
                     type="datetime-local"
                     value={scheduledTime}
                     onChange={(e) => setScheduledTime(e.target.value)}
+                    error={fieldErrors.scheduled_time}
                     required
                 />
                 <Grid>
@@ -2,6 +2,7 @@ import React, { useEffect, useState } from 'react';
 import { useLocation, useNavigate } from 'react-router-dom';
 import { Form, Button, Container, Header, Segment, Message, Loader, Icon, Grid, Modal } from 'semantic-ui-react';
 import 'semantic-ui-css/semantic.min.css';
+import { readProblem } from './problem';
 
 const UpdatePost = () => {
     const navigate = useNavigate();
@@ -15,6 +16,7 @@ const UpdatePost = () => {
     const [modalOpen, setModalOpen] = useState(false);
     const [modalMessage, setModalMessage] = useState('');
     const [modalSuccess, setModalSuccess] = useState(true);
+    const [fieldErrors, setFieldErrors] = useState({});
 
     const formatDate = (timestamp) => {
         const date = new Date(timestamp);
@@ -51,6 +53,7 @@ const UpdatePost = () => {
         if (!post) return;
 
         setLoading(true);
+        setFieldErrors({});
         const formattedDate = scheduledTime.replace('T', ' ');
 
         const updatedPost = {
@@ -70,13 +73,14 @@ const UpdatePost = () => {
                     showModal('Post updated successfully!');
                     //navigate('/', { state: { message: 'Post updated successfully!' } });
                 } else {
-                    return response.text().then(text => {
-                        throw new Error(text);
+                    return readProblem(response).then(error => {
+                        throw error;
                     });
                 }
             })
             .catch(error => {
                 console.error('Error updating post:', error);
+                setFieldErrors(error.fieldErrors || {});
                 showModal(`Failed to update post: ${error.message}`, false);
             })
             .finally(() => setLoading(false));
@@ -113,12 +117,14 @@ const UpdatePost = () => {
                         label='Title'
                         value={title}
                         onChange={(e) => setTitle(e.target.value)}
+                        error={fieldErrors.title}
                         required
                     />
                     <Form.TextArea
                         label='Content'
                         value={content}
                         onChange={(e) => setContent(e.target.value)}
+                        error={fieldErrors.content}
                         required
                     />
                     <Form.Input
@@ -126,6 +132,7 @@ const UpdatePost = () => {
                         type='datetime-local'
                         value={scheduledTime}
                         onChange={(e) => setScheduledTime(e.target.value)}
+                        error={fieldErrors.scheduled_time}
                         required
                     />
                     <Grid>
@@ -0,0 +1,21 @@
+/**
+ * Reads the problem-details body of a failed response of the backend.
+ *
+ * The backend answers errors with `application/problem+json` bodies: `detail` sums up the error, and
+ * validation errors list the fields at fault in `errors`.
+ *
+ * @param {Response} response - The failed response.
+ * @returns {Promise<Error>} An error whose message is the problem's detail, with a `fieldErrors`
+ * object mapping each field at fault (e.g. `scheduled_time`) to its message.
+ */
+export const readProblem = (response) =>
+    response
+        .json()
+        .catch(() => ({ detail: response.statusText }))
+        .then(problem => {
+            const error = new Error(problem.detail || response.statusText);
+            error.fieldErrors = Object.fromEntries(
+                (problem.errors || []).map(fieldError => [fieldError.field, fieldError.message])
+            );
+            return error;
+        });